@@ -17,11 +17,32 @@ cdh() {
     return 127
   fi
 
+  # 通用选择器（cdh pick）与多选输出给脚本 / 管道使用，不做 cd；置顶 / 书签 / daemon 等管理子命令同样直接透传
+  case "${1:-}" in
+    pick|pin|mark|unmark|marks|compact|ignore|daemon) "$bin" "$@"; return $? ;;
+  esac
+  case " $* " in
+    *" --multi "*) "$bin" "$@"; return $? ;;
+  esac
+
   sel="$("$bin" "$@")"; st=$?
   case "$st" in
     0) [ -n "$sel" ] && builtin cd -- "$sel"; return 0 ;;
     1) return 0 ;;
     2) echo "cdh: 未匹配到目录（可尝试输入关键字）" >&2; return 2 ;;
+    3) echo "cdh: 长时间无操作，已自动退出" >&2; return 0 ;;
     *) echo "cdh: 执行错误（退出码 $st）" >&2; return "$st" ;;
   esac
 }
+
+# Tab 补全：第一个参数补全书签（@name）与子命令，unmark 后补全书签名
+_cdh_complete() {
+  local cur="${COMP_WORDS[COMP_CWORD]}" words=""
+  if [ "$COMP_CWORD" -eq 1 ]; then
+    words="$(cdh marks --names 2>/dev/null) log daemon pick pin mark unmark marks compact ignore"
+  elif [ "${COMP_WORDS[1]}" = unmark ]; then
+    words="$(cdh marks --names 2>/dev/null)"
+  fi
+  COMPREPLY=($(compgen -W "$words" -- "$cur"))
+}
+complete -F _cdh_complete cdh
@@ -14,6 +14,12 @@ function cdh -d "智能 cd 历史（Rust 版 TUI）"
         return 127
     end
 
+    # 通用选择器（cdh pick）与多选输出给脚本 / 管道使用，不做 cd；置顶 / 书签 / daemon 等管理子命令同样直接透传
+    if contains -- "$argv[1]" pick pin mark unmark marks compact ignore daemon; or contains -- --multi $argv
+        $bin $argv
+        return $status
+    end
+
     set -l sel ( $bin $argv )
     set -l st $status
 
@@ -27,8 +33,16 @@ function cdh -d "智能 cd 历史（Rust 版 TUI）"
         case 2
             echo "cdh: 未匹配到目录（可尝试输入关键字）" >&2
             return 2
+        case 3
+            echo "cdh: 长时间无操作，已自动退出" >&2
+            return 0
         case "*"
             echo "cdh: 执行错误（退出码 $st）" >&2
             return $st
     end
 end
+
+# Tab 补全：第一个参数补全书签（@name）与子命令，unmark 后补全书签名
+complete -c cdh -f -n __fish_use_subcommand -a '(cdh marks --names 2>/dev/null)' -d 书签
+complete -c cdh -f -n __fish_use_subcommand -a 'log daemon pick pin mark unmark marks compact ignore'
+complete -c cdh -f -n '__fish_seen_subcommand_from unmark' -a '(cdh marks --names 2>/dev/null)' -d 书签
@@ -2,20 +2,41 @@
 //! 全局运行时上下文：汇总 Paths + Config 等信息。
 
 use crate::config::EffectiveConfig;
+use crate::ignore::IgnoreRules;
 use crate::paths::Paths;
 use std::fs;
 use std::path::Path;
+#[cfg(test)]
+use std::path::PathBuf;
+use std::sync::OnceLock;
 
 /// 程序运行时的全局上下文。
 /// - paths: 所有用到的路径（历史文件 / XDG 目录等）
-/// - config: 合并后的配置（默认值 + 环境变量，后续可加文件）
+/// - config: 合并后的配置（默认值 + 配置文件 + 环境变量）
 #[derive(Debug, Clone)]
 pub struct AppContext {
     pub paths: Paths,
     pub config: EffectiveConfig,
+    /// 记录时的忽略规则：第一次用到时读取并编译，之后整个进程复用（见 `ignore_rules`）
+    ignore: OnceLock<IgnoreRules>,
 }
 
 impl AppContext {
+    pub fn new(paths: Paths, config: EffectiveConfig) -> Self {
+        AppContext {
+            paths,
+            config,
+            ignore: OnceLock::new(),
+        }
+    }
+
+    /// 记录时的忽略规则（见 src/ignore.rs）：每个进程只加载一次，无效的规则静默跳过
+    /// （cd 钩子不往 prompt 里打印东西；`cdh ignore check` 负责报告）
+    pub fn ignore_rules(&self) -> &IgnoreRules {
+        self.ignore
+            .get_or_init(|| IgnoreRules::load(&self.paths, &self.config).0)
+    }
+
     /// 从当前进程环境构建上下文，并确保必要的目录/文件已经存在。
     pub fn init_from_process() -> Self {
         let paths = Paths::from_env();
@@ -23,9 +44,39 @@ impl AppContext {
         // 确保 XDG 目录和历史文件存在（失败时只打印 warning，不直接 panic）
         ensure_dirs_and_files(&paths);
 
-        let config = EffectiveConfig::from_env();
+        let config = EffectiveConfig::load(&paths);
 
-        AppContext { paths, config }
+        AppContext::new(paths, config)
+    }
+
+    /// 测试用：在临时目录下建一套独立的上下文（默认配置；目录都已创建，历史文件还不存在）。
+    /// 返回临时根目录，测试结束时自行删除。
+    #[cfg(test)]
+    pub(crate) fn for_test(name: &str) -> (PathBuf, AppContext) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        static SEQ: AtomicUsize = AtomicUsize::new(0);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!(
+            "cdh_test_{name}_{}_{nanos}_{}",
+            std::process::id(),
+            SEQ.fetch_add(1, Ordering::Relaxed)
+        ));
+        let paths = Paths::under(&root);
+        for dir in [
+            &paths.config_dir,
+            &paths.data_dir,
+            &paths.state_dir,
+            &paths.cache_dir,
+        ] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::create_dir_all(paths.history_raw.parent().unwrap()).unwrap();
+        (root, AppContext::new(paths, EffectiveConfig::default()))
     }
 }
 
@@ -0,0 +1,155 @@
+// src/bookmarks.rs
+//! 书签：给常用目录起一个短名字（`@api` → `~/work/platform/services/api`），不随时间衰减。
+//!
+//! 文件：DATA/bookmarks，一行一个 `<名字>\t<绝对路径>`，按名字排序；
+//! 写入复用历史锁，“临时文件 + rename”原子替换。
+//!
+//! 名字只允许字母、数字、`-`、`_`、`.`；命令行里的 `@` 前缀可写可不写。
+//! `cdh @name` 直接输出书签路径（不打开 picker）；picker 里书签条目带 `@name` 标签，也能按它搜索。
+
+use crate::history::{with_history_lock, write_lines_atomic};
+use crate::AppContext;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// 一个书签
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bookmark {
+    /// 名字（不含 `@`）
+    pub name: String,
+    /// 绝对路径
+    pub path: String,
+}
+
+/// 书签文件路径：DATA/bookmarks
+pub fn bookmarks_path(ctx: &AppContext) -> PathBuf {
+    ctx.paths.data_dir.join("bookmarks")
+}
+
+/// 去掉可选的 `@` 前缀并校验名字；不合法时返回 None
+pub fn parse_name(s: &str) -> Option<&str> {
+    let name = s.strip_prefix('@').unwrap_or(s);
+    let ok = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    ok.then_some(name)
+}
+
+/// 读取全部书签（按名字排序；不存在视为空，格式不对的行跳过）
+pub fn load(ctx: &AppContext) -> Vec<Bookmark> {
+    fs::read_to_string(bookmarks_path(ctx))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|l| {
+            let (name, path) = l.split_once('\t')?;
+            let name = parse_name(name.trim())?;
+            let path = path.trim();
+            (!path.is_empty()).then(|| Bookmark {
+                name: name.to_string(),
+                path: path.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// 按名字查路径（名字可带 `@`）
+pub fn get(ctx: &AppContext, name: &str) -> Option<String> {
+    let name = parse_name(name)?;
+    load(ctx)
+        .into_iter()
+        .find(|b| b.name == name)
+        .map(|b| b.path)
+}
+
+/// 添加或覆盖书签，返回原来指向的路径
+pub fn set(ctx: &AppContext, name: &str, path: &str) -> io::Result<Option<String>> {
+    let name = parse_name(name).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("书签名只能包含字母、数字、- _ .: {name}"),
+        )
+    })?;
+    if path.is_empty() || path.contains(['\t', '\n']) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("无效的路径: {path}"),
+        ));
+    }
+    update(ctx, |marks| {
+        let old = marks
+            .iter()
+            .position(|b| b.name == name)
+            .map(|i| marks.remove(i).path);
+        marks.push(Bookmark {
+            name: name.to_string(),
+            path: path.to_string(),
+        });
+        old
+    })
+}
+
+/// 删除书签；不存在时返回 false
+pub fn remove(ctx: &AppContext, name: &str) -> io::Result<bool> {
+    let Some(name) = parse_name(name) else {
+        return Ok(false);
+    };
+    update(ctx, |marks| {
+        let before = marks.len();
+        marks.retain(|b| b.name != name);
+        marks.len() != before
+    })
+}
+
+/// 在历史锁内读出、修改、按名字排序写回
+fn update<T>(ctx: &AppContext, f: impl FnOnce(&mut Vec<Bookmark>) -> T) -> io::Result<T> {
+    with_history_lock(ctx, || {
+        let mut marks = load(ctx);
+        let out = f(&mut marks);
+        marks.sort_by(|a, b| a.name.cmp(&b.name));
+        let lines: Vec<String> = marks
+            .iter()
+            .map(|b| format!("{}\t{}", b.name, b.path))
+            .collect();
+        write_lines_atomic(&bookmarks_path(ctx), &lines)?;
+        Ok(out)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_validated() {
+        assert_eq!(parse_name("@api"), Some("api"));
+        assert_eq!(parse_name("run-books_2.x"), Some("run-books_2.x"));
+        assert_eq!(parse_name("@"), None);
+        assert_eq!(parse_name("a b"), None);
+        assert_eq!(parse_name("a/b"), None);
+    }
+
+    #[test]
+    fn set_get_remove() {
+        let (root, ctx) = AppContext::for_test("crud");
+        assert_eq!(set(&ctx, "@web", "/w/web").unwrap(), None);
+        assert_eq!(set(&ctx, "api", "/w/api-old").unwrap(), None);
+        assert_eq!(
+            set(&ctx, "api", "/w/api").unwrap(),
+            Some("/w/api-old".into())
+        );
+        assert!(set(&ctx, "bad name", "/x").is_err());
+
+        // 按名字排序
+        let names: Vec<String> = load(&ctx).into_iter().map(|b| b.name).collect();
+        assert_eq!(names, ["api", "web"]);
+        assert_eq!(get(&ctx, "@api").as_deref(), Some("/w/api"));
+        assert_eq!(get(&ctx, "nope"), None);
+
+        assert!(remove(&ctx, "@web").unwrap());
+        assert!(!remove(&ctx, "web").unwrap());
+        assert_eq!(load(&ctx).len(), 1);
+        let _ = fs::remove_dir_all(root);
+    }
+}
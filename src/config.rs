@@ -1,26 +1,50 @@
 // src/config.rs
-//! 运行时配置：默认值 + 环境变量（后续可以再加 config.toml）
+//! 运行时配置：默认值 + 配置文件 + 环境变量
 //!
-//! 优先级设计（当前版本）：
+//! 优先级设计：
 //!   1. 内置默认值
-//!   2. 环境变量 CDH_* 覆盖
-//!   3. 最后由 CLI 参数覆盖（在 controller.rs 里做）
-//
-// 未来如果要支持 config.toml，可以在这里再加 from_file / from_env_and_file 等方法。
+//!   2. 配置文件 CONFIG/config.toml（可用 `CDH_CONFIG` 指定其他路径）
+//!   3. 环境变量 CDH_* 覆盖
+//!   4. 最后由 CLI 参数覆盖（在 controller.rs 里做）
+//!
+//! 配置文件是 TOML 的一个小子集（不额外引第三方 crate）：
+//!   - `[section]` / `[a.b]` 分节；`key = value`；`#` 注释
+//!   - value 支持字符串（"..." / '...'）、数字、true/false、以及由它们组成的数组（可跨行）
+//!
+//! 顶层键对应下面 `EffectiveConfig` 的同名字段；其余分节（如 `[keys]`）由各模块自己解释，
+//! 原样保存在 `EffectiveConfig::file` 里。
 
+use crate::frecency::{Frecency, FrecencyKind, DEFAULT_LONG_HALF_LIFE, DEFAULT_WINDOW};
+use crate::paths::Paths;
+use crate::retention::{self, Retention, DEFAULT_MAX_SIZE};
+use crate::scorer::Normalize;
 use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-/// “有效配置”——已经合并了默认值和环境变量
+/// “有效配置”——已经合并了默认值、配置文件和环境变量
 #[derive(Debug, Clone)]
 pub struct EffectiveConfig {
     /// 推荐列表最大条数（默认 20）
     pub limit: usize,
     /// Frecency 半衰期（秒），默认 7 天
     pub half_life: f64,
+    /// Frecency 模型：decay（默认）/ zoxide / window / dual
+    pub frecency_model: FrecencyKind,
+    /// dual 模型的长期半衰期（秒），默认 90 天
+    pub long_half_life: f64,
+    /// window 模型的窗口长度（秒），默认 30 天
+    pub frecency_window: f64,
     /// 最终融合分阈值（< threshold 的条目被丢弃；0 表示不启用）
     pub threshold: f64,
     /// 忽略路径的正则（默认读取 `CDH_IGNORE_RE`，解析失败则忽略）
     pub ignore_re: Option<Regex>,
+    /// 记录时忽略的路径正则（命中的目录不写入历史，见 src/ignore.rs）
+    pub log_ignore_re: Vec<String>,
+    /// 记录时忽略的 glob（另见 CONFIG/ignore）
+    pub log_ignore: Vec<String>,
     /// 是否检查目录存在性（默认 true；可用 CDH_CHECK_DIR=false 关闭）
     pub check_dir: bool,
     /// uniq 的几何衰减系数（最新=1.0，次新=decay，…；默认 0.85）
@@ -28,65 +52,725 @@ pub struct EffectiveConfig {
     /// 融合权重：frecency 与 uniq（建议和为 1.0；默认 0.7 / 0.3）
     pub w_frecency: f64,
     pub w_uniq: f64,
+    /// frecency 与 uniq 的归一化方式：minmax（默认）/ rank / log / zscore
+    pub normalize: Normalize,
+    /// 选择反馈的权重（额外加分，默认 0 不启用）
+    pub w_feedback: f64,
+    /// 位置信号（离当前目录越近越高）的权重（额外加分，默认 0 不启用）
+    pub w_proximity: f64,
+    /// 时段信号（常在这个星期几 / 几点访问）的权重（额外加分，默认 0 不启用）
+    pub w_temporal: f64,
+    /// history_raw 里的访问最多保留多久（秒；默认 0 不限制）
+    pub history_max_age: f64,
+    /// history_raw 最多保留多少行访问（默认 0 不限制）
+    pub history_max_lines: usize,
+    /// history_raw 最大字节数（默认 8M；0 不限制；支持 K / M / G 后缀）
+    pub history_max_size: u64,
+    /// 解析后的配置文件（各模块从中读取自己的分节；没有文件时为空）
+    pub file: ConfigFile,
+}
+
+impl Default for EffectiveConfig {
+    /// 内置默认值（不读文件、不读环境变量）
+    fn default() -> Self {
+        Self {
+            limit: 20,
+            half_life: 7.0 * 24.0 * 3600.0,
+            frecency_model: FrecencyKind::Decay,
+            long_half_life: DEFAULT_LONG_HALF_LIFE,
+            frecency_window: DEFAULT_WINDOW,
+            threshold: 0.0,
+            ignore_re: None,
+            log_ignore_re: Vec::new(),
+            log_ignore: Vec::new(),
+            check_dir: true,
+            uniq_decay: 0.85,
+            w_frecency: 0.7,
+            w_uniq: 0.3,
+            normalize: Normalize::MinMax,
+            w_feedback: 0.0,
+            w_proximity: 0.0,
+            w_temporal: 0.0,
+            history_max_age: 0.0,
+            history_max_lines: 0,
+            history_max_size: DEFAULT_MAX_SIZE,
+            file: ConfigFile::default(),
+        }
+    }
 }
 
 impl EffectiveConfig {
-    /// 从当前进程环境构造配置（默认值 + CDH_* 环境变量）
+    /// 从当前进程环境构造配置（默认值 + CDH_* 环境变量，不读配置文件）
     pub fn from_env() -> Self {
-        // 默认 limit = 20（原行为）
-        let limit = std::env::var("CDH_LIMIT")
-            .ok()
-            .and_then(|s| s.parse::<usize>().ok())
-            .unwrap_or(20);
+        let mut cfg = Self::default();
+        cfg.apply_env();
+        cfg
+    }
 
-        // 原 RecommendOpt::default 中的 half_life 环境逻辑
-        let half_life = std::env::var("CDH_HALF_LIFE")
-            .ok()
-            .and_then(|s| s.parse::<f64>().ok())
-            .unwrap_or(7.0 * 24.0 * 3600.0);
+    /// 默认值 → 配置文件 → 环境变量
+    ///
+    /// 配置文件不存在视为空；解析失败时打印 warning 并忽略整个文件。
+    pub fn load(paths: &Paths) -> Self {
+        let mut cfg = Self::default();
+        let path = config_file_path(paths);
+        match ConfigFile::load(&path) {
+            Ok(file) => cfg.apply_file(file),
+            Err(e) => eprintln!("cdh: 忽略配置文件 {}: {e}", path.display()),
+        }
+        cfg.apply_env();
+        cfg
+    }
 
-        // threshold 以前只有默认 0，这里顺便支持一下 CDH_THRESHOLD（可选）
-        let threshold = std::env::var("CDH_THRESHOLD")
-            .ok()
-            .and_then(|s| s.parse::<f64>().ok())
-            .unwrap_or(0.0);
+    /// 按配置构造 Frecency 模型
+    pub fn frecency(&self) -> Frecency {
+        Frecency::new(self.half_life)
+            .with_kind(self.frecency_model)
+            .with_long_half_life(self.long_half_life)
+            .with_window(self.frecency_window)
+    }
 
-        // 原 RecommendOpt::default 中的 ignore_re
-        let ignore_re = std::env::var("CDH_IGNORE_RE")
-            .ok()
-            .and_then(|re| Regex::new(&re).ok());
+    /// history_raw 的保留策略
+    pub fn retention(&self) -> Retention {
+        Retention {
+            max_age: self.history_max_age,
+            max_lines: self.history_max_lines,
+            max_size: self.history_max_size,
+        }
+    }
 
-        // 是否检查目录存在性（默认 true）
-        let check_dir = std::env::var("CDH_CHECK_DIR")
-            .ok()
-            .and_then(|s| s.parse::<bool>().ok())
-            .unwrap_or(true);
+    /// 用配置文件的顶层键覆盖默认值
+    fn apply_file(&mut self, file: ConfigFile) {
+        if let Some(v) = file.get_usize("", "limit") {
+            self.limit = v;
+        }
+        if let Some(v) = file
+            .get_f64("", "half_life")
+            .and_then(|v| positive_secs("half_life", v))
+        {
+            self.half_life = v;
+        }
+        if let Some(name) = file.get_str("", "frecency_model") {
+            match name.parse() {
+                Ok(k) => self.frecency_model = k,
+                Err(e) => eprintln!("cdh: 配置 frecency_model 无效: {e}"),
+            }
+        }
+        if let Some(v) = file
+            .get_f64("", "long_half_life")
+            .and_then(|v| positive_secs("long_half_life", v))
+        {
+            self.long_half_life = v;
+        }
+        if let Some(v) = file
+            .get_f64("", "frecency_window")
+            .and_then(|v| positive_secs("frecency_window", v))
+        {
+            self.frecency_window = v;
+        }
+        if let Some(v) = file.get_f64("", "threshold") {
+            self.threshold = v;
+        }
+        if let Some(re) = file.get_str("", "ignore_re") {
+            match Regex::new(re) {
+                Ok(rx) => self.ignore_re = Some(rx),
+                Err(e) => eprintln!("cdh: 配置 ignore_re 不是合法正则: {e}"),
+            }
+        }
+        if let Some(v) = file.get("", "log_ignore_re") {
+            // 到 `IgnoreRules::load` 再编译：不合法的正则由 `cdh ignore check` 报告
+            self.log_ignore_re = v.as_strings();
+        }
+        if let Some(v) = file.get("", "log_ignore") {
+            self.log_ignore = v.as_strings();
+        }
+        if let Some(v) = file.get_bool("", "check_dir") {
+            self.check_dir = v;
+        }
+        if let Some(v) = file.get_f64("", "uniq_decay") {
+            self.uniq_decay = v;
+        }
+        if let Some(v) = file.get_f64("", "w_frecency") {
+            self.w_frecency = v;
+        }
+        if let Some(v) = file.get_f64("", "w_uniq") {
+            self.w_uniq = v;
+        }
+        if let Some(name) = file.get_str("", "normalize") {
+            match name.parse() {
+                Ok(n) => self.normalize = n,
+                Err(e) => eprintln!("cdh: 配置 normalize 无效: {e}"),
+            }
+        }
+        if let Some(v) = file.get_f64("", "w_feedback") {
+            self.w_feedback = v;
+        }
+        if let Some(v) = file.get_f64("", "w_proximity") {
+            self.w_proximity = v;
+        }
+        if let Some(v) = file.get_f64("", "w_temporal") {
+            self.w_temporal = v;
+        }
+        if let Some(v) = file.get_f64("", "history_max_age").filter(|v| *v >= 0.0) {
+            self.history_max_age = v;
+        }
+        if let Some(v) = file.get_usize("", "history_max_lines") {
+            self.history_max_lines = v;
+        }
+        if let Some(v) = file.get("", "history_max_size") {
+            let size = match v {
+                ConfigValue::Num(n) if *n >= 0.0 => Some(*n as u64),
+                ConfigValue::Str(s) => retention::parse_size(s),
+                _ => None,
+            };
+            match size {
+                Some(n) => self.history_max_size = n,
+                None => eprintln!("cdh: 配置 history_max_size 无效: {v:?}"),
+            }
+        }
+        self.file = file;
+    }
 
-        // 原 RecommendOpt::default 中的三个权重相关 env
-        let w_frecency = std::env::var("CDH_W_FRECENCY")
+    /// 用 CDH_* 环境变量覆盖（解析失败的值忽略）
+    fn apply_env(&mut self) {
+        if let Some(v) = env_parse("CDH_LIMIT") {
+            self.limit = v;
+        }
+        if let Some(v) = env_parse("CDH_HALF_LIFE").and_then(|v| positive_secs("CDH_HALF_LIFE", v))
+        {
+            self.half_life = v;
+        }
+        if let Some(v) = env_parse("CDH_FRECENCY_MODEL") {
+            self.frecency_model = v;
+        }
+        if let Some(v) =
+            env_parse("CDH_LONG_HALF_LIFE").and_then(|v| positive_secs("CDH_LONG_HALF_LIFE", v))
+        {
+            self.long_half_life = v;
+        }
+        if let Some(v) =
+            env_parse("CDH_FRECENCY_WINDOW").and_then(|v| positive_secs("CDH_FRECENCY_WINDOW", v))
+        {
+            self.frecency_window = v;
+        }
+        if let Some(v) = env_parse("CDH_THRESHOLD") {
+            self.threshold = v;
+        }
+        if let Some(rx) = std::env::var("CDH_IGNORE_RE")
             .ok()
-            .and_then(|s| s.parse::<f64>().ok())
-            .unwrap_or(0.7);
-
-        let w_uniq = std::env::var("CDH_W_UNIQ")
+            .and_then(|re| Regex::new(&re).ok())
+        {
+            self.ignore_re = Some(rx);
+        }
+        if let Ok(re) = std::env::var("CDH_LOG_IGNORE_RE") {
+            self.log_ignore_re = if re.is_empty() { Vec::new() } else { vec![re] };
+        }
+        if let Ok(v) = std::env::var("CDH_LOG_IGNORE") {
+            self.log_ignore = v
+                .split(':')
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(v) = env_parse("CDH_CHECK_DIR") {
+            self.check_dir = v;
+        }
+        if let Some(v) = env_parse("CDH_W_FRECENCY") {
+            self.w_frecency = v;
+        }
+        if let Some(v) = env_parse("CDH_W_UNIQ") {
+            self.w_uniq = v;
+        }
+        if let Some(v) = env_parse("CDH_NORMALIZE") {
+            self.normalize = v;
+        }
+        if let Some(v) = env_parse("CDH_W_FEEDBACK") {
+            self.w_feedback = v;
+        }
+        if let Some(v) = env_parse("CDH_W_PROXIMITY") {
+            self.w_proximity = v;
+        }
+        if let Some(v) = env_parse("CDH_W_TEMPORAL") {
+            self.w_temporal = v;
+        }
+        if let Some(v) = env_parse("CDH_UNIQ_DECAY") {
+            self.uniq_decay = v;
+        }
+        if let Some(v) = env_parse::<f64>("CDH_HISTORY_MAX_AGE").filter(|v| *v >= 0.0) {
+            self.history_max_age = v;
+        }
+        if let Some(v) = env_parse("CDH_HISTORY_MAX_LINES") {
+            self.history_max_lines = v;
+        }
+        if let Some(v) = std::env::var("CDH_HISTORY_MAX_SIZE")
             .ok()
-            .and_then(|s| s.parse::<f64>().ok())
-            .unwrap_or(0.3);
+            .and_then(|s| retention::parse_size(&s))
+        {
+            self.history_max_size = v;
+        }
+    }
+}
 
-        let uniq_decay = std::env::var("CDH_UNIQ_DECAY")
-            .ok()
-            .and_then(|s| s.parse::<f64>().ok())
-            .unwrap_or(0.85);
+/// 半衰期 / 窗口这类秒数必须是大于 0 的有限数字（否则 `Frecency::new` 会 panic）：不合法时警告并忽略
+fn positive_secs(name: &str, v: f64) -> Option<f64> {
+    if v.is_finite() && v > 0.0 {
+        Some(v)
+    } else {
+        eprintln!("cdh: {name} 必须是大于 0 的有限数字，忽略 {v}");
+        None
+    }
+}
 
-        Self {
-            limit,
-            half_life,
-            threshold,
-            ignore_re,
-            check_dir,
-            uniq_decay,
-            w_frecency,
-            w_uniq,
+fn env_parse<T: FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|s| s.parse::<T>().ok())
+}
+
+/// 配置文件路径：`CDH_CONFIG` 或 CONFIG/config.toml
+pub fn config_file_path(paths: &Paths) -> PathBuf {
+    match std::env::var("CDH_CONFIG") {
+        Ok(p) if !p.is_empty() => PathBuf::from(p),
+        _ => paths.config_dir.join("config.toml"),
+    }
+}
+
+// ---------------- 配置文件（TOML 子集） ----------------
+
+/// 配置文件里的一个值
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValue {
+    Str(String),
+    Num(f64),
+    Bool(bool),
+    List(Vec<ConfigValue>),
+}
+
+impl ConfigValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ConfigValue::Str(s) => Some(s),
+            _ => None,
         }
     }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ConfigValue::Num(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ConfigValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// 字符串或字符串数组统一成列表（数组里的非字符串元素忽略）
+    pub fn as_strings(&self) -> Vec<String> {
+        match self {
+            ConfigValue::Str(s) => vec![s.clone()],
+            ConfigValue::List(items) => items
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// 解析后的配置文件：分节名（顶层为 ""）→ 按出现顺序排列的键值
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFile {
+    sections: BTreeMap<String, Vec<(String, ConfigValue)>>,
+}
+
+impl ConfigFile {
+    /// 读取并解析；文件不存在时返回空配置
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// 解析文本；出错时返回 “第 N 行: 原因”
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut out = Self::default();
+        let mut section = String::new();
+        let mut lines = text.lines().enumerate();
+
+        while let Some((i, line)) = lines.next() {
+            let lineno = i + 1;
+            let line = strip_comment(line);
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(rest) = line.strip_prefix('[') {
+                let name = rest
+                    .strip_suffix(']')
+                    .ok_or_else(|| format!("第 {lineno} 行: 分节缺少 ]"))?;
+                section = name.trim().to_string();
+                out.sections.entry(section.clone()).or_default();
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("第 {lineno} 行: 需要 key = value"))?;
+            let key = unquote_key(key.trim());
+            if key.is_empty() {
+                return Err(format!("第 {lineno} 行: 键名为空"));
+            }
+
+            // 数组可以跨行：一直读到方括号配平
+            let mut value = value.trim().to_string();
+            while value.starts_with('[') && !brackets_balanced(&value) {
+                let Some((_, next)) = lines.next() else {
+                    return Err(format!("第 {lineno} 行: 数组缺少 ]"));
+                };
+                value.push(' ');
+                value.push_str(strip_comment(next).trim());
+            }
+
+            let v = parse_value(&value).map_err(|e| format!("第 {lineno} 行: {e}"))?;
+            let entries = out.sections.entry(section.clone()).or_default();
+            entries.retain(|(k, _)| *k != key);
+            entries.push((key, v));
+        }
+        Ok(out)
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&ConfigValue> {
+        self.sections
+            .get(section)?
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// 某分节下的全部键值（按文件中出现的顺序）
+    pub fn section(&self, section: &str) -> &[(String, ConfigValue)] {
+        self.sections.get(section).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn get_str(&self, section: &str, key: &str) -> Option<&str> {
+        self.get(section, key)?.as_str()
+    }
+
+    pub fn get_f64(&self, section: &str, key: &str) -> Option<f64> {
+        self.get(section, key)?.as_f64()
+    }
+
+    pub fn get_bool(&self, section: &str, key: &str) -> Option<bool> {
+        self.get(section, key)?.as_bool()
+    }
+
+    pub fn get_usize(&self, section: &str, key: &str) -> Option<usize> {
+        let n = self.get_f64(section, key)?;
+        (n >= 0.0 && n.fract() == 0.0).then_some(n as usize)
+    }
+}
+
+/// 去掉行尾注释（引号内的 # 保留）
+fn strip_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' => return &line[..i],
+            None => {}
+        }
+    }
+    line
+}
+
+fn unquote_key(key: &str) -> String {
+    for q in ['"', '\''] {
+        if let Some(inner) = key.strip_prefix(q).and_then(|k| k.strip_suffix(q)) {
+            return inner.to_string();
+        }
+    }
+    key.to_string()
+}
+
+fn brackets_balanced(s: &str) -> bool {
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in s.chars() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                _ => {}
+            },
+        }
+    }
+    depth <= 0
+}
+
+fn parse_value(s: &str) -> Result<ConfigValue, String> {
+    let mut p = ValueParser {
+        chars: s.chars().collect(),
+        pos: 0,
+    };
+    let v = p.value()?;
+    p.skip_ws();
+    if p.pos < p.chars.len() {
+        return Err(format!("多余的内容: {}", p.rest()));
+    }
+    Ok(v)
+}
+
+/// 逐字符解析一个值（字符串 / 数字 / 布尔 / 数组）
+struct ValueParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl ValueParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn rest(&self) -> String {
+        self.chars[self.pos..].iter().collect()
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self) -> Result<ConfigValue, String> {
+        self.skip_ws();
+        match self.peek() {
+            Some('"') => self.basic_string().map(ConfigValue::Str),
+            Some('\'') => self.literal_string().map(ConfigValue::Str),
+            Some('[') => self.array(),
+            Some(_) => self.bare(),
+            None => Err("缺少值".to_string()),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut out = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '"' => return Ok(out),
+                '\\' => {
+                    let e = self.peek().ok_or("字符串未结束")?;
+                    self.pos += 1;
+                    out.push(match e {
+                        'n' => '\n',
+                        't' => '\t',
+                        '\\' => '\\',
+                        '"' => '"',
+                        other => return Err(format!("不支持的转义 \\{other}")),
+                    });
+                }
+                c => out.push(c),
+            }
+        }
+        Err("字符串未结束".to_string())
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut out = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if c == '\'' {
+                return Ok(out);
+            }
+            out.push(c);
+        }
+        Err("字符串未结束".to_string())
+    }
+
+    fn array(&mut self) -> Result<ConfigValue, String> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_ws();
+            match self.peek() {
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(ConfigValue::List(items));
+                }
+                None => return Err("数组缺少 ]".to_string()),
+                _ => {}
+            }
+            items.push(self.value()?);
+            self.skip_ws();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {}
+                _ => return Err("数组元素之间需要逗号".to_string()),
+            }
+        }
+    }
+
+    /// 裸值：true / false / 数字（允许 1_000 这样的下划线）
+    fn bare(&mut self) -> Result<ConfigValue, String> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && c != ',' && c != ']')
+        {
+            self.pos += 1;
+        }
+        let tok: String = self.chars[start..self.pos].iter().collect();
+        match tok.as_str() {
+            "true" => Ok(ConfigValue::Bool(true)),
+            "false" => Ok(ConfigValue::Bool(false)),
+            _ => tok
+                .replace('_', "")
+                .parse::<f64>()
+                .map(ConfigValue::Num)
+                .map_err(|_| format!("无法识别的值: {tok}（字符串需要加引号）")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_sections_values_and_comments() {
+        let text = r##"
+# 顶层
+limit = 30
+half_life = 86_400.5   # 行尾注释
+check_dir = false
+ignore_re = '\.git($|/)'
+
+[keys]
+preset = "vi"
+quit = ["q", "ctrl-c"]
+page-next = [
+  "l",   # 跨行数组
+  "#",
+]
+
+[keys.search]
+"up" = "ctrl-k"
+"##;
+        let f = ConfigFile::parse(text).unwrap();
+        assert_eq!(f.get_usize("", "limit"), Some(30));
+        assert_eq!(f.get_f64("", "half_life"), Some(86400.5));
+        assert_eq!(f.get_bool("", "check_dir"), Some(false));
+        assert_eq!(f.get_str("", "ignore_re"), Some(r"\.git($|/)"));
+        assert_eq!(f.get_str("keys", "preset"), Some("vi"));
+        assert_eq!(
+            f.get("keys", "quit").unwrap().as_strings(),
+            vec!["q", "ctrl-c"]
+        );
+        assert_eq!(
+            f.get("keys", "page-next").unwrap().as_strings(),
+            vec!["l", "#"]
+        );
+        assert_eq!(f.get_str("keys.search", "up"), Some("ctrl-k"));
+        let keys: Vec<&str> = f.section("keys").iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["preset", "quit", "page-next"]);
+    }
+
+    #[test]
+    fn parse_errors_report_line_numbers() {
+        let err = ConfigFile::parse("limit = 1\nfoo = bar\n").unwrap_err();
+        assert!(err.starts_with("第 2 行"), "{err}");
+        assert!(ConfigFile::parse("[keys\n").is_err());
+        assert!(ConfigFile::parse("a = [1, 2\n").is_err());
+        assert!(ConfigFile::parse("a = \"x\n").is_err());
+    }
+
+    #[test]
+    fn file_values_override_defaults() {
+        let file = ConfigFile::parse("limit = 5\nw_uniq = 0.5\n").unwrap();
+        let mut cfg = EffectiveConfig::default();
+        cfg.apply_file(file);
+        assert_eq!(cfg.limit, 5);
+        assert_eq!(cfg.w_uniq, 0.5);
+        assert_eq!(cfg.w_frecency, 0.7);
+        assert_eq!(cfg.file.get_usize("", "limit"), Some(5));
+    }
+
+    #[test]
+    fn frecency_model_from_file() {
+        let file = ConfigFile::parse(
+            "frecency_model = \"dual\"\nlong_half_life = 1_000\nfrecency_window = -1\n",
+        )
+        .unwrap();
+        let mut cfg = EffectiveConfig::default();
+        cfg.apply_file(file);
+        assert_eq!(cfg.frecency_model, FrecencyKind::Dual);
+        assert_eq!(cfg.long_half_life, 1000.0);
+        // 非正数忽略，保留默认值
+        assert_eq!(cfg.frecency_window, DEFAULT_WINDOW);
+        assert_eq!(cfg.frecency().kind(), FrecencyKind::Dual);
+    }
+
+    #[test]
+    fn invalid_half_life_keeps_default() {
+        for bad in ["0", "-5", "1e400"] {
+            let file =
+                ConfigFile::parse(&format!("half_life = {bad}\nlong_half_life = {bad}\n")).unwrap();
+            let mut cfg = EffectiveConfig::default();
+            cfg.apply_file(file);
+            assert_eq!(cfg.half_life, EffectiveConfig::default().half_life, "{bad}");
+            assert_eq!(cfg.long_half_life, DEFAULT_LONG_HALF_LIFE, "{bad}");
+            // 不会 panic
+            let _ = cfg.frecency();
+        }
+    }
+
+    #[test]
+    fn retention_from_file() {
+        let file = ConfigFile::parse(
+            "history_max_age = 7_776_000\nhistory_max_lines = 50_000\nhistory_max_size = \"4M\"\n",
+        )
+        .unwrap();
+        let mut cfg = EffectiveConfig::default();
+        // 默认只限制大小
+        let r = cfg.retention();
+        assert_eq!(
+            (r.max_age, r.max_lines, r.max_size),
+            (0.0, 0, DEFAULT_MAX_SIZE)
+        );
+        cfg.apply_file(file);
+        let r = cfg.retention();
+        assert_eq!(r.max_age, 7_776_000.0);
+        assert_eq!(r.max_lines, 50_000);
+        assert_eq!(r.max_size, 4 << 20);
+    }
+
+    #[test]
+    fn log_ignore_rules_from_file() {
+        let file = ConfigFile::parse(
+            "log_ignore_re = ['^/tmp(/|$)', '(']\nlog_ignore = [\"node_modules\", \"/mnt/secrets\"]\n",
+        )
+        .unwrap();
+        let mut cfg = EffectiveConfig::default();
+        cfg.apply_file(file);
+        // 原样保留，编译（和报错）留给 IgnoreRules
+        assert_eq!(cfg.log_ignore_re, ["^/tmp(/|$)", "("]);
+        assert_eq!(cfg.log_ignore, ["node_modules", "/mnt/secrets"]);
+    }
 }
@@ -1,26 +1,47 @@
+use crate::bookmarks::{self, Bookmark};
+#[cfg(unix)]
+use crate::daemon;
+use crate::feedback::{self, Selection};
 use crate::history; // 历史子系统
-use crate::picker;
+use crate::ignore::{self, IgnoreRules};
+use crate::picker::layout::Height;
+use crate::picker::{
+    self, Columns, Keymap, LayoutOpt, PathDisplay, PickEffect, PickItem, PickOpt, PickOutcome,
+    Theme, Watchdog,
+};
+use crate::pins;
+use crate::queries;
+use crate::retention;
+use crate::temporal;
 use crate::AppContext;
-use crate::{recommend_paths, RecommendOpt};
+use crate::{recommend, RecommendOpt, Recommendation};
 
 use regex::Regex;
 use std::env;
-use std::io::{self, Write};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
 
 /// 运行控制器：
 /// - 默认模式：推荐 + 选择（交互选目录）
 /// - 子命令：`cdh log --dir <path>` 追加历史日志
+/// - 子命令：`cdh daemon [run|stop|status]` 常驻进程（可选）
+/// - 子命令：`cdh pick` 从 stdin 读候选行，用同一个 TUI 选择（通用选择器）
+/// - 子命令：`cdh pin [add|rm|ls]` 管理置顶目录
+/// - 子命令：`cdh mark <name> [path]` / `cdh unmark <name>` / `cdh marks` 管理书签；`cdh @name` 直接跳转
+/// - 子命令：`cdh compact` 按保留策略修剪 history_raw
+/// - 子命令：`cdh ignore [check|purge]` 检查记录时的忽略规则 / 把规则应用到已有历史
 ///
 /// 退出码：
 ///   - 0：成功（选中 或 log 成功）
 ///   - 1：错误 / 用户取消 / log 失败
 ///   - 2：无可用候选
+///   - 3：picker 打开后一直没有操作，超时退出
 pub fn run(ctx: &AppContext) -> i32 {
     run_with_args(ctx, env::args().skip(1))
 }
 
 fn run_with_args(ctx: &AppContext, args: impl Iterator<Item = String>) -> i32 {
-    // 0) 先看看是不是子命令：cdh log ...
+    // 0) 先看看是不是子命令：cdh log ... / cdh daemon ...
     let mut args = args.peekable();
 
     if let Some(cmd) = args.peek() {
@@ -29,25 +50,63 @@ fn run_with_args(ctx: &AppContext, args: impl Iterator<Item = String>) -> i32 {
             args.next();
             return run_log_subcommand(ctx, args);
         }
+        if cmd == "daemon" {
+            args.next();
+            return run_daemon_subcommand(ctx, args);
+        }
+        if cmd == "pick" {
+            args.next();
+            return run_pick_subcommand(ctx, args);
+        }
+        if cmd == "pin" {
+            args.next();
+            return run_pin_subcommand(ctx, args);
+        }
+        if matches!(cmd.as_str(), "mark" | "unmark" | "marks") {
+            let cmd = args.next().unwrap_or_default();
+            return run_bookmark_subcommand(ctx, &cmd, args);
+        }
+        if cmd == "compact" {
+            args.next();
+            return run_compact_subcommand(ctx, args);
+        }
+        if cmd == "ignore" {
+            args.next();
+            return run_ignore_subcommand(ctx, args);
+        }
     }
 
     // 1) 默认模式：构造 RecommendOpt
-    let mut opt = RecommendOpt::default();
-
     // 1.1 用全局 Paths 覆盖历史文件路径（由 XDG 解析出来）
-    opt.raw = ctx.paths.history_raw.to_string_lossy().into_owned();
-    opt.uniq = ctx.paths.history_uniq.to_string_lossy().into_owned();
+    let mut opt = RecommendOpt {
+        raw: ctx.paths.history_raw.to_string_lossy().into_owned(),
+        uniq: ctx.paths.history_uniq.to_string_lossy().into_owned(),
+        ..RecommendOpt::default()
+    };
 
     // 1.2 用全局配置覆盖算法参数（ENV + 配置文件已经合并到 ctx.config 里）
     let cfg = &ctx.config;
+    opt.feedback = feedback::feedback_path(ctx).to_string_lossy().into_owned();
     opt.limit = cfg.limit;
     opt.half_life = cfg.half_life;
+    opt.frecency_model = cfg.frecency_model;
+    opt.long_half_life = cfg.long_half_life;
+    opt.frecency_window = cfg.frecency_window;
     opt.threshold = cfg.threshold;
     opt.ignore_re = cfg.ignore_re.clone();
     opt.check_dir = cfg.check_dir;
     opt.uniq_decay = cfg.uniq_decay;
     opt.w_frecency = cfg.w_frecency;
     opt.w_uniq = cfg.w_uniq;
+    opt.normalize = cfg.normalize;
+    opt.w_feedback = cfg.w_feedback;
+    opt.w_proximity = cfg.w_proximity;
+    opt.pins = pins::load_pins(ctx);
+    opt.cwd = current_dir();
+    opt.w_temporal = cfg.w_temporal;
+    if opt.w_temporal > 0.0 {
+        opt.utc_offset = temporal::local_utc_offset();
+    }
 
     // 2) 解析命令行（仅覆盖必要项；其余用 config/默认）
     // 支持：
@@ -57,8 +116,18 @@ fn run_with_args(ctx: &AppContext, args: impl Iterator<Item = String>) -> i32 {
     //      --threshold <f>  评分阈值
     //      --ignore-re <re> 忽略路径正则
     //      --no-check-dir   不检查目录是否存在
+    //      --height <h>     picker 高度：auto / full / 行数 / 百分比（后两者为内联模式）
+    //      --multi          多选：Space/Tab 标记，输出全部选中路径（每行一个）
+    //      --print0         多选输出改用 NUL 分隔
+    //      --timeout <sec>  无操作超时（秒，0 / off 关闭）
     //      --help, -h       显示帮助
     //  其余位置参数作为 tokens 参与过滤（大小写不敏感子串）
+    let mut layout = LayoutOpt::from_config(&ctx.config.file);
+    let mut watchdog = Watchdog::from_config(&ctx.config.file);
+    let mut multi = false;
+    let mut print0 = false;
+    // `@name`：参数全部解析完再跳转，后面的选项（如 --no-check-dir）同样生效
+    let mut jump: Option<String> = None;
     let mut args = args; // 复用上面的迭代器（已经消耗/判断过 log 子命令）
     while let Some(a) = args.next() {
         match a.as_str() {
@@ -104,11 +173,37 @@ fn run_with_args(ctx: &AppContext, args: impl Iterator<Item = String>) -> i32 {
             "--no-check-dir" => {
                 opt.check_dir = false;
             }
+            "--multi" => multi = true,
+            "--print0" => print0 = true,
+            "--height" => {
+                let v = args.next().unwrap_or_default();
+                match Height::parse(&v) {
+                    Some(h) => layout.height = h,
+                    None => {
+                        eprintln!("cdh: --height 取值为 auto / full / 行数 / 百分比（如 15、40%）");
+                        return 1;
+                    }
+                }
+            }
+            "--timeout" => match parse_timeout_arg(args.next()) {
+                Some(w) => watchdog = w,
+                None => {
+                    eprintln!("cdh: --timeout 取值为秒数（0 或 off 关闭）");
+                    return 1;
+                }
+            },
             "--help" | "-h" => {
                 eprintln!(
                     "用法:
   cdh [选项] [关键字...]      # 交互选择历史目录（默认模式）
   cdh log --dir <path>       # 记录一次目录访问（供 shell hook 使用）
+  cdh daemon [run|stop|status]  # 可选常驻进程：内存索引，加速 log/查询
+  cdh pick [--multi] [--print0] [--height <h>] [--timeout <sec>]  # 从 stdin 读取候选行并选择（通用选择器）
+  cdh pin [add|rm|ls] [path...]  # 管理置顶目录（总是排在推荐列表最前）
+  cdh mark <name> [path]     # 添加书签（默认当前目录）；cdh unmark <name> 删除，cdh marks 列出
+  cdh @name                  # 直接输出书签指向的目录（不打开 picker）
+  cdh compact [--max-age <sec>] [--max-lines <N>] [--max-size <bytes>]  # 按保留策略修剪历史
+  cdh ignore [check <path...>|purge [--dry-run]]  # 检查忽略规则 / 从已有历史里删掉被忽略的目录
 
 选项:
   -v, --version          显示版本并退出
@@ -117,11 +212,21 @@ fn run_with_args(ctx: &AppContext, args: impl Iterator<Item = String>) -> i32 {
       --threshold <f64>  融合分阈值（默认 0，可用 CDH_THRESHOLD 覆盖）
       --ignore-re <re>   忽略路径正则（默认取 ENV:CDH_IGNORE_RE）
       --no-check-dir     不检查目录是否存在（默认检查，可用 CDH_CHECK_DIR=false 关闭）
+      --height <h>       picker 高度：auto / full，或行数 / 百分比（如 15、40%）以内联模式
+                         显示在提示符下方，不切换备用屏幕（可用 CDH_HEIGHT 覆盖）
+      --multi            多选：Space / Tab 标记，Enter 按标记顺序输出全部路径（每行一个）
+      --print0           多选输出改用 NUL 分隔（配合 xargs -0）
+      --timeout <sec>    打开后多久没有按键就退出（默认 15，0 / off 关闭；退出码 3，
+                         可用 CDH_TIMEOUT 覆盖）
 
   其余位置参数作为过滤关键字（大小写不敏感，命中任一即可）"
                 );
                 return 0;
             }
+            // `@name`：有这个书签时直接输出路径，不打开 picker；没有时当普通关键字
+            _ if jump.is_none() && a.starts_with('@') && bookmarks::get(ctx, &a).is_some() => {
+                jump = Some(a);
+            }
             _ => {
                 // 关键字过滤 token
                 opt.tokens.push(a);
@@ -129,92 +234,219 @@ fn run_with_args(ctx: &AppContext, args: impl Iterator<Item = String>) -> i32 {
         }
     }
 
-    // 3) 计算推荐路径（推荐算法完全由 recommend_paths 控制）
-    let paths = recommend_paths(&opt);
-    if paths.is_empty() {
+    if let Some(name) = jump {
+        return jump_to_bookmark(ctx, &name, opt.check_dir);
+    }
+
+    // 3) 计算推荐（daemon 在跑时直接用它的内存索引，否则读历史文件）；分数等信息留给 picker 显示
+    let items: Vec<PickItem> = query_recommendations(ctx, &opt)
+        .into_iter()
+        .map(PickItem::from)
+        .collect();
+    let items = with_bookmarks(items, bookmarks::load(ctx), &opt);
+    if items.is_empty() {
         return 2;
     }
 
+    // 3.1 置顶已经由推荐排在最前；这里再把降级的挪到最后
+    let pinned = opt.pins;
+    let demoted = pins::load_demoted(ctx);
+    let items = pins::apply(items, &pinned, &demoted);
+
     // 4) 打开 TUI 选择（非交互环境时 picker 会直接返回第一项）
-    match picker::pick(&paths) {
-        Ok(Some(sel)) => {
-            // 与 Fish 集成友好：不换行，避免命令替换多出 \n
-            print!("{sel}");
-            let _ = io::stdout().flush();
+    let pick_opt = PickOpt {
+        actions: true,
+        pinned,
+        demoted,
+        keymap: Keymap::from_config(&ctx.config.file),
+        theme: Theme::from_config(&ctx.config.file),
+        layout,
+        columns: Columns::from_config(&ctx.config.file),
+        display: PathDisplay::from_config(&ctx.config.file),
+        multi,
+        keys_from_tty: false,
+        queries: queries::load(ctx),
+        watchdog,
+    };
+    let outcome = picker::pick_items(items, &pick_opt).map(|o| {
+        // 条目操作即使取消选择也要落盘
+        apply_pick_effects(ctx, &o.effects);
+        if !o.query.is_empty() && !o.selected.is_empty() {
+            if let Err(e) = queries::record(ctx, &o.query, &o.selected) {
+                eprintln!("cdh: 保存搜索历史失败: {e}");
+            }
+        }
+        record_feedback(ctx, &o);
+        o
+    });
+    match outcome {
+        Ok(o) if o.timed_out => 3,
+        Ok(o) if o.selected.is_empty() => 1, // 用户取消
+        Ok(o) => {
+            // 单选与 Fish 集成友好：不换行，避免命令替换多出 \n
+            let sep = multi.then_some(if print0 { '\0' } else { '\n' });
+            print_selected(&o.selected, sep);
             0
         }
-        Ok(None) => 1, // 用户取消/超时
-        Err(_e) => 1,  // 渲染异常等
+        Err(_e) => 1, // 渲染异常等
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{EffectiveConfig, Paths};
-    use std::fs;
-    use std::path::PathBuf;
-    use std::time::{SystemTime, UNIX_EPOCH};
+/// 当前目录：优先 `$PWD`（保留符号链接，和 shell 里看到的一致），否则取进程工作目录
+fn current_dir() -> Option<String> {
+    env::var("PWD")
+        .ok()
+        .filter(|p| Path::new(p).is_absolute())
+        .or_else(|| {
+            env::current_dir()
+                .ok()
+                .map(|p| p.to_string_lossy().into_owned())
+        })
+}
 
-    fn test_ctx(name: &str) -> (PathBuf, AppContext) {
-        let uniq = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        let root = std::env::temp_dir().join(format!("cdh_controller_test_{name}_{uniq}"));
-        let paths = Paths {
-            config_dir: root.join("config"),
-            data_dir: root.join("data"),
-            state_dir: root.join("state"),
-            cache_dir: root.join("cache"),
-            history_raw: root.join("data").join("history").join("history_raw"),
-            history_uniq: root.join("data").join("history").join("history_uniq"),
-        };
-        fs::create_dir_all(paths.history_raw.parent().unwrap()).unwrap();
-        fs::write(&paths.history_raw, format!("1\t{}\n", root.display())).unwrap();
-        fs::write(&paths.history_uniq, format!("{}\n", root.display())).unwrap();
-        (
-            root,
-            AppContext {
-                paths,
-                config: EffectiveConfig {
-                    limit: 20,
-                    half_life: 7.0 * 24.0 * 3600.0,
-                    threshold: 0.0,
-                    ignore_re: None,
-                    check_dir: false,
-                    uniq_decay: 0.85,
-                    w_frecency: 0.7,
-                    w_uniq: 0.3,
-                },
+/// 单选时把“选了第几行、跳过了哪些”记入选择反馈；失败只打印 warning
+fn record_feedback(ctx: &AppContext, o: &PickOutcome) {
+    let (Some(rank), [path]) = (o.rank, o.selected.as_slice()) else {
+        return;
+    };
+    let sel = Selection {
+        path: path.clone(),
+        rank,
+        query: o.query.clone(),
+        passed_over: o.passed_over.clone(),
+    };
+    if let Err(e) = feedback::record(ctx, &sel) {
+        eprintln!("cdh: 保存选择反馈失败: {e}");
+    }
+}
+
+/// `--timeout` 的取值：秒数，`0` / `off` 关闭
+fn parse_timeout_arg(v: Option<String>) -> Option<Watchdog> {
+    v.as_deref().and_then(Watchdog::parse)
+}
+
+/// 输出选中结果：每项后跟 `sep`（换行或 NUL，按选择顺序）；`sep` 为 None 时只输出第一项且不带换行
+fn print_selected(sel: &[String], sep: Option<char>) {
+    let mut out = io::stdout().lock();
+    match sep {
+        Some(sep) => {
+            for s in sel {
+                let _ = write!(out, "{s}{sep}");
+            }
+        }
+        None => {
+            if let Some(s) = sel.first() {
+                let _ = write!(out, "{s}");
+            }
+        }
+    }
+    let _ = out.flush();
+}
+
+/// 处理子命令：`cdh pick`——从 stdin 读取任意候选行（空行忽略），用同一个 TUI 选择
+///
+/// 键盘输入走 /dev/tty（stdin 已被候选占用），界面仍画在 stderr 上；
+/// 选中结果输出到 stdout，每项后跟换行（`--print0` 时为 NUL）。
+/// 退出码与默认模式一致：0 选中 / 1 取消 / 2 stdin 没有候选 / 3 超时。
+fn run_pick_subcommand(ctx: &AppContext, mut args: impl Iterator<Item = String>) -> i32 {
+    let mut layout = LayoutOpt::from_config(&ctx.config.file);
+    let mut watchdog = Watchdog::from_config(&ctx.config.file);
+    let mut multi = false;
+    let mut print0 = false;
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--multi" => multi = true,
+            "--print0" => print0 = true,
+            "--height" => {
+                let v = args.next().unwrap_or_default();
+                match Height::parse(&v) {
+                    Some(h) => layout.height = h,
+                    None => {
+                        eprintln!(
+                            "cdh pick: --height 取值为 auto / full / 行数 / 百分比（如 15、40%）"
+                        );
+                        return 1;
+                    }
+                }
+            }
+            "--timeout" => match parse_timeout_arg(args.next()) {
+                Some(w) => watchdog = w,
+                None => {
+                    eprintln!("cdh pick: --timeout 取值为秒数（0 或 off 关闭）");
+                    return 1;
+                }
             },
-        )
+            "--help" | "-h" => {
+                eprintln!(
+                    "用法: <命令> | cdh pick [--multi] [--print0] [--height <h>] [--timeout <sec>]
+
+从 stdin 读取候选（一行一个），交互选择后输出到 stdout。
+
+示例:
+  git branch --format='%(refname:short)' | cdh pick | xargs git switch
+  tmux ls -F '#S' | cdh pick | xargs tmux attach -t
+  ls | cdh pick --multi --print0 | xargs -0 rm -i"
+                );
+                return 0;
+            }
+            other => {
+                eprintln!("cdh pick: 未知的参数: {other}");
+                return 1;
+            }
+        }
     }
 
-    #[test]
-    fn half_life_zero_returns_error_instead_of_panicking() {
-        let (root, ctx) = test_ctx("half_life_zero");
-        let status = run_with_args(
-            &ctx,
-            ["--half-life", "0", "--no-check-dir"]
-                .into_iter()
-                .map(String::from),
-        );
-        assert_eq!(status, 1);
-        let _ = fs::remove_dir_all(root);
+    let items: Vec<PickItem> = io::stdin()
+        .lock()
+        .lines()
+        .map_while(Result::ok)
+        .map(|l| l.trim_end_matches('\r').to_string())
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| PickItem::from(l.as_str()))
+        .collect();
+    if items.is_empty() {
+        return 2;
     }
 
-    #[test]
-    fn half_life_negative_returns_error_instead_of_panicking() {
-        let (root, ctx) = test_ctx("half_life_negative");
-        let status = run_with_args(
-            &ctx,
-            ["--half-life", "-1", "--no-check-dir"]
-                .into_iter()
-                .map(String::from),
-        );
-        assert_eq!(status, 1);
-        let _ = fs::remove_dir_all(root);
+    // 候选不一定是路径：不做 ~ 缩写，也没有条目操作与附加列
+    let pick_opt = PickOpt {
+        keymap: Keymap::from_config(&ctx.config.file),
+        theme: Theme::from_config(&ctx.config.file),
+        layout,
+        multi,
+        keys_from_tty: true,
+        watchdog,
+        ..PickOpt::default()
+    };
+    match picker::pick_items(items, &pick_opt) {
+        Ok(sel) if sel.timed_out => 3,
+        Ok(sel) if sel.selected.is_empty() => 1,
+        Ok(sel) => {
+            print_selected(&sel.selected, Some(if print0 { '\0' } else { '\n' }));
+            0
+        }
+        Err(e) => {
+            eprintln!("cdh pick: {e}");
+            1
+        }
+    }
+}
+
+/// 把 picker 里的条目操作落盘；失败只打印 warning，不影响选择结果
+fn apply_pick_effects(ctx: &AppContext, effects: &[PickEffect]) {
+    for eff in effects {
+        let res = match eff {
+            PickEffect::Delete(p) => {
+                history::remove_path(ctx, p).and_then(|_| pins::forget(ctx, p))
+            }
+            PickEffect::Pin(p) => pins::pin(ctx, p),
+            PickEffect::Unpin(p) => pins::unpin(ctx, p),
+            PickEffect::Demote(p) => pins::demote(ctx, p),
+            PickEffect::Undemote(p) => pins::undemote(ctx, p),
+        };
+        if let Err(e) = res {
+            eprintln!("cdh: 保存条目操作失败 ({eff:?}): {e}");
+        }
     }
 }
 
@@ -266,8 +498,7 @@ fn run_log_subcommand(ctx: &AppContext, mut args: impl Iterator<Item = String>)
         }
     };
 
-    // 统一走 history 子系统的高层入口：log_visit（内部会写 raw + 更新 uniq）
-    match history::log_visit(ctx, &dir) {
+    match log_dir(ctx, &dir) {
         Ok(()) => 0,
         Err(e) => {
             eprintln!("cdh log: 写入历史失败: {e}");
@@ -275,3 +506,550 @@ fn run_log_subcommand(ctx: &AppContext, mut args: impl Iterator<Item = String>)
         }
     }
 }
+
+/// 记录一次访问：daemon 在跑时交给它（顺带更新内存索引），否则直接写文件。
+///
+/// 直接写文件统一走 history 子系统的高层入口：log_visit（内部会写 raw + 更新 uniq），
+/// 写完再按保留策略做例行修剪（daemon 在跑时由 daemon 负责）；修剪失败只报告，不算记录失败
+fn log_dir(ctx: &AppContext, dir: &str) -> io::Result<()> {
+    #[cfg(unix)]
+    if let Some(res) = daemon::try_log(ctx, dir) {
+        return res;
+    }
+    history::log_visit(ctx, dir)?;
+    if let Err(e) = retention::maybe_compact(ctx, history::now_secs()) {
+        eprintln!("cdh log: 修剪历史失败: {e}");
+    }
+    Ok(())
+}
+
+/// 处理子命令：`cdh pin [add|rm|ls] [path...]`
+///
+/// - add：置顶（默认当前目录）；路径按 log 的规则转成绝对路径
+/// - rm：取消置顶（原样和规范化后的路径都会尝试移除）
+/// - ls（默认）：按置顶顺序输出到 stdout，一行一个
+fn run_pin_subcommand(ctx: &AppContext, mut args: impl Iterator<Item = String>) -> i32 {
+    let action = args.next().unwrap_or_else(|| "ls".to_string());
+    let dirs: Vec<String> = args.collect();
+    let normalize = |d: &str| history::normalize_history_path(d).unwrap_or_else(|_| d.to_string());
+
+    let res: io::Result<()> = match action.as_str() {
+        "add" => {
+            let dirs = if dirs.is_empty() {
+                vec![".".to_string()]
+            } else {
+                dirs
+            };
+            dirs.iter().try_for_each(|d| {
+                let p = normalize(d);
+                pins::pin(ctx, &p)?;
+                eprintln!("cdh pin: 已置顶 {p}");
+                Ok(())
+            })
+        }
+        "rm" => {
+            if dirs.is_empty() {
+                eprintln!("cdh pin rm: 需要至少一个路径");
+                return 1;
+            }
+            let pinned = pins::load_pins(ctx);
+            dirs.iter().try_for_each(|d| {
+                let p = [d.clone(), normalize(d)]
+                    .into_iter()
+                    .find(|p| pinned.contains(p));
+                match p {
+                    Some(p) => {
+                        pins::unpin(ctx, &p)?;
+                        eprintln!("cdh pin: 已取消置顶 {p}");
+                    }
+                    None => eprintln!("cdh pin: 未置顶 {d}"),
+                }
+                Ok(())
+            })
+        }
+        "ls" if dirs.is_empty() => {
+            for p in pins::load_pins(ctx) {
+                println!("{p}");
+            }
+            Ok(())
+        }
+        "--help" | "-h" => {
+            eprintln!(
+                "用法: cdh pin [add|rm|ls] [path...]
+
+  add [path...]   置顶目录（默认当前目录）；置顶的目录总是排在推荐列表最前
+  rm <path...>    取消置顶
+  ls              列出置顶目录（默认）
+
+在 picker 里也可以用 pin 动作（默认 Ctrl-T）切换置顶。"
+            );
+            return 0;
+        }
+        "ls" => {
+            eprintln!("cdh pin ls: 多余的参数: {}", dirs[0]);
+            return 1;
+        }
+        other => {
+            eprintln!("cdh pin: 未知操作: {other}");
+            return 1;
+        }
+    };
+    match res {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("cdh pin: 写入置顶列表失败: {e}");
+            1
+        }
+    }
+}
+
+/// 处理子命令：`cdh mark <name> [path]` / `cdh unmark <name...>` / `cdh marks [--names]`
+///
+/// - mark：添加或覆盖书签（默认当前目录）；路径按 log 的规则转成绝对路径
+/// - unmark：删除书签
+/// - marks：输出 `@name<TAB>path` 到 stdout；`--names` 只输出 `@name`（供 Tab 补全）
+fn run_bookmark_subcommand(ctx: &AppContext, cmd: &str, args: impl Iterator<Item = String>) -> i32 {
+    let args: Vec<String> = args.collect();
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        eprintln!(
+            "用法:
+  cdh mark <name> [path]     添加书签（默认当前目录；同名覆盖）
+  cdh unmark <name...>       删除书签
+  cdh marks [--names]        列出书签（--names 只输出名字，供 Tab 补全）
+  cdh @name                  直接输出书签指向的目录；shell 包装函数会 cd 过去
+
+书签不随时间衰减；在 picker 里以 `@name` 标签显示，也可以搜索 `@name`。"
+        );
+        return 0;
+    }
+
+    let res: io::Result<i32> = match (cmd, args.as_slice()) {
+        ("mark", [name, rest @ ..]) if rest.len() <= 1 => {
+            let dir = rest.first().map_or(".", String::as_str);
+            let path = history::normalize_history_path(dir).unwrap_or_else(|_| dir.to_string());
+            bookmarks::set(ctx, name, &path).map(|old| {
+                let name = bookmarks::parse_name(name).unwrap_or(name);
+                match old.filter(|o| *o != path) {
+                    Some(old) => eprintln!("cdh mark: @{name} → {path}（原为 {old}）"),
+                    None => eprintln!("cdh mark: @{name} → {path}"),
+                }
+                0
+            })
+        }
+        ("unmark", names) if !names.is_empty() => names.iter().try_fold(0, |code, name| {
+            Ok(if bookmarks::remove(ctx, name)? {
+                code
+            } else {
+                eprintln!("cdh unmark: 没有书签 {name}");
+                1
+            })
+        }),
+        ("marks", []) => {
+            for b in bookmarks::load(ctx) {
+                println!("@{}\t{}", b.name, b.path);
+            }
+            Ok(0)
+        }
+        ("marks", [flag]) if flag == "--names" => {
+            for b in bookmarks::load(ctx) {
+                println!("@{}", b.name);
+            }
+            Ok(0)
+        }
+        _ => {
+            eprintln!("cdh {cmd}: 参数不正确（cdh {cmd} --help 查看用法）");
+            return 1;
+        }
+    };
+    res.unwrap_or_else(|e| {
+        eprintln!("cdh {cmd}: {e}");
+        1
+    })
+}
+
+/// `cdh @name`：像普通跳转一样输出书签路径（不带换行）；开启目录检查时目录必须存在
+fn jump_to_bookmark(ctx: &AppContext, name: &str, check_dir: bool) -> i32 {
+    let Some(path) = bookmarks::get(ctx, name) else {
+        eprintln!("cdh: 没有书签 {name}");
+        return 1;
+    };
+    if check_dir && !Path::new(&path).is_dir() {
+        eprintln!("cdh: 书签 {name} 指向的目录不存在: {path}");
+        return 1;
+    }
+    print_selected(std::slice::from_ref(&path), None);
+    0
+}
+
+/// 把书签并进候选列表：已在列表里的条目标上名字；其余追加在末尾
+/// （同样按关键字和 ignore_re 过滤，开启目录检查时跳过不存在的目录；总数不超过 limit）
+fn with_bookmarks(
+    mut items: Vec<PickItem>,
+    marks: Vec<Bookmark>,
+    opt: &RecommendOpt,
+) -> Vec<PickItem> {
+    let tokens: Vec<String> = opt.tokens.iter().map(|t| t.to_lowercase()).collect();
+    for b in marks {
+        if let Some(it) = items.iter_mut().find(|it| it.path == b.path) {
+            it.bookmark.get_or_insert(b.name);
+            continue;
+        }
+        if items.len() >= opt.limit {
+            continue;
+        }
+        let hay = format!("@{} {}", b.name, b.path).to_lowercase();
+        let wanted = tokens.is_empty() || tokens.iter().any(|t| hay.contains(t));
+        let ignored = opt
+            .ignore_re
+            .as_ref()
+            .is_some_and(|re| re.is_match(&b.path));
+        if wanted && !ignored && (!opt.check_dir || Path::new(&b.path).is_dir()) {
+            items.push(PickItem {
+                path: b.path,
+                bookmark: Some(b.name),
+                ..PickItem::default()
+            });
+        }
+    }
+    items
+}
+
+/// 计算推荐：daemon 在跑时用它的内存索引，否则（或出错时）回退到直接读历史文件
+fn query_recommendations(ctx: &AppContext, opt: &RecommendOpt) -> Vec<Recommendation> {
+    #[cfg(unix)]
+    if let Some(recs) = daemon::try_query(ctx, opt) {
+        return recs;
+    }
+    #[cfg(not(unix))]
+    let _ = ctx;
+    recommend(opt)
+}
+
+/// 处理子命令：`cdh compact [--max-age <sec>] [--max-lines <N>] [--max-size <bytes>]`
+///
+/// 默认按配置的保留策略立即修剪（不等例行检查）；选项只覆盖对应的一项。
+fn run_compact_subcommand(ctx: &AppContext, mut args: impl Iterator<Item = String>) -> i32 {
+    let mut policy = ctx.config.retention();
+    while let Some(a) = args.next() {
+        let value = match a.as_str() {
+            "--help" | "-h" => {
+                eprintln!(
+                    "用法: cdh compact [--max-age <sec>] [--max-lines <N>] [--max-size <bytes>]
+
+  --max-age <sec>     访问最多保留多久（秒；配置 history_max_age）
+  --max-lines <N>     最多保留多少行访问（配置 history_max_lines）
+  --max-size <bytes>  history_raw 最大字节数，可带 K / M / G 后缀（配置 history_max_size）
+
+各项为 0 表示不限制（默认三项都是 0，不做修剪）。修剪掉的旧访问按目录折叠成摘要行，排序分数保持不变。"
+                );
+                return 0;
+            }
+            "--max-age" | "--max-lines" | "--max-size" => match args.next() {
+                Some(v) => v,
+                None => {
+                    eprintln!("cdh compact: {a} 需要一个参数");
+                    return 1;
+                }
+            },
+            other => {
+                eprintln!("cdh compact: 未知参数: {other}");
+                return 1;
+            }
+        };
+        let ok = match a.as_str() {
+            "--max-age" => value
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .map(|v| policy.max_age = v),
+            "--max-lines" => value.parse().ok().map(|v| policy.max_lines = v),
+            _ => retention::parse_size(&value).map(|v| policy.max_size = v),
+        };
+        if ok.is_none() {
+            eprintln!("cdh compact: {a} 的值无效: {value}");
+            return 1;
+        }
+    }
+    if policy.is_unlimited() {
+        eprintln!("cdh compact: 没有设置任何保留限制，不做修剪");
+        return 0;
+    }
+
+    match retention::compact(ctx, &policy) {
+        Ok(st) if st.dropped == 0 => {
+            eprintln!("cdh compact: 无需修剪（{} 条访问）", st.kept);
+            0
+        }
+        Ok(st) => {
+            eprintln!(
+                "cdh compact: 折叠了 {} 条旧访问，保留 {} 条访问 + {} 条目录摘要",
+                st.dropped, st.kept, st.summaries
+            );
+            0
+        }
+        Err(e) => {
+            eprintln!("cdh compact: 修剪历史失败: {e}");
+            1
+        }
+    }
+}
+
+/// 处理子命令：`cdh ignore check <path...>` / `cdh ignore purge [--dry-run]`
+///
+/// - check：输出被忽略的路径（规范化后）；至少有一个被忽略时返回 0，否则 1（同 `git check-ignore`）
+/// - purge：把忽略规则应用到已有的历史文件
+fn run_ignore_subcommand(ctx: &AppContext, mut args: impl Iterator<Item = String>) -> i32 {
+    let action = args.next().unwrap_or_default();
+    let rest: Vec<String> = args.collect();
+    let (rules, invalid) = IgnoreRules::load(&ctx.paths, &ctx.config);
+
+    match action.as_str() {
+        "check" if !rest.is_empty() => {
+            // 只有这里报告无效的规则：cd 钩子里它们被静默跳过
+            for msg in &invalid {
+                eprintln!("cdh ignore: 无效的规则（已跳过）{msg}");
+            }
+            let mut any = false;
+            for d in &rest {
+                let p = history::normalize_history_path(d).unwrap_or_else(|_| d.clone());
+                if rules.is_ignored(&p) {
+                    println!("{p}");
+                    any = true;
+                }
+            }
+            i32::from(!any)
+        }
+        "purge" if rest.iter().all(|a| a == "--dry-run") => {
+            if rules.is_empty() {
+                eprintln!("cdh ignore: 没有配置任何忽略规则");
+                return 0;
+            }
+            let dry_run = !rest.is_empty();
+            match ignore::purge(ctx, &rules, dry_run) {
+                Ok(st) => {
+                    let verb = if dry_run { "将删除" } else { "已删除" };
+                    eprintln!(
+                        "cdh ignore: {verb} {} 行（raw {}，uniq {}，选择反馈 {}，搜索历史 {}）",
+                        st.total(),
+                        st.raw,
+                        st.uniq,
+                        st.selections,
+                        st.queries
+                    );
+                    0
+                }
+                Err(e) => {
+                    eprintln!("cdh ignore: 清理历史失败: {e}");
+                    1
+                }
+            }
+        }
+        "--help" | "-h" => {
+            eprintln!(
+                "用法: cdh ignore [check <path...>|purge [--dry-run]]
+
+  check <path...>     输出其中会被忽略的路径（都不忽略时退出码为 1）
+  purge [--dry-run]   从已有历史里删掉被忽略的目录（--dry-run 只统计）
+
+规则来源：配置 log_ignore_re（正则）、log_ignore（glob），以及 gitignore 风格的
+{}（一行一个 glob，`!` 开头为放行）。命中的目录 cdh log 不会记录。",
+                ignore::ignore_file_path(&ctx.paths).display()
+            );
+            0
+        }
+        "" => {
+            eprintln!("cdh ignore: 需要一个操作：check 或 purge（见 cdh ignore --help）");
+            1
+        }
+        other => {
+            eprintln!("cdh ignore: 未知操作或参数有误: {other}（见 cdh ignore --help）");
+            1
+        }
+    }
+}
+
+/// 处理子命令：`cdh daemon [run|stop|status]`
+///
+/// - run（默认）：前台运行，直到 `cdh daemon stop`；需要后台运行可用 `cdh daemon &`、nohup 或 systemd
+/// - stop：通知正在运行的 daemon 退出
+/// - status：探测 daemon 是否在运行
+fn run_daemon_subcommand(ctx: &AppContext, mut args: impl Iterator<Item = String>) -> i32 {
+    let action = args.next().unwrap_or_else(|| "run".to_string());
+    if let Some(extra) = args.next() {
+        eprintln!("cdh daemon: 多余的参数: {extra}");
+        return 1;
+    }
+
+    #[cfg(unix)]
+    match action.as_str() {
+        "run" => match daemon::serve(ctx) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("cdh daemon: {e}");
+                1
+            }
+        },
+        "stop" => match daemon::stop(ctx) {
+            Some(Ok(())) => 0,
+            Some(Err(e)) => {
+                eprintln!("cdh daemon: 停止失败: {e}");
+                1
+            }
+            None => {
+                eprintln!("cdh daemon: 未运行");
+                1
+            }
+        },
+        "status" => match daemon::ping(ctx) {
+            Some(version) => {
+                eprintln!(
+                    "cdh daemon: 运行中（v{version}，{}）",
+                    daemon::socket_path(ctx).display()
+                );
+                0
+            }
+            None => {
+                eprintln!("cdh daemon: 未运行");
+                1
+            }
+        },
+        "--help" | "-h" => {
+            eprintln!(
+                "用法: cdh daemon [run|stop|status]
+
+  run      前台运行常驻进程（默认；后台运行可用 `cdh daemon &`）
+  stop     停止正在运行的常驻进程
+  status   查看常驻进程是否在运行
+
+daemon 未运行时，cdh log / cdh 会自动回退为直接读写历史文件。"
+            );
+            0
+        }
+        other => {
+            eprintln!("cdh daemon: 未知操作: {other}");
+            1
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (ctx, action);
+        eprintln!("cdh daemon: 当前平台不支持");
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// 历史里只有临时根目录本身的上下文
+    fn test_ctx(name: &str) -> (PathBuf, AppContext) {
+        let (root, mut ctx) = AppContext::for_test(name);
+        fs::write(&ctx.paths.history_raw, format!("1\t{}\n", root.display())).unwrap();
+        fs::write(&ctx.paths.history_uniq, format!("{}\n", root.display())).unwrap();
+        ctx.config.check_dir = false;
+        (root, ctx)
+    }
+
+    #[test]
+    fn half_life_zero_returns_error_instead_of_panicking() {
+        let (root, ctx) = test_ctx("half_life_zero");
+        let status = run_with_args(
+            &ctx,
+            ["--half-life", "0", "--no-check-dir"]
+                .into_iter()
+                .map(String::from),
+        );
+        assert_eq!(status, 1);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn half_life_negative_returns_error_instead_of_panicking() {
+        let (root, ctx) = test_ctx("half_life_negative");
+        let status = run_with_args(
+            &ctx,
+            ["--half-life", "-1", "--no-check-dir"]
+                .into_iter()
+                .map(String::from),
+        );
+        assert_eq!(status, 1);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn bookmarks_tag_existing_items_and_append_the_rest() {
+        let mark = |name: &str, path: &str| Bookmark {
+            name: name.into(),
+            path: path.into(),
+        };
+        let items = vec![PickItem::from("/w/api"), PickItem::from("/w/web")];
+        let marks = vec![
+            mark("api", "/w/api"),
+            mark("docs", "/w/docs"),
+            mark("ops", "/srv/runbooks"),
+        ];
+        let opt = RecommendOpt {
+            tokens: vec!["W".into()],
+            check_dir: false,
+            ..RecommendOpt::default()
+        };
+        let out = with_bookmarks(items, marks, &opt);
+        let got: Vec<(&str, Option<&str>)> = out
+            .iter()
+            .map(|it| (it.path.as_str(), it.bookmark.as_deref()))
+            .collect();
+        assert_eq!(
+            got,
+            [
+                ("/w/api", Some("api")),
+                ("/w/web", None),
+                ("/w/docs", Some("docs")),
+            ]
+        );
+
+        // ignore_re 命中的不追加；追加到 limit 为止（已有条目照样标名字）
+        let items = vec![PickItem::from("/w/api"), PickItem::from("/w/web")];
+        let marks = vec![
+            mark("tmp", "/w/tmp"),
+            mark("docs", "/w/docs"),
+            mark("site", "/w/site"),
+            mark("api", "/w/api"),
+        ];
+        let opt = RecommendOpt {
+            limit: 3,
+            ignore_re: Some(Regex::new("tmp").unwrap()),
+            check_dir: false,
+            ..RecommendOpt::default()
+        };
+        let out = with_bookmarks(items, marks, &opt);
+        let got: Vec<(&str, Option<&str>)> = out
+            .iter()
+            .map(|it| (it.path.as_str(), it.bookmark.as_deref()))
+            .collect();
+        assert_eq!(
+            got,
+            [
+                ("/w/api", Some("api")),
+                ("/w/web", None),
+                ("/w/docs", Some("docs")),
+            ]
+        );
+    }
+
+    #[test]
+    fn options_after_a_bookmark_still_apply() {
+        let (root, mut ctx) = test_ctx("bookmark_flags");
+        ctx.config.check_dir = true;
+        let gone = root.join("gone");
+        bookmarks::set(&ctx, "gone", &gone.to_string_lossy()).unwrap();
+        let run = |args: &[&str]| run_with_args(&ctx, args.iter().map(|s| s.to_string()));
+        // 目录不存在：默认检查目录时失败，后面跟 --no-check-dir 时照样输出
+        assert_eq!(run(&["@gone"]), 1);
+        assert_eq!(run(&["@gone", "--no-check-dir"]), 0);
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,587 @@
+// src/daemon.rs
+//! 常驻进程（可选）：在内存中维护 Frecency 索引 + uniq 列表，通过 Unix socket 响应 log / query。
+//!
+//! 设计要点：
+//!   - 监听 STATE/daemon.sock；每个连接只处理一条请求，单线程串行处理，天然没有并发写；
+//!   - log：仍然走 `history::record_visit` 写文件（文件是唯一真相），写完再同步内存索引；
+//!     回复之后再按保留策略做例行修剪（见 src/retention.rs），免得 cd 钩子等修剪；
+//!   - query：直接在内存索引上跑 `recommend_from_index`，不再逐行解析历史文件；
+//!     选择反馈（DATA/selections）很小，每次查询时直接读文件；
+//!   - 文件被其他进程改动（daemon 启动前写入、手动编辑等）时，按 (长度, mtime) 检测后整体重载。
+//!
+//! 协议（文本，逐行，字段以 TAB 分隔）：
+//!   PING                                  -> `OK\t<version>`
+//!   LOG\t<abs_path>                       -> `OK` | `ERR\t<msg>`
+//!   QUERY，若干 `key\tvalue` 行，空行结束 -> `OK` + 若干 `<score>\t<visits>\t<last_ts|->\t<path>` 行 | `ERR\t<msg>`
+//!   STOP                                  -> `OK`（随后退出）
+//!
+//! 客户端（`try_log` / `try_query`）连不上 daemon 时返回 `None`，由调用方回退到直接读写文件。
+
+use crate::feedback::feedback_path;
+use crate::frecency::{Frecency, FrecencyIndex};
+use crate::history;
+use crate::recommend::{
+    build_frecency_from_raw, load_uniq_lines, recommend_from_index, RecommendOpt, Recommendation,
+};
+use crate::retention;
+use crate::AppContext;
+use regex::Regex;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 客户端读写超时：daemon 卡住时不能拖慢 prompt
+const CLIENT_TIMEOUT: Duration = Duration::from_millis(1000);
+/// 服务端单个连接的读超时：防止半开连接卡住整个 accept 循环
+const SERVER_READ_TIMEOUT: Duration = Duration::from_millis(2000);
+
+/// daemon socket 路径：放在 STATE 目录下（与历史锁同目录）
+pub fn socket_path(ctx: &AppContext) -> PathBuf {
+    ctx.paths.state_dir.join("daemon.sock")
+}
+
+/* ------------------------------- 服务端 ------------------------------- */
+
+/// 历史文件的“指纹”：长度 + 修改时间，用于发现外部写入
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+fn stamp_of(path: &Path) -> Option<FileStamp> {
+    fs::metadata(path).ok().map(|m| FileStamp {
+        len: m.len(),
+        modified: m.modified().ok(),
+    })
+}
+
+/// daemon 的内存状态
+struct Store {
+    idx: FrecencyIndex,
+    /// 最近唯一列表（旧->新），语义同 history_uniq
+    uniq: Vec<String>,
+    raw_stamp: Option<FileStamp>,
+    uniq_stamp: Option<FileStamp>,
+}
+
+impl Store {
+    /// 从历史文件全量加载（不做任何过滤，过滤留到查询时按请求参数做）；
+    /// patterns：同时记录访问时段分布（有请求用到时段信号时才打开）
+    fn load(ctx: &AppContext, model: Frecency, patterns: bool) -> Self {
+        let raw = ctx.paths.history_raw.to_string_lossy();
+        let uniq = ctx.paths.history_uniq.to_string_lossy();
+        let (idx, _) = build_frecency_from_raw(&raw, &None, &[], false, model, patterns);
+        Self {
+            idx,
+            uniq: load_uniq_lines(&uniq),
+            raw_stamp: stamp_of(&ctx.paths.history_raw),
+            uniq_stamp: stamp_of(&ctx.paths.history_uniq),
+        }
+    }
+
+    /// 文件被外部改动、请求的模型（种类 / 半衰期等）与索引不一致，
+    /// 或请求要用时段信号而索引没记时段分布时，整体重载（时段分布一旦记了就一直记）
+    fn refresh(&mut self, ctx: &AppContext, model: Frecency, patterns: bool) {
+        let patterns = patterns || self.idx.has_patterns();
+        let stale = self.raw_stamp != stamp_of(&ctx.paths.history_raw)
+            || self.uniq_stamp != stamp_of(&ctx.paths.history_uniq)
+            || *self.idx.model() != model
+            || patterns != self.idx.has_patterns();
+        if stale {
+            *self = Store::load(ctx, model, patterns);
+        }
+    }
+
+    /// 自己写完文件后同步内存，并换上写完时（还持有历史锁时）取的指纹，避免下次查询误判为外部改动
+    fn apply_visit(&mut self, v: LoggedVisit) {
+        self.idx.record_visit(v.dir.clone(), v.ts);
+        self.uniq.retain(|p| p != &v.dir);
+        self.uniq.push(v.dir);
+        self.raw_stamp = v.raw_stamp;
+        self.uniq_stamp = v.uniq_stamp;
+    }
+}
+
+/// 一次写入的访问，连同写完时的文件指纹
+struct LoggedVisit {
+    dir: String,
+    ts: i64,
+    raw_stamp: Option<FileStamp>,
+    uniq_stamp: Option<FileStamp>,
+}
+
+impl LoggedVisit {
+    /// 在 `history::record_visit` 的回调里调用：此时仍持有历史锁，指纹只包含这次写入
+    fn capture(ctx: &AppContext, dir: &str, ts: i64) -> Self {
+        Self {
+            dir: dir.to_string(),
+            ts,
+            raw_stamp: stamp_of(&ctx.paths.history_raw),
+            uniq_stamp: stamp_of(&ctx.paths.history_uniq),
+        }
+    }
+}
+
+/// 删除 socket 文件的守卫：正常退出 / 出错返回时都清理
+struct SocketGuard(PathBuf);
+
+impl Drop for SocketGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// 在前台运行 daemon，直到收到 STOP。
+///
+/// - 已有 daemon 在跑：返回 `AlreadyExists`；
+/// - 残留的 socket 文件（上次被 kill）：直接清理后重新 bind。
+pub fn serve(ctx: &AppContext) -> io::Result<()> {
+    let sock = socket_path(ctx);
+    if ping(ctx).is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("daemon already running: {}", sock.display()),
+        ));
+    }
+    if let Some(parent) = sock.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let _ = fs::remove_file(&sock);
+
+    let listener = UnixListener::bind(&sock)?;
+    let _guard = SocketGuard(sock);
+    let mut store = Store::load(ctx, ctx.config.frecency(), ctx.config.w_temporal > 0.0);
+
+    for conn in listener.incoming() {
+        let stream = match conn {
+            Ok(s) => s,
+            Err(_) => continue,
+        };
+        // 单个连接出错（客户端提前断开等）不影响 daemon 本身
+        if let Ok(true) = handle_conn(ctx, &mut store, stream) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// 处理一个连接；返回 `Ok(true)` 表示收到 STOP
+fn handle_conn(ctx: &AppContext, store: &mut Store, stream: UnixStream) -> io::Result<bool> {
+    stream.set_read_timeout(Some(SERVER_READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut out = io::BufWriter::new(stream);
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let line = line.trim_end_matches(['\r', '\n']);
+    let (cmd, arg) = line.split_once('\t').unwrap_or((line, ""));
+
+    match cmd {
+        "PING" => writeln!(out, "OK\t{}", env!("CARGO_PKG_VERSION"))?,
+        "STOP" => {
+            writeln!(out, "OK")?;
+            out.flush()?;
+            return Ok(true);
+        }
+        "LOG" => {
+            match history::record_visit(ctx, arg, |dir, ts| LoggedVisit::capture(ctx, dir, ts)) {
+                Ok(Some(v)) => {
+                    let ts = v.ts;
+                    store.apply_visit(v);
+                    writeln!(out, "OK")?;
+                    out.flush()?;
+                    // 先回复客户端再做例行修剪；修剪改动了文件，下次查询时按指纹重载
+                    if let Err(e) = retention::maybe_compact(ctx, ts) {
+                        eprintln!("cdh daemon: 修剪历史失败: {e}");
+                    }
+                }
+                Ok(None) => writeln!(out, "OK")?,
+                Err(e) => writeln!(out, "ERR\t{}", one_line(&e.to_string()))?,
+            }
+        }
+        "QUERY" => {
+            let mut fields = Vec::new();
+            loop {
+                let mut l = String::new();
+                if reader.read_line(&mut l)? == 0 {
+                    break;
+                }
+                let l = l.trim_end_matches(['\r', '\n']);
+                if l.is_empty() {
+                    break;
+                }
+                fields.push(l.to_string());
+            }
+            match decode_query(ctx, &fields) {
+                Ok(opt) => {
+                    store.refresh(ctx, opt.frecency(), opt.w_temporal > 0.0);
+                    let recs = recommend_from_index(&opt, &store.idx, &store.uniq, now_secs());
+                    writeln!(out, "OK")?;
+                    for r in recs {
+                        let last = r.last_visit.map(|t| t.to_string());
+                        let last = last.as_deref().unwrap_or("-");
+                        writeln!(out, "{}\t{}\t{last}\t{}", r.score, r.visits, r.path)?;
+                    }
+                }
+                Err(msg) => writeln!(out, "ERR\t{}", one_line(&msg))?,
+            }
+        }
+        other => writeln!(out, "ERR\tunknown command: {}", one_line(other))?,
+    }
+    out.flush()?;
+    Ok(false)
+}
+
+/// 把 QUERY 的 `key\tvalue` 行还原成 RecommendOpt。
+///
+/// raw/uniq（以及非空的 feedback）必须与 daemon 自己的文件一致（不同 XDG 环境下的客户端不能共用内存索引）。
+fn decode_query(ctx: &AppContext, fields: &[String]) -> Result<RecommendOpt, String> {
+    let mut opt = RecommendOpt::default();
+    for f in fields {
+        let (k, v) = f.split_once('\t').unwrap_or((f.as_str(), ""));
+        let bad = || format!("bad value for {k}: {v}");
+        match k {
+            "raw" => opt.raw = v.to_string(),
+            "uniq" => opt.uniq = v.to_string(),
+            "limit" => opt.limit = v.parse().map_err(|_| bad())?,
+            "half_life" => opt.half_life = v.parse().map_err(|_| bad())?,
+            "frecency_model" => opt.frecency_model = v.parse().map_err(|_| bad())?,
+            "long_half_life" => opt.long_half_life = v.parse().map_err(|_| bad())?,
+            "frecency_window" => opt.frecency_window = v.parse().map_err(|_| bad())?,
+            "threshold" => opt.threshold = v.parse().map_err(|_| bad())?,
+            "ignore_re" => opt.ignore_re = Some(Regex::new(v).map_err(|_| bad())?),
+            "check_dir" => opt.check_dir = v.parse().map_err(|_| bad())?,
+            "uniq_decay" => opt.uniq_decay = v.parse().map_err(|_| bad())?,
+            "w_frecency" => opt.w_frecency = v.parse().map_err(|_| bad())?,
+            "w_uniq" => opt.w_uniq = v.parse().map_err(|_| bad())?,
+            "normalize" => opt.normalize = v.parse().map_err(|_| bad())?,
+            "feedback" => opt.feedback = v.to_string(),
+            "w_feedback" => opt.w_feedback = v.parse().map_err(|_| bad())?,
+            "cwd" => opt.cwd = Some(v.to_string()),
+            "w_proximity" => opt.w_proximity = v.parse().map_err(|_| bad())?,
+            "w_temporal" => opt.w_temporal = v.parse().map_err(|_| bad())?,
+            "utc_offset" => opt.utc_offset = v.parse().map_err(|_| bad())?,
+            "token" => opt.tokens.push(v.to_string()),
+            "pin" => opt.pins.push(v.to_string()),
+            _ => return Err(format!("unknown field: {k}")),
+        }
+    }
+    if opt.raw != ctx.paths.history_raw.to_string_lossy()
+        || opt.uniq != ctx.paths.history_uniq.to_string_lossy()
+        || (!opt.feedback.is_empty() && opt.feedback != feedback_path(ctx).to_string_lossy())
+    {
+        return Err("history files differ from daemon's".into());
+    }
+    for (name, v) in [
+        ("half_life", opt.half_life),
+        ("long_half_life", opt.long_half_life),
+        ("frecency_window", opt.frecency_window),
+    ] {
+        if !v.is_finite() || v <= 0.0 {
+            return Err(format!("{name} must be > 0"));
+        }
+    }
+    Ok(opt)
+}
+
+/* ------------------------------- 客户端 ------------------------------- */
+
+/// 连接 daemon；没有在跑（socket 不存在 / 拒绝连接）时返回 None
+fn connect(ctx: &AppContext) -> Option<UnixStream> {
+    let stream = UnixStream::connect(socket_path(ctx)).ok()?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    Some(stream)
+}
+
+/// 发送一条请求，读回全部响应行（首行必须是 OK）
+fn request(ctx: &AppContext, msg: &str) -> Option<Result<Vec<String>, String>> {
+    let mut stream = connect(ctx)?;
+    Some(exchange(&mut stream, msg))
+}
+
+fn exchange(stream: &mut UnixStream, msg: &str) -> Result<Vec<String>, String> {
+    stream
+        .write_all(msg.as_bytes())
+        .and_then(|_| stream.flush())
+        .map_err(|e| e.to_string())?;
+    let mut lines = BufReader::new(stream).lines();
+    let status = match lines.next() {
+        Some(Ok(s)) => s,
+        Some(Err(e)) => return Err(e.to_string()),
+        None => return Err("daemon closed connection".into()),
+    };
+    let (head, rest) = status.split_once('\t').unwrap_or((status.as_str(), ""));
+    match head {
+        "OK" => {
+            let mut out = vec![rest.to_string()];
+            for l in lines {
+                out.push(l.map_err(|e| e.to_string())?);
+            }
+            Ok(out)
+        }
+        _ => Err(rest.to_string()),
+    }
+}
+
+/// 探测 daemon 是否在跑，返回其版本号
+pub fn ping(ctx: &AppContext) -> Option<String> {
+    match request(ctx, "PING\n")? {
+        Ok(lines) => lines.into_iter().next(),
+        Err(_) => None,
+    }
+}
+
+/// 请求 daemon 退出；没有在跑时返回 None
+pub fn stop(ctx: &AppContext) -> Option<Result<(), String>> {
+    request(ctx, "STOP\n").map(|r| r.map(|_| ()))
+}
+
+/// 通过 daemon 记录一次访问。
+///
+/// - daemon 没有在跑：返回 None，调用方应回退到 `history::log_visit`；
+/// - 已经连上但出错：返回 `Some(Err)`，此时不再回退，避免 raw 被重复追加。
+pub fn try_log(ctx: &AppContext, dir: &str) -> Option<io::Result<()>> {
+    let dir = dir.trim();
+    if dir.is_empty() {
+        return None;
+    }
+    // 相对路径必须在客户端按自己的 cwd 展开，daemon 的 cwd 与之无关
+    let dir = match history::normalize_history_path(dir) {
+        Ok(d) => d,
+        Err(e) => return Some(Err(e)),
+    };
+    if dir.contains(['\t', '\n']) {
+        return None;
+    }
+    let res = request(ctx, &format!("LOG\t{dir}\n"))?;
+    Some(
+        res.map(|_| ())
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e)),
+    )
+}
+
+/// 通过 daemon 计算推荐；daemon 没有在跑或任何出错都返回 None（查询只读，回退总是安全的）
+pub fn try_query(ctx: &AppContext, opt: &RecommendOpt) -> Option<Vec<Recommendation>> {
+    let msg = encode_query(opt)?;
+    let lines = request(ctx, &msg)?.ok()?;
+    let mut recs = Vec::with_capacity(lines.len().saturating_sub(1));
+    for l in lines.iter().skip(1) {
+        // 旧版 daemon 只返回 `<score>\t<path>`，解析失败时整体回退到本地计算
+        let mut it = l.splitn(4, '\t');
+        let (score, visits, last, path) = (it.next()?, it.next()?, it.next()?, it.next()?);
+        recs.push(Recommendation {
+            path: path.to_string(),
+            score: score.parse().ok()?,
+            visits: visits.parse().ok()?,
+            last_visit: match last {
+                "-" => None,
+                t => Some(t.parse().ok()?),
+            },
+        });
+    }
+    Some(recs)
+}
+
+/// 把 RecommendOpt 编码成 QUERY 请求；任何值含 TAB/换行时放弃（交给本地计算）
+fn encode_query(opt: &RecommendOpt) -> Option<String> {
+    let mut fields: Vec<(&str, String)> = vec![
+        ("raw", opt.raw.clone()),
+        ("uniq", opt.uniq.clone()),
+        ("limit", opt.limit.to_string()),
+        ("half_life", opt.half_life.to_string()),
+        ("frecency_model", opt.frecency_model.as_str().to_string()),
+        ("long_half_life", opt.long_half_life.to_string()),
+        ("frecency_window", opt.frecency_window.to_string()),
+        ("threshold", opt.threshold.to_string()),
+        ("check_dir", opt.check_dir.to_string()),
+        ("uniq_decay", opt.uniq_decay.to_string()),
+        ("w_frecency", opt.w_frecency.to_string()),
+        ("w_uniq", opt.w_uniq.to_string()),
+        ("normalize", opt.normalize.as_str().to_string()),
+        ("feedback", opt.feedback.clone()),
+        ("w_feedback", opt.w_feedback.to_string()),
+        ("w_proximity", opt.w_proximity.to_string()),
+        ("w_temporal", opt.w_temporal.to_string()),
+        ("utc_offset", opt.utc_offset.to_string()),
+    ];
+    if let Some(cwd) = &opt.cwd {
+        fields.push(("cwd", cwd.clone()));
+    }
+    if let Some(rx) = &opt.ignore_re {
+        fields.push(("ignore_re", rx.as_str().to_string()));
+    }
+    for t in &opt.tokens {
+        fields.push(("token", t.clone()));
+    }
+    for p in &opt.pins {
+        fields.push(("pin", p.clone()));
+    }
+
+    let mut msg = String::from("QUERY\n");
+    for (k, v) in fields {
+        if v.contains(['\t', '\n', '\r']) {
+            return None;
+        }
+        msg.push_str(k);
+        msg.push('\t');
+        msg.push_str(&v);
+        msg.push('\n');
+    }
+    msg.push('\n');
+    Some(msg)
+}
+
+fn one_line(s: &str) -> String {
+    s.replace(['\t', '\n', '\r'], " ")
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn query_opt(ctx: &AppContext) -> RecommendOpt {
+        RecommendOpt {
+            raw: ctx.paths.history_raw.to_string_lossy().into_owned(),
+            uniq: ctx.paths.history_uniq.to_string_lossy().into_owned(),
+            check_dir: false,
+            ..RecommendOpt::default()
+        }
+    }
+
+    fn spawn_daemon(ctx: &AppContext) -> thread::JoinHandle<io::Result<()>> {
+        let server_ctx = ctx.clone();
+        let handle = thread::spawn(move || serve(&server_ctx));
+        for _ in 0..100 {
+            if ping(ctx).is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        handle
+    }
+
+    #[test]
+    fn client_falls_back_when_daemon_not_running() {
+        let (root, ctx) = AppContext::for_test("not_running");
+        assert!(ping(&ctx).is_none());
+        assert!(try_log(&ctx, root.to_str().unwrap()).is_none());
+        assert!(try_query(&ctx, &query_opt(&ctx)).is_none());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn log_and_query_through_daemon() {
+        let (root, ctx) = AppContext::for_test("log_and_query");
+        let dir_a = root.join("a");
+        let dir_b = root.join("b");
+        fs::create_dir_all(&dir_a).unwrap();
+        fs::create_dir_all(&dir_b).unwrap();
+
+        let handle = spawn_daemon(&ctx);
+        assert_eq!(ping(&ctx).as_deref(), Some(env!("CARGO_PKG_VERSION")));
+
+        try_log(&ctx, dir_a.to_str().unwrap()).unwrap().unwrap();
+        try_log(&ctx, dir_b.to_str().unwrap()).unwrap().unwrap();
+        try_log(&ctx, dir_a.to_str().unwrap()).unwrap().unwrap();
+
+        // 文件仍然是唯一真相：daemon 写入的内容与直接写入一致
+        let uniq = fs::read_to_string(&ctx.paths.history_uniq).unwrap();
+        assert_eq!(
+            uniq.lines().collect::<Vec<_>>(),
+            vec![dir_b.to_str().unwrap(), dir_a.to_str().unwrap()]
+        );
+
+        let via_daemon = try_query(&ctx, &query_opt(&ctx)).unwrap();
+        let direct = crate::recommend::recommend(&query_opt(&ctx));
+        let paths = |v: &[Recommendation]| v.iter().map(|r| r.path.clone()).collect::<Vec<_>>();
+        assert_eq!(paths(&via_daemon), paths(&direct));
+        assert_eq!(via_daemon[0].path, dir_a.to_string_lossy());
+
+        stop(&ctx).unwrap().unwrap();
+        handle.join().unwrap().unwrap();
+        assert!(!socket_path(&ctx).exists());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn stamps_are_taken_under_the_history_lock() {
+        let (root, ctx) = AppContext::for_test("stamps");
+        let dir = root.join("a");
+        fs::create_dir_all(&dir).unwrap();
+        let model = ctx.config.frecency();
+        let mut store = Store::load(&ctx, model, false);
+
+        let v = history::record_visit(&ctx, dir.to_str().unwrap(), |d, ts| {
+            LoggedVisit::capture(&ctx, d, ts)
+        })
+        .unwrap()
+        .unwrap();
+        // 锁一释放、daemon 还没同步内存时，另一个进程写了一行
+        history::append_raw(&ctx, "/other/dir").unwrap();
+        store.apply_visit(v);
+
+        // 指纹是写完自己那行时取的：别人的写入会被当作外部改动，下次查询时重载
+        store.refresh(&ctx, model, false);
+        assert!(store.idx.state("/other/dir").is_some());
+        assert!(store.idx.state(dir.to_str().unwrap()).is_some());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn time_patterns_are_loaded_on_demand() {
+        let (root, ctx) = AppContext::for_test("patterns");
+        history::append_raw(&ctx, "/a").unwrap();
+        let model = ctx.config.frecency();
+        let mut store = Store::load(&ctx, model, false);
+        assert!(store.idx.pattern("/a").is_none());
+
+        // 用到时段信号的请求：重载并记下时段分布，之后不用时段信号的请求也不再丢掉
+        store.refresh(&ctx, model, true);
+        assert!(store.idx.pattern("/a").is_some());
+        store.refresh(&ctx, model, false);
+        assert!(store.idx.has_patterns());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn daemon_reloads_after_external_write() {
+        let (root, ctx) = AppContext::for_test("external_write");
+        let handle = spawn_daemon(&ctx);
+
+        // 绕过 daemon 直接写文件，daemon 应在下次查询时发现并重载
+        history::log_visit(&ctx, "/external/dir").unwrap();
+        let recs = try_query(&ctx, &query_opt(&ctx)).unwrap();
+        assert_eq!(recs.len(), 1);
+        assert_eq!(recs[0].path, "/external/dir");
+
+        stop(&ctx).unwrap().unwrap();
+        handle.join().unwrap().unwrap();
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn query_for_other_history_files_is_rejected() {
+        let (root, ctx) = AppContext::for_test("other_files");
+        let handle = spawn_daemon(&ctx);
+
+        let mut opt = query_opt(&ctx);
+        opt.raw = "/somewhere/else/history_raw".into();
+        assert!(try_query(&ctx, &opt).is_none());
+
+        stop(&ctx).unwrap().unwrap();
+        handle.join().unwrap().unwrap();
+        let _ = fs::remove_dir_all(root);
+    }
+}
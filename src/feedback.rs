@@ -0,0 +1,188 @@
+// src/feedback.rs
+//! 选择反馈：记录用户在 picker 里选了第几行、跳过了哪些路径，反过来影响排序。
+//!
+//! 文件：DATA/selections，一行一次选择：
+//!   `<ts_secs>\t<名次>\t<查询>\t<选中的路径>[\t<跳过的路径>...]`
+//! 名次是选中条目在当时列表（搜索时为过滤后的列表）里的位置，从 0 开始；
+//! 跳过的路径是排在它前面、用户没有选的那些。只保留最近 `MAX_LINES` 行，
+//! 写入复用历史锁，“临时文件 + rename”原子替换。
+//!
+//! 反馈分：同一路径每次从第 r 行被选中记 `r / (r + 1)`（第一行为 0，越靠后越接近 1），
+//! 再按半衰期随时间衰减后累加 —— 经常要往下翻才选到的路径分数高，
+//! 由 `recommend` 按 `w_feedback` 融合进最终分，把它往前提。
+
+use crate::history::{now_secs, with_history_lock, write_lines_atomic};
+use crate::scorer::{Normalize, ScoreContext, Scorer};
+use crate::AppContext;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// 文件最多保留的行数
+const MAX_LINES: usize = 2000;
+
+/// 一次选择
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selection {
+    /// 选中的路径
+    pub path: String,
+    /// 在当时列表里的位置（0 起）
+    pub rank: usize,
+    /// 搜索查询（不是在搜索模式下选中的为空）
+    pub query: String,
+    /// 排在前面、被跳过的路径（按列表顺序）
+    pub passed_over: Vec<String>,
+}
+
+/// 选择记录路径：DATA/selections
+pub fn feedback_path(ctx: &AppContext) -> PathBuf {
+    ctx.paths.data_dir.join("selections")
+}
+
+/// 记录一次选择（当前时间）
+pub fn record(ctx: &AppContext, sel: &Selection) -> io::Result<()> {
+    record_at(ctx, sel, now_secs())
+}
+
+/// 同 `record`，时间戳由调用方给出
+pub fn record_at(ctx: &AppContext, sel: &Selection, ts: i64) -> io::Result<()> {
+    let bad = |s: &str| s.contains(['\t', '\n']);
+    if sel.path.is_empty() || bad(&sel.path) || bad(&sel.query) {
+        return Ok(());
+    }
+    let mut line = format!("{ts}\t{}\t{}\t{}", sel.rank, sel.query.trim(), sel.path);
+    for p in sel.passed_over.iter().filter(|p| !bad(p)) {
+        line.push('\t');
+        line.push_str(p);
+    }
+
+    let path = feedback_path(ctx);
+    with_history_lock(ctx, || {
+        let mut lines: Vec<String> = fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        lines.push(line);
+        let excess = lines.len().saturating_sub(MAX_LINES);
+        lines.drain(..excess);
+        write_lines_atomic(&path, &lines)
+    })
+}
+
+/// 读取全部选择记录（不存在视为空；格式不对的行跳过），返回 (时间戳, 选择)
+pub fn load(file: &str) -> Vec<(i64, Selection)> {
+    fs::read_to_string(file)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|l| {
+            let mut it = l.split('\t');
+            let ts = it.next()?.parse::<i64>().ok()?;
+            let rank = it.next()?.parse::<usize>().ok()?;
+            let query = it.next()?.to_string();
+            let path = it.next().filter(|p| !p.is_empty())?.to_string();
+            let passed_over = it.map(str::to_string).collect();
+            Some((
+                ts,
+                Selection {
+                    path,
+                    rank,
+                    query,
+                    passed_over,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// 每个路径的反馈分（未归一化，见模块文档）；file 为空或不存在时为空表
+pub(crate) fn scores_from_file(file: &str, half_life: f64, now: i64) -> HashMap<String, f64> {
+    let mut scores: HashMap<String, f64> = HashMap::new();
+    if file.is_empty() {
+        return scores;
+    }
+    for (ts, sel) in load(file) {
+        if sel.rank == 0 {
+            continue;
+        }
+        let r = sel.rank as f64;
+        let age = (now - ts).max(0) as f64;
+        let decay = 0.5f64.powf(age / half_life);
+        *scores.entry(sel.path).or_insert(0.0) += r / (r + 1.0) * decay;
+    }
+    scores
+}
+
+/// 选择反馈信号：按最大值缩放（没有反馈的路径不参与）
+pub struct FeedbackScorer {
+    scores: HashMap<String, f64>,
+}
+
+impl FeedbackScorer {
+    /// 从选择记录文件读出反馈分（按 half_life 衰减到 now）
+    pub fn load(file: &str, half_life: f64, now: i64) -> Self {
+        Self {
+            scores: scores_from_file(file, half_life, now),
+        }
+    }
+}
+
+impl Scorer for FeedbackScorer {
+    fn name(&self) -> &str {
+        "feedback"
+    }
+
+    fn score(&self, path: &str, _ctx: &ScoreContext) -> Option<f64> {
+        self.scores.get(path).copied()
+    }
+
+    fn normalize(&self) -> Normalize {
+        Normalize::Max
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sel(path: &str, rank: usize, passed_over: &[&str]) -> Selection {
+        Selection {
+            path: path.into(),
+            rank,
+            query: String::new(),
+            passed_over: passed_over.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let (root, ctx) = AppContext::for_test("roundtrip");
+        let mut s = sel("/w/deep", 2, &["/a", "/b"]);
+        s.query = "de".into();
+        record_at(&ctx, &s, 100).unwrap();
+        record_at(&ctx, &sel("/w/top", 0, &[]), 200).unwrap();
+
+        let file = feedback_path(&ctx).to_string_lossy().into_owned();
+        assert_eq!(load(&file), vec![(100, s), (200, sel("/w/top", 0, &[]))]);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn low_picks_score_higher_and_decay() {
+        let (root, ctx) = AppContext::for_test("scores");
+        record_at(&ctx, &sel("/top", 0, &[]), 1000).unwrap();
+        record_at(&ctx, &sel("/deep", 3, &["/top", "/x", "/y"]), 1000).unwrap();
+        record_at(&ctx, &sel("/deep", 3, &["/top", "/x", "/y"]), 1000).unwrap();
+        record_at(&ctx, &sel("/old", 3, &["/top", "/x", "/y"]), 0).unwrap();
+
+        let file = feedback_path(&ctx).to_string_lossy().into_owned();
+        let s = scores_from_file(&file, 1000.0, 1000);
+        assert!(!s.contains_key("/top"));
+        assert!((s["/deep"] - 1.5).abs() < 1e-9);
+        // 过了一个半衰期，只剩一半
+        assert!((s["/old"] - 0.375).abs() < 1e-9);
+        assert!(scores_from_file("", 1000.0, 1000).is_empty());
+        let _ = fs::remove_dir_all(root);
+    }
+}
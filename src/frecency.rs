@@ -1,26 +1,134 @@
 //! Frecency：频次 × 时效衰减 打分（单文件实现 + 测试）
 //! - 批量评分：从事件时间戳向量计算分数
 //! - 在线增量：常数时间更新 score
-//! - 索引聚合：多目录 Top-N / 清理 / 容量上限
-//! 约定：时间戳单位为秒；半衰期 > 0；未来事件按 1.0 处理（不放大）
-
+//! - 索引聚合：多目录 Top-N / 清理 / 容量上限；按需同时记下每个目录的访问时段分布（见 src/temporal.rs）
+//!
+//! 可选模型（配置 `frecency_model`，见 `FrecencyKind`）：
+//! - `decay`（默认）：Σ 0.5^(dt / half_life)
+//! - `zoxide`：访问次数（rank）× 按最近一次访问分档的倍率（1 小时内 4、1 天内 2、1 周内 0.5、更早 0.25）；
+//!   全部目录的 rank 之和超过 `ZOXIDE_MAX_AGE` 时整体按比例缩小，低于 1 的目录被遗忘
+//! - `window`：最近 `window` 秒内的访问次数（窗口外的访问不计）
+//! - `dual`：短期（half_life）+ 长期（long_half_life）两个衰减和相加，
+//!   最近常去的和长期常去的目录都不会被对方完全压下去
+//!
+//! 约定：时间戳单位为秒；半衰期 / 窗口 > 0；未来事件按 1.0 处理（不放大）
+//!
+//! 摘要（`FrecencySummary`）：历史保留策略丢掉旧访问时，把它们折叠成每个目录一条
+//! “访问次数 + 最近时刻 + 衰减到该时刻的短期 / 长期分 + 访问时段分布”，索引读回后 decay / dual / zoxide
+//! 的分数和时段信号都不变；window 模型只数访问行，所以修剪时不会折叠还在窗口内的访问（见 src/retention.rs）。
+
+use crate::temporal::TimePattern;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+
+/// dual 模型的默认长期半衰期：90 天
+pub const DEFAULT_LONG_HALF_LIFE: f64 = 90.0 * 24.0 * 3600.0;
+/// window 模型的默认窗口：30 天
+pub const DEFAULT_WINDOW: f64 = 30.0 * 24.0 * 3600.0;
+/// zoxide 模型：rank 总和的上限（同 zoxide 的 `_ZO_MAXAGE` 默认值）
+const ZOXIDE_MAX_AGE: f64 = 10_000.0;
+
+/// Frecency 模型种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrecencyKind {
+    /// 指数衰减：Σ 0.5^(dt / half_life)
+    #[default]
+    Decay,
+    /// zoxide 风格：rank × 分档倍率，总分超限时整体老化
+    Zoxide,
+    /// 滑动窗口内的访问次数
+    Window,
+    /// 短期 + 长期两个半衰期
+    Dual,
+}
 
-/// Frecency 模型：只有一个参数——半衰期（秒）
-#[derive(Debug, Clone, Copy)]
+impl FrecencyKind {
+    /// 配置里的名字
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FrecencyKind::Decay => "decay",
+            FrecencyKind::Zoxide => "zoxide",
+            FrecencyKind::Window => "window",
+            FrecencyKind::Dual => "dual",
+        }
+    }
+}
+
+impl FromStr for FrecencyKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "decay" => Ok(FrecencyKind::Decay),
+            "zoxide" => Ok(FrecencyKind::Zoxide),
+            "window" => Ok(FrecencyKind::Window),
+            "dual" => Ok(FrecencyKind::Dual),
+            other => Err(format!(
+                "未知的 frecency 模型: {other}（decay / zoxide / window / dual）"
+            )),
+        }
+    }
+}
+
+/// Frecency 模型：种类 + 参数（半衰期 / 长期半衰期 / 窗口，单位秒；各模型只用到其中一部分）
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Frecency {
+    kind: FrecencyKind,
     half_life_secs: f64,
+    long_half_life_secs: f64,
+    window_secs: f64,
+}
+
+fn assert_positive(v: f64, what: &str) {
+    assert!(v.is_finite() && v > 0.0, "{what} must be > 0");
 }
 
 impl Frecency {
-    /// 创建模型（half_life_secs 必须 > 0）
+    /// 创建 decay 模型（half_life_secs 必须 > 0）
     pub fn new(half_life_secs: f64) -> Self {
-        assert!(
-            half_life_secs.is_finite() && half_life_secs > 0.0,
-            "half_life_secs must be > 0"
-        );
-        Self { half_life_secs }
+        assert_positive(half_life_secs, "half_life_secs");
+        Self {
+            kind: FrecencyKind::Decay,
+            half_life_secs,
+            long_half_life_secs: DEFAULT_LONG_HALF_LIFE,
+            window_secs: DEFAULT_WINDOW,
+        }
+    }
+
+    /// 换一种模型（参数不变）
+    pub fn with_kind(mut self, kind: FrecencyKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// dual 模型的长期半衰期（必须 > 0）
+    pub fn with_long_half_life(mut self, secs: f64) -> Self {
+        assert_positive(secs, "long_half_life_secs");
+        self.long_half_life_secs = secs;
+        self
+    }
+
+    /// window 模型的窗口长度（必须 > 0）
+    pub fn with_window(mut self, secs: f64) -> Self {
+        assert_positive(secs, "window_secs");
+        self.window_secs = secs;
+        self
+    }
+
+    /// 模型种类
+    pub fn kind(&self) -> FrecencyKind {
+        self.kind
+    }
+
+    /// 半衰期（秒）
+    pub fn half_life_secs(&self) -> f64 {
+        self.half_life_secs
+    }
+
+    /// window 模型的窗口长度（秒）
+    pub fn window_secs(&self) -> f64 {
+        self.window_secs
     }
 
     /// 衰减权重：0.5 ^ (dt / half_life)
@@ -33,15 +141,146 @@ impl Frecency {
         }
     }
 
-    /// 批量评分： score = Σ 0.5^((now - t_i)/half_life)
-    pub fn batch_score(&self, mut events: Vec<i64>, now: i64) -> f64 {
+    /// dual 模型的长期衰减权重
+    #[inline]
+    fn long_weight(&self, dt_secs: f64) -> f64 {
+        if dt_secs <= 0.0 {
+            1.0
+        } else {
+            0.5f64.powf(dt_secs / self.long_half_life_secs)
+        }
+    }
+
+    /// 批量评分（按模型，见模块文档），如 decay： score = Σ 0.5^((now - t_i)/half_life)
+    ///
+    /// 只有一个目录的访问；zoxide 的老化看的是全部目录，多个目录要用 `batch_scores` 一起算
+    pub fn batch_score(&self, events: Vec<i64>, now: i64) -> f64 {
+        let dts = events.iter().map(|&t| (now - t) as f64);
+        match self.kind {
+            FrecencyKind::Decay => dts.map(|dt| self.weight(dt)).sum(),
+            FrecencyKind::Dual => dts.map(|dt| self.weight(dt) + self.long_weight(dt)).sum(),
+            FrecencyKind::Window => dts.filter(|&dt| dt < self.window_secs).count() as f64,
+            FrecencyKind::Zoxide => {
+                let events: Vec<(&str, i64)> = events.iter().map(|&t| ("", t)).collect();
+                self.batch_scores(&events, now)
+                    .get("")
+                    .copied()
+                    .unwrap_or(0.0)
+            }
+        }
+    }
+
+    /// 多目录批量评分：events 为 (目录, 访问时刻)，返回每个目录的分数（被 zoxide 老化遗忘的目录不在结果里）。
+    ///
+    /// zoxide 按时间顺序重放全部访问，rank 之和超限时和 `FrecencyIndex` 一样整体老化；其余模型按目录分别算
+    pub fn batch_scores<'a>(&self, events: &[(&'a str, i64)], now: i64) -> HashMap<&'a str, f64> {
+        if self.kind != FrecencyKind::Zoxide {
+            let mut by_dir: HashMap<&str, Vec<i64>> = HashMap::new();
+            for &(d, t) in events {
+                by_dir.entry(d).or_default().push(t);
+            }
+            return by_dir
+                .into_iter()
+                .map(|(d, ts)| (d, self.batch_score(ts, now)))
+                .collect();
+        }
+        let mut sorted = events.to_vec();
+        sorted.sort_by_key(|&(_, t)| t);
+        // 目录 -> (rank, 最近访问)
+        let mut ranks: HashMap<&str, (f64, i64)> = HashMap::new();
+        let mut total = 0.0;
+        for (d, t) in sorted {
+            let e = ranks.entry(d).or_insert((0.0, t));
+            e.0 += 1.0;
+            e.1 = e.1.max(t);
+            total += 1.0;
+            if total > ZOXIDE_MAX_AGE {
+                total = zoxide_age(&mut ranks, total, |e| &mut e.0);
+            }
+        }
+        ranks
+            .into_iter()
+            .map(|(d, (rank, last))| (d, rank * zoxide_multiplier((now - last) as f64)))
+            .collect()
+    }
+}
+
+/// 一个目录被折叠掉的旧访问（见模块文档）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrecencySummary {
+    /// 访问次数
+    pub visits: u64,
+    /// 最近一次访问时刻
+    pub last_ts: i64,
+    /// 按 half_life 衰减到 last_ts 的分数
+    pub score: f64,
+    /// 按 long_half_life 衰减到 last_ts 的分数
+    pub long_score: f64,
+    /// 访问时段分布（见 src/temporal.rs）
+    pub hours: TimePattern,
+}
+
+impl Frecency {
+    /// 把一批访问（连同之前的摘要）折叠成一条摘要；没有任何访问时返回 None
+    pub fn summarize(
+        &self,
+        prev: &[FrecencySummary],
+        mut events: Vec<i64>,
+    ) -> Option<FrecencySummary> {
+        // 短期 / 长期两份分都要留着：用 dual 模型聚合
+        let model = self.with_kind(FrecencyKind::Dual);
+        let mut st = FrecencyState::new();
+        let mut hours = TimePattern::default();
+        for p in prev {
+            st.merge_summary(p, &model);
+            hours.merge(&p.hours);
+        }
         events.sort_unstable();
-        let mut s = 0.0;
-        for &t in &events {
-            let dt = (now - t) as f64;
-            s += self.weight(dt);
+        for ts in events {
+            st.observe(ts, &model);
+            hours.observe(ts);
         }
-        s
+        st.initialized.then_some(FrecencySummary {
+            visits: st.visits,
+            last_ts: st.last_ts,
+            score: st.score,
+            long_score: st.long_score,
+            hours,
+        })
+    }
+}
+
+/// zoxide 老化（rank 之和 total 超过上限时调用）：所有 rank 按比例缩小到上限的 90%，
+/// 低于 1 的目录被遗忘；返回老化后的 rank 之和
+fn zoxide_age<K, V>(map: &mut HashMap<K, V>, total: f64, rank: impl Fn(&mut V) -> &mut f64) -> f64 {
+    let factor = 0.9 * ZOXIDE_MAX_AGE / total;
+    map.retain(|_, v| {
+        let r = rank(v);
+        *r *= factor;
+        *r >= 1.0
+    });
+    rank_sum(map.values_mut().map(|v| *rank(v)))
+}
+
+/// rank 之和：排好序再加，结果与 HashMap 的遍历顺序无关，
+/// 索引和批量评分才会在同一次访问上触发老化
+fn rank_sum(ranks: impl Iterator<Item = f64>) -> f64 {
+    let mut v: Vec<f64> = ranks.collect();
+    v.sort_by(f64::total_cmp);
+    v.into_iter().sum()
+}
+
+/// zoxide 的分档倍率：距最近一次访问 1 小时内 4、1 天内 2、1 周内 0.5、更早 0.25
+fn zoxide_multiplier(dt_secs: f64) -> f64 {
+    const HOUR: f64 = 3600.0;
+    if dt_secs < HOUR {
+        4.0
+    } else if dt_secs < 24.0 * HOUR {
+        2.0
+    } else if dt_secs < 7.0 * 24.0 * HOUR {
+        0.5
+    } else {
+        0.25
     }
 }
 
@@ -51,11 +290,24 @@ impl Frecency {
 ///   score = score * decay + 1
 /// 查询 now：
 ///   score_now = score * 0.5^((now - last_ts)/half_life)
+///
+/// 其余模型：zoxide 的 score 是不衰减的 rank；dual 另记一份长期分。
+/// window 要记下窗口内的每个访问时刻，放在 `FrecencyIndex` 里（按目录），单独的状态上查询 window 分恒为 0。
 #[derive(Debug, Clone, Copy)]
 pub struct FrecencyState {
     pub score: f64,
     pub last_ts: i64,
+    /// 累计访问次数（不衰减）
+    pub visits: u64,
     initialized: bool,
+    /// dual：长期半衰期下的分数（同样聚合到 last_ts）
+    long_score: f64,
+}
+
+impl Default for FrecencyState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl FrecencyState {
@@ -63,16 +315,22 @@ impl FrecencyState {
         Self {
             score: 0.0,
             last_ts: 0,
+            visits: 0,
             initialized: false,
+            long_score: 0.0,
         }
     }
 
     /// 记录一次访问。
     /// - 正常（ts >= last_ts）：把已有分数衰减到 ts，再 +1，并前移 last_ts。
     /// - 乱序（ts < last_ts）：视作“与 last_ts 同时发生”，**只 +1，不衰减，也不回拨 last_ts**。
+    ///
+    /// zoxide 模型的 rank 不随时间衰减，只 +1。
     pub fn observe(&mut self, ts: i64, model: &Frecency) {
+        self.visits += 1;
         if !self.initialized {
             self.score = 1.0;
+            self.long_score = 1.0;
             self.last_ts = ts;
             self.initialized = true;
             return;
@@ -81,29 +339,93 @@ impl FrecencyState {
         let dt_raw = ts - self.last_ts;
         if dt_raw >= 0 {
             // 正常顺序
-            let decay = model.weight(dt_raw as f64);
-            self.score = self.score * decay + 1.0;
+            let dt = dt_raw as f64;
+            if model.kind == FrecencyKind::Zoxide {
+                self.score += 1.0;
+            } else {
+                self.score = self.score * model.weight(dt) + 1.0;
+            }
+            if model.kind == FrecencyKind::Dual {
+                self.long_score = self.long_score * model.long_weight(dt) + 1.0;
+            }
             self.last_ts = ts;
         } else {
             // 乱序事件：不做时间衰减，不修改 last_ts
             self.score += 1.0;
+            self.long_score += 1.0;
         }
     }
 
+    /// 并入一条摘要：两边都衰减到较晚的那个时刻再相加（zoxide 的 rank 直接加访问次数）
+    pub fn merge_summary(&mut self, s: &FrecencySummary, model: &Frecency) {
+        if s.visits == 0 {
+            return;
+        }
+        if !self.initialized {
+            self.score = 0.0;
+            self.long_score = 0.0;
+            self.last_ts = s.last_ts;
+            self.initialized = true;
+        }
+        let last = self.last_ts.max(s.last_ts);
+        let mine = (last - self.last_ts) as f64;
+        let theirs = (last - s.last_ts) as f64;
+        self.score = if model.kind == FrecencyKind::Zoxide {
+            self.score + s.visits as f64
+        } else {
+            self.score * model.weight(mine) + s.score * model.weight(theirs)
+        };
+        self.long_score =
+            self.long_score * model.long_weight(mine) + s.long_score * model.long_weight(theirs);
+        self.visits += s.visits;
+        self.last_ts = last;
+    }
+
     /// 在 now 的分数（只读）
     pub fn score_at(&self, now: i64, model: &Frecency) -> f64 {
         if !self.initialized {
             return 0.0;
         }
         let dt = (now - self.last_ts).max(0) as f64;
-        self.score * model.weight(dt)
+        match model.kind {
+            FrecencyKind::Decay => self.score * model.weight(dt),
+            FrecencyKind::Dual => {
+                self.score * model.weight(dt) + self.long_score * model.long_weight(dt)
+            }
+            FrecencyKind::Zoxide => self.score * zoxide_multiplier(dt),
+            // 窗口内的访问时刻由 FrecencyIndex 记录
+            FrecencyKind::Window => 0.0,
+        }
+    }
+}
+
+/// 某目录在 now 的分数：window 模型按 recent 里的访问时刻计数，其余模型看聚合状态
+fn score_with_window(
+    st: &FrecencyState,
+    recent: Option<&VecDeque<i64>>,
+    now: i64,
+    model: &Frecency,
+) -> f64 {
+    if model.kind != FrecencyKind::Window {
+        return st.score_at(now, model);
     }
+    recent.map_or(0.0, |r| {
+        r.iter()
+            .filter(|&&t| ((now - t) as f64) < model.window_secs)
+            .count() as f64
+    })
 }
 
 /// 多目录聚合索引
 pub struct FrecencyIndex {
     model: Frecency,
     map: HashMap<String, FrecencyState>,
+    /// 访问时段分布（与 map 的键相同）；只有 `with_patterns` 打开时才记录，否则为 None
+    patterns: Option<HashMap<String, TimePattern>>,
+    /// window：每个目录最近的访问时刻（早于 last_ts - window 的会被丢弃；其余模型为空）
+    recent: HashMap<String, VecDeque<i64>>,
+    /// zoxide：全部目录的 rank 之和（用于整体老化）
+    total_rank: f64,
 }
 
 impl FrecencyIndex {
@@ -111,23 +433,121 @@ impl FrecencyIndex {
         Self {
             model,
             map: HashMap::new(),
+            patterns: None,
+            recent: HashMap::new(),
+            total_rank: 0.0,
         }
     }
 
+    /// 同时记录每个目录的访问时段分布（时段信号要用；默认不记，省下每次访问的开销）
+    pub fn with_patterns(mut self) -> Self {
+        self.patterns.get_or_insert_with(HashMap::new);
+        self
+    }
+
+    /// 是否记录了访问时段分布
+    pub fn has_patterns(&self) -> bool {
+        self.patterns.is_some()
+    }
+
+    /// 索引使用的模型
+    pub fn model(&self) -> &Frecency {
+        &self.model
+    }
+
+    /// 索引中的目录数
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// 遍历索引中的所有目录（无序）
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.map.keys().map(String::as_str)
+    }
+
     /// 记录某目录一次访问
     pub fn record_visit<S: Into<String>>(&mut self, dir: S, ts: i64) {
-        let entry = self
-            .map
-            .entry(dir.into())
-            .or_insert_with(FrecencyState::new);
+        let dir = dir.into();
+        if let Some(patterns) = &mut self.patterns {
+            patterns.entry(dir.clone()).or_default().observe(ts);
+        }
+        if self.model.kind == FrecencyKind::Window {
+            let recent = self.recent.entry(dir.clone()).or_default();
+            recent.push_back(ts);
+            // 窗口只会往后移：队首已经滑出窗口的访问以后也不会再计入
+            let last = self.map.get(&dir).map_or(ts, |st| st.last_ts.max(ts));
+            while let Some(&t) = recent.front() {
+                if ((last - t) as f64) < self.model.window_secs {
+                    break;
+                }
+                recent.pop_front();
+            }
+        }
+        let entry = self.map.entry(dir).or_default();
         entry.observe(ts, &self.model);
+        if self.model.kind == FrecencyKind::Zoxide {
+            self.total_rank += 1.0;
+            if self.total_rank > ZOXIDE_MAX_AGE {
+                self.age();
+            }
+        }
+    }
+
+    /// 并入某目录的摘要（历史保留策略折叠掉的旧访问）
+    pub fn record_summary<S: Into<String>>(&mut self, dir: S, summary: &FrecencySummary) {
+        let dir = dir.into();
+        if let Some(patterns) = &mut self.patterns {
+            patterns
+                .entry(dir.clone())
+                .or_default()
+                .merge(&summary.hours);
+        }
+        let entry = self.map.entry(dir).or_default();
+        entry.merge_summary(summary, &self.model);
+        if self.model.kind == FrecencyKind::Zoxide {
+            self.total_rank += summary.visits as f64;
+            if self.total_rank > ZOXIDE_MAX_AGE {
+                self.age();
+            }
+        }
+    }
+
+    /// zoxide 老化（见 `zoxide_age`）
+    fn age(&mut self) {
+        self.total_rank = zoxide_age(&mut self.map, self.total_rank, |st| &mut st.score);
+        self.after_retain();
+    }
+
+    /// 删掉条目后同步时段分布与 rank 总和
+    fn after_retain(&mut self) {
+        if let Some(patterns) = &mut self.patterns {
+            patterns.retain(|k, _| self.map.contains_key(k));
+        }
+        self.recent.retain(|k, _| self.map.contains_key(k));
+        if self.model.kind == FrecencyKind::Zoxide {
+            self.total_rank = rank_sum(self.map.values().map(|st| st.score));
+        }
+    }
+
+    /// 某目录的聚合状态（访问次数 / 最近访问时刻）
+    pub fn state(&self, dir: &str) -> Option<&FrecencyState> {
+        self.map.get(dir)
+    }
+
+    /// 某目录的访问时段分布（索引没有打开 `with_patterns` 时总是 None）
+    pub fn pattern(&self, dir: &str) -> Option<&TimePattern> {
+        self.patterns.as_ref()?.get(dir)
     }
 
     /// 某目录在 now 的分数
     pub fn score_at(&self, dir: &str, now: i64) -> f64 {
         self.map
             .get(dir)
-            .map(|st| st.score_at(now, &self.model))
+            .map(|st| score_with_window(st, self.recent.get(dir), now, &self.model))
             .unwrap_or(0.0)
     }
 
@@ -136,7 +556,10 @@ impl FrecencyIndex {
         let mut v: Vec<(String, f64)> = self
             .map
             .iter()
-            .map(|(k, st)| (k.clone(), st.score_at(now, &self.model)))
+            .map(|(k, st)| {
+                let s = score_with_window(st, self.recent.get(k), now, &self.model);
+                (k.clone(), s)
+            })
             .collect();
         v.sort_by(
             |a, b| match b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal) {
@@ -152,8 +575,10 @@ impl FrecencyIndex {
 
     /// 清理：低于阈值的条目
     pub fn prune_below(&mut self, now: i64, threshold: f64) {
+        let (recent, model) = (&self.recent, &self.model);
         self.map
-            .retain(|_, st| st.score_at(now, &self.model) >= threshold);
+            .retain(|k, st| score_with_window(st, recent.get(k), now, model) >= threshold);
+        self.after_retain();
     }
 
     /// 容量上限：保留 now 分数最高的前 max_entries
@@ -164,7 +589,12 @@ impl FrecencyIndex {
         let mut pairs: Vec<(&String, f64)> = self
             .map
             .iter()
-            .map(|(k, st)| (k, st.score_at(now, &self.model)))
+            .map(|(k, st)| {
+                (
+                    k,
+                    score_with_window(st, self.recent.get(k), now, &self.model),
+                )
+            })
             .collect();
         pairs.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
 
@@ -177,6 +607,7 @@ impl FrecencyIndex {
             }
         }
         self.map.retain(|k, _| keep.contains_key(k));
+        self.after_retain();
     }
 }
 
@@ -302,4 +733,145 @@ mod tests {
         let keys: Vec<_> = idx.top_n(now, 10).into_iter().map(|x| x.0).collect();
         assert_eq!(keys, vec!["C".to_string(), "B".to_string()]);
     }
+
+    /// 同一段历史：20 次两个月前的 old、50 次十天前的 burst、30 天每天一次的 steady、刚刚 4 次的 new
+    fn replay(model: Frecency, now: i64) -> (FrecencyIndex, HashMap<&'static str, Vec<i64>>) {
+        const MIN: i64 = 60;
+        const DAY: i64 = 86_400;
+        let mut events: HashMap<&str, Vec<i64>> = HashMap::new();
+        events.insert("old", (0..20).map(|i| now - 60 * DAY + i * MIN).collect());
+        events.insert("burst", (0..50).map(|i| now - 10 * DAY + i * MIN).collect());
+        events.insert(
+            "steady",
+            (0..30).map(|k| now - k * DAY - 120 * MIN).collect(),
+        );
+        events.insert("new", (1..=4).map(|i| now - i * 10 * MIN).collect());
+
+        let mut all: Vec<(i64, &str)> = events
+            .iter()
+            .flat_map(|(d, ts)| ts.iter().map(move |&t| (t, *d)))
+            .collect();
+        all.sort_unstable();
+        let mut idx = FrecencyIndex::new(model);
+        for (t, d) in all {
+            idx.record_visit(d, t);
+        }
+        (idx, events)
+    }
+
+    #[test]
+    fn models_rank_same_history_differently() {
+        const DAY: f64 = 86_400.0;
+        let now = 100_000_000i64;
+        let base = Frecency::new(7.0 * DAY);
+        let order = |model: Frecency| -> Vec<String> {
+            let (idx, events) = replay(model, now);
+            // 增量与批量一致
+            for (d, ts) in events {
+                let batch = model.batch_score(ts, now);
+                let incr = idx.score_at(d, now);
+                assert!(
+                    approx_eq(batch, incr, 1e-9),
+                    "{model:?} {d}: {batch} vs {incr}"
+                );
+            }
+            idx.top_n(now, 10).into_iter().map(|x| x.0).collect()
+        };
+
+        // decay：十天前的一大串仍然最高，两个月前的几乎归零
+        assert_eq!(order(base), ["burst", "steady", "new", "old"]);
+        // zoxide：超过一周的只剩 0.25 倍，每天都去的 steady 和刚去过的 new 排前面
+        assert_eq!(
+            order(base.with_kind(FrecencyKind::Zoxide)),
+            ["steady", "new", "burst", "old"]
+        );
+        // window（7 天）：窗口外的访问完全不计
+        let window = base.with_kind(FrecencyKind::Window).with_window(7.0 * DAY);
+        assert_eq!(order(window), ["steady", "new", "burst", "old"]);
+        let (idx, _) = replay(window, now);
+        assert_eq!(idx.score_at("steady", now), 7.0);
+        assert_eq!(idx.score_at("burst", now), 0.0);
+        // dual（7 天 + 90 天）：长期常去的 old 又排到了只去过几次的 new 前面
+        let dual = base
+            .with_kind(FrecencyKind::Dual)
+            .with_long_half_life(90.0 * DAY);
+        assert_eq!(order(dual), ["burst", "steady", "old", "new"]);
+    }
+
+    #[test]
+    fn state_stays_copy() {
+        // FrecencyState 是公开类型：window 的访问时刻放在索引里，状态本身保持 Copy
+        fn assert_copy<T: Copy>() {}
+        assert_copy::<FrecencyState>();
+    }
+
+    #[test]
+    fn zoxide_ages_total_rank() {
+        let model = Frecency::new(60_000.0).with_kind(FrecencyKind::Zoxide);
+        let mut idx = FrecencyIndex::new(model).with_patterns();
+        idx.record_visit("rare", 0);
+        assert!(idx.pattern("rare").is_some());
+        for i in 0..ZOXIDE_MAX_AGE as i64 {
+            idx.record_visit("busy", i);
+        }
+        // 第 10001 次访问触发老化：busy 缩到上限的 90%，rare 低于 1 被遗忘
+        assert!(idx.state("rare").is_none());
+        assert!(idx.pattern("rare").is_none());
+        let busy = idx.state("busy").unwrap().score;
+        assert!(
+            approx_eq(busy, 0.9 * ZOXIDE_MAX_AGE * 10_000.0 / 10_001.0, 1e-6),
+            "{busy}"
+        );
+        assert_eq!(idx.len(), 1);
+    }
+
+    #[test]
+    fn zoxide_batch_ages_like_the_index() {
+        let model = Frecency::new(60_000.0).with_kind(FrecencyKind::Zoxide);
+        // 40000 次访问、总和反复超限：早期去过几次的目录被遗忘，其余 rank 被多次缩小
+        let dirs = ["a", "b", "c", "d", "e", "f", "g", "h"];
+        let events: Vec<(&str, i64)> = (0..40_000i64)
+            .map(|i| {
+                let d = if i < 40 {
+                    dirs[(i % 8) as usize]
+                } else {
+                    dirs[(i % 3) as usize]
+                };
+                (d, i * 60)
+            })
+            .collect();
+        let now = 40_000 * 60;
+
+        let mut idx = FrecencyIndex::new(model);
+        for &(d, t) in &events {
+            idx.record_visit(d, t);
+        }
+        let batch = model.batch_scores(&events, now);
+        assert!(batch.len() < dirs.len());
+        for d in dirs {
+            let (a, b) = (batch.get(d).copied().unwrap_or(0.0), idx.score_at(d, now));
+            assert!(approx_eq(a, b, 1e-6), "{d}: {a} vs {b}");
+        }
+        // 单个目录也会老化
+        let single: Vec<i64> = (0..12_000).collect();
+        let mut one = FrecencyIndex::new(model);
+        single.iter().for_each(|&t| one.record_visit("x", t));
+        let b = model.batch_score(single, 12_000);
+        assert!(approx_eq(b, one.score_at("x", 12_000), 1e-6), "{b}");
+        assert!(b < 12_000.0 * 4.0);
+    }
+
+    #[test]
+    fn kind_names_round_trip() {
+        for k in [
+            FrecencyKind::Decay,
+            FrecencyKind::Zoxide,
+            FrecencyKind::Window,
+            FrecencyKind::Dual,
+        ] {
+            assert_eq!(k.as_str().parse::<FrecencyKind>(), Ok(k));
+        }
+        assert_eq!(" Zoxide ".parse::<FrecencyKind>(), Ok(FrecencyKind::Zoxide));
+        assert!("z".parse::<FrecencyKind>().is_err());
+    }
 }
@@ -2,18 +2,21 @@
 //! 历史子系统：统一管理 history_raw（原始日志）和 history_uniq（最近唯一列表）。
 //!
 //! 约定：
-//!   - history_raw: 每行 `<ts_secs>\t<abs_path>`
+//!   - history_raw: 每行 `<ts_secs>\t<abs_path>`；开头可能有保留策略折叠出的摘要行
+//!     `summary\t<visits>\t<last_ts>\t<score>\t<long_score>\t<hours>\t<abs_path>`（见 src/retention.rs）
 //!   - history_uniq: 每行一个 `<abs_path>`，从旧到新，同一路径最多出现一次
 //!
 //! 对外主要 API：
-//!   - log_visit(ctx, dir): 记录一次访问（写 raw + 更新 uniq）
+//!   - log_visit(ctx, dir): 记录一次访问（写 raw + 更新 uniq；命中忽略规则的目录不记录，见 src/ignore.rs）
 //!   - append_raw(ctx, dir): 仅写 raw（保留给测试/兼容）
 //!   - load_raw(ctx): 读 raw 为 HistoryEntry 列表
+//!   - remove_path(ctx, dir): 从 raw + uniq 中彻底删除某个路径（连同它的摘要行）
 //!
 //! 写入安全：
 //!   - 使用粗粒度文件锁 + 短暂重试/过期锁清理，降低并发写失败概率；
 //!   - 使用“临时文件 + rename”保证 history_uniq 的更新尽量原子。
 
+use crate::frecency::FrecencySummary;
 use crate::AppContext;
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, BufRead, BufReader, BufWriter, Write};
@@ -35,7 +38,7 @@ pub struct HistoryEntry {
 }
 
 /// 统一获取当前时间戳（秒）
-fn now_secs() -> i64 {
+pub(crate) fn now_secs() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
@@ -48,8 +51,10 @@ fn now_secs() -> i64 {
 /// - 这只是“写 raw 文件”的最小单位操作。
 /// - 不做加锁；外层应通过 `log_visit` 来保证并发安全。
 pub fn append_raw(ctx: &AppContext, dir: &str) -> io::Result<()> {
-    let ts_secs = now_secs();
+    append_raw_at(ctx, dir, now_secs())
+}
 
+fn append_raw_at(ctx: &AppContext, dir: &str, ts_secs: i64) -> io::Result<()> {
     let mut f = OpenOptions::new()
         .create(true)
         .append(true)
@@ -67,20 +72,36 @@ pub fn append_raw(ctx: &AppContext, dir: &str) -> io::Result<()> {
 ///   * 在同一把锁里更新 history_raw + history_uniq；
 ///   * 以后不管再加什么额外索引/缓存，都可以挂在这里，不改调用方。
 pub fn log_visit(ctx: &AppContext, dir: &str) -> io::Result<()> {
+    record_visit(ctx, dir, |_, _| ()).map(|_| ())
+}
+
+/// 与 `log_visit` 相同；写完后趁还持有历史锁，用实际写入的 `(规范化路径, 时间戳)` 调用 `then`
+/// 并返回它的结果。空路径 / 被忽略的路径不调用，返回 `None`。
+///
+/// 供 daemon 同步自己的内存索引：在锁里取文件指纹，才不会把别的进程紧接着的写入当成自己的。
+pub(crate) fn record_visit<R>(
+    ctx: &AppContext,
+    dir: &str,
+    then: impl FnOnce(&str, i64) -> R,
+) -> io::Result<Option<R>> {
     let dir = dir.trim();
     if dir.is_empty() {
         // 空路径直接忽略
-        return Ok(());
+        return Ok(None);
     }
 
     let dir = normalize_history_path(dir)?;
+    if ctx.ignore_rules().is_ignored(&dir) {
+        return Ok(None);
+    }
 
     with_history_lock(ctx, || {
+        let ts_secs = now_secs();
         // 1) 追加到 raw
-        append_raw(ctx, &dir)?;
+        append_raw_at(ctx, &dir, ts_secs)?;
         // 2) 更新 uniq（最近唯一列表）
         update_uniq_after_visit(ctx, &dir)?;
-        Ok(())
+        Ok(Some(then(&dir, ts_secs)))
     })
 }
 
@@ -90,7 +111,7 @@ pub fn log_visit(ctx: &AppContext, dir: &str) -> io::Result<()> {
 /// - 相对路径会基于当前工作目录转成绝对路径；
 /// - 如果目标存在，优先 canonicalize，去掉 `.` / `..` 并解析软链接；
 /// - 如果目标暂时不存在，则退化为词法级规范化，至少保证是绝对路径。
-fn normalize_history_path(dir: &str) -> io::Result<String> {
+pub(crate) fn normalize_history_path(dir: &str) -> io::Result<String> {
     let path = Path::new(dir);
     let abs = if path.is_absolute() {
         path.to_path_buf()
@@ -142,7 +163,6 @@ fn normalize_lexically(path: &Path) -> PathBuf {
 ///   - 写入临时文件，再原子 rename 覆盖原文件
 fn update_uniq_after_visit(ctx: &AppContext, dir: &str) -> io::Result<()> {
     let uniq_path = &ctx.paths.history_uniq;
-    let tmp_path = uniq_path.with_extension("tmp");
 
     // 1) 读旧 uniq
     let mut paths: Vec<String> = Vec::new();
@@ -171,29 +191,121 @@ fn update_uniq_after_visit(ctx: &AppContext, dir: &str) -> io::Result<()> {
     // 2) 追加当前目录
     paths.push(dir.to_string());
 
-    // 3) 写入临时文件
-    if let Some(parent) = uniq_path.parent() {
+    // 3) 写入临时文件，再原子替换
+    write_lines_atomic(uniq_path, &paths)?;
+
+    Ok(())
+}
+
+/// 从历史中彻底删除某个路径（raw 中所有该路径的行 + uniq 中的该行），返回删除的 raw 行数。
+///
+/// - 与 `log_visit` 共用同一把历史锁；
+/// - raw / uniq 都用“临时文件 + rename”整体替换。
+pub fn remove_path(ctx: &AppContext, dir: &str) -> io::Result<usize> {
+    let dir = dir.trim();
+    if dir.is_empty() {
+        return Ok(0);
+    }
+
+    with_history_lock(ctx, || {
+        let mut removed = 0;
+        rewrite_lines(&ctx.paths.history_raw, |line| {
+            let hit = parse_raw_line(line).is_some_and(|l| l.path() == dir);
+            if hit {
+                removed += 1;
+            }
+            !hit
+        })?;
+        rewrite_lines(&ctx.paths.history_uniq, |line| line.trim() != dir)?;
+        Ok(removed)
+    })
+}
+
+/// 按行过滤重写文件（保留 `keep` 返回 true 的行）；文件不存在时什么都不做。
+pub(crate) fn rewrite_lines(path: &Path, mut keep: impl FnMut(&str) -> bool) -> io::Result<()> {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let mut lines = Vec::new();
+    for line_res in BufReader::new(file).lines() {
+        let line = line_res?;
+        if keep(&line) {
+            lines.push(line);
+        }
+    }
+    write_lines_atomic(path, &lines)
+}
+
+/// 写入临时文件，再原子 rename 覆盖目标文件。
+pub(crate) fn write_lines_atomic(path: &Path, lines: &[String]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
+    let tmp_path = path.with_extension("tmp");
     {
         let file = File::create(&tmp_path)?;
         let mut writer = BufWriter::new(file);
-        for p in &paths {
-            writeln!(writer, "{p}")?;
+        for l in lines {
+            writeln!(writer, "{l}")?;
         }
         writer.flush()?;
     }
+    fs::rename(&tmp_path, path)
+}
+
+/// history_raw 里的一行
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum RawLine<'a> {
+    /// 一次访问：`<ts_secs>\t<abs_path>`
+    Visit(i64, &'a str),
+    /// 保留策略折叠掉的旧访问（摘要带 168 格的时段分布，装箱后访问行不用跟着占这么大）
+    Summary(Box<FrecencySummary>, &'a str),
+}
+
+impl<'a> RawLine<'a> {
+    pub(crate) fn path(&self) -> &'a str {
+        match self {
+            RawLine::Visit(_, p) | RawLine::Summary(_, p) => p,
+        }
+    }
+}
 
-    // 4) 原子替换
-    fs::rename(&tmp_path, uniq_path)?;
+/// 摘要行的标记（放在时间戳的位置，旧版本读到时会当作无效行跳过）
+pub(crate) const SUMMARY_TAG: &str = "summary";
 
-    Ok(())
+/// 解析 history_raw 的一行；格式不对时返回 None
+pub(crate) fn parse_raw_line(line: &str) -> Option<RawLine<'_>> {
+    let (head, rest) = line.split_once('\t')?;
+    if head != SUMMARY_TAG {
+        let ts = head.trim().parse::<i64>().ok()?;
+        return Some(RawLine::Visit(ts, rest.trim()));
+    }
+    let mut it = rest.splitn(6, '\t');
+    let summary = FrecencySummary {
+        visits: it.next()?.parse().ok()?,
+        last_ts: it.next()?.parse().ok()?,
+        score: it.next()?.parse().ok()?,
+        long_score: it.next()?.parse().ok()?,
+        hours: it.next()?.parse().ok()?,
+    };
+    let path = it.next()?.trim();
+    (!path.is_empty()).then(|| RawLine::Summary(Box::new(summary), path))
+}
+
+/// 格式化一条摘要行
+pub(crate) fn format_summary_line(path: &str, s: &FrecencySummary) -> String {
+    format!(
+        "{SUMMARY_TAG}\t{}\t{}\t{}\t{}\t{}\t{path}",
+        s.visits, s.last_ts, s.score, s.long_score, s.hours
+    )
 }
 
 /// 读取 history_raw，解析为结构化列表。
 ///
 /// - 如果文件不存在，则返回空列表；
-/// - 解析失败的行会被跳过，不会导致整体报错。
+/// - 解析失败的行（以及摘要行）会被跳过，不会导致整体报错。
 pub fn load_raw(ctx: &AppContext) -> io::Result<Vec<HistoryEntry>> {
     parse_history_file(&ctx.paths.history_raw)
 }
@@ -335,7 +447,7 @@ fn history_lock_path(ctx: &AppContext) -> PathBuf {
 }
 
 /// 在“历史锁”保护下执行闭包，用于所有写历史的高层操作。
-fn with_history_lock<F, T>(ctx: &AppContext, f: F) -> io::Result<T>
+pub(crate) fn with_history_lock<F, T>(ctx: &AppContext, f: F) -> io::Result<T>
 where
     F: FnOnce() -> io::Result<T>,
 {
@@ -347,60 +459,9 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{AppContext, EffectiveConfig, Paths};
+    use crate::temporal::TimePattern;
+    use crate::AppContext;
     use std::env;
-    use std::process;
-
-    fn test_config() -> EffectiveConfig {
-        EffectiveConfig {
-            limit: 20,
-            half_life: 7.0 * 24.0 * 3600.0,
-            threshold: 0.0,
-            ignore_re: None,
-            check_dir: true,
-            uniq_decay: 0.85,
-            w_frecency: 0.7,
-            w_uniq: 0.3,
-        }
-    }
-
-    fn make_test_ctx(name: &str) -> (PathBuf, AppContext) {
-        let uniq = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        let root = env::temp_dir().join(format!(
-            "cdh_history_test_{}_{}_{}",
-            name,
-            process::id(),
-            uniq
-        ));
-
-        let paths = Paths {
-            config_dir: root.join("config"),
-            data_dir: root.join("data"),
-            state_dir: root.join("state"),
-            cache_dir: root.join("cache"),
-            history_raw: root.join("data").join("history").join("history_raw"),
-            history_uniq: root.join("data").join("history").join("history_uniq"),
-        };
-
-        fs::create_dir_all(&paths.config_dir).unwrap();
-        fs::create_dir_all(&paths.data_dir).unwrap();
-        fs::create_dir_all(&paths.state_dir).unwrap();
-        fs::create_dir_all(&paths.cache_dir).unwrap();
-        if let Some(parent) = paths.history_raw.parent() {
-            fs::create_dir_all(parent).unwrap();
-        }
-
-        (
-            root,
-            AppContext {
-                paths,
-                config: test_config(),
-            },
-        )
-    }
 
     fn read_lines(path: &Path) -> Vec<String> {
         fs::read_to_string(path)
@@ -412,7 +473,7 @@ mod tests {
 
     #[test]
     fn log_visit_writes_raw_and_uniq() {
-        let (root, ctx) = make_test_ctx("writes_raw_and_uniq");
+        let (root, ctx) = AppContext::for_test("writes_raw_and_uniq");
         let dir = root.join("visited_dir");
         fs::create_dir_all(&dir).unwrap();
 
@@ -420,9 +481,7 @@ mod tests {
 
         let raw = fs::read_to_string(&ctx.paths.history_raw).unwrap();
         let raw_line = raw.trim();
-        let mut parts = raw_line.splitn(2, '\t');
-        let ts = parts.next().unwrap();
-        let path = parts.next().unwrap();
+        let (ts, path) = raw_line.split_once('\t').unwrap();
 
         assert!(ts.parse::<i64>().is_ok());
         assert_eq!(path, dir.to_string_lossy());
@@ -436,7 +495,7 @@ mod tests {
 
     #[test]
     fn log_visit_moves_existing_path_to_end_of_uniq() {
-        let (root, ctx) = make_test_ctx("moves_existing_path");
+        let (root, ctx) = AppContext::for_test("moves_existing_path");
         let dir_a = root.join("a");
         let dir_b = root.join("b");
         fs::create_dir_all(&dir_a).unwrap();
@@ -459,7 +518,7 @@ mod tests {
 
     #[test]
     fn log_visit_returns_error_when_uniq_path_is_invalid() {
-        let (root, mut ctx) = make_test_ctx("uniq_open_error");
+        let (root, mut ctx) = AppContext::for_test("uniq_open_error");
         let bad_parent = root.join("not_a_dir");
         let dir = root.join("visited_dir");
 
@@ -476,7 +535,7 @@ mod tests {
 
     #[test]
     fn log_visit_normalizes_relative_path_to_absolute() {
-        let (root, ctx) = make_test_ctx("normalize_relative_path");
+        let (root, ctx) = AppContext::for_test("normalize_relative_path");
         let workspace = root.join("workspace");
         let nested = workspace.join("nested");
         let target = workspace.join("target_dir");
@@ -497,11 +556,68 @@ mod tests {
             .to_string();
 
         let raw = fs::read_to_string(&ctx.paths.history_raw).unwrap();
-        let raw_path = raw.trim().splitn(2, '\t').nth(1).unwrap();
+        let raw_path = raw.trim().split_once('\t').unwrap().1;
 
         assert_eq!(raw_path, expected);
         assert_eq!(read_lines(&ctx.paths.history_uniq), vec![expected]);
 
         let _ = fs::remove_dir_all(root);
     }
+
+    #[test]
+    fn remove_path_drops_raw_lines_and_uniq_entry() {
+        let (root, ctx) = AppContext::for_test("remove_path");
+        let dir_a = root.join("a");
+        let dir_b = root.join("b");
+        fs::create_dir_all(&dir_a).unwrap();
+        fs::create_dir_all(&dir_b).unwrap();
+
+        log_visit(&ctx, dir_a.to_str().unwrap()).unwrap();
+        log_visit(&ctx, dir_b.to_str().unwrap()).unwrap();
+        log_visit(&ctx, dir_a.to_str().unwrap()).unwrap();
+
+        let removed = remove_path(&ctx, dir_a.to_str().unwrap()).unwrap();
+        assert_eq!(removed, 2);
+
+        let raw = load_raw(&ctx).unwrap();
+        assert_eq!(raw.len(), 1);
+        assert_eq!(raw[0].path, dir_b);
+        assert_eq!(
+            read_lines(&ctx.paths.history_uniq),
+            vec![dir_b.to_string_lossy().to_string()]
+        );
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn summary_lines_parse_and_are_removed_with_path() {
+        let (root, ctx) = AppContext::for_test("summary_lines");
+        let mut hours = TimePattern::default();
+        hours.observe(1000);
+        let s = FrecencySummary {
+            visits: 12,
+            last_ts: 1000,
+            score: 3.25,
+            long_score: 9.5,
+            hours,
+        };
+        let line = format_summary_line("/w/a b", &s);
+        assert_eq!(
+            parse_raw_line(&line),
+            Some(RawLine::Summary(Box::new(s), "/w/a b"))
+        );
+        assert_eq!(parse_raw_line("12\t/w/x"), Some(RawLine::Visit(12, "/w/x")));
+        assert_eq!(parse_raw_line("summary\t1\t2\t/w/x"), None);
+        assert_eq!(parse_raw_line("summary\t1\t2\t1\t1\t999:1\t/w/x"), None);
+
+        let lines = vec![line, "1001\t/w/a b".into(), "1002\t/w/c".into()];
+        write_lines_atomic(&ctx.paths.history_raw, &lines).unwrap();
+        // 摘要行不算访问：load_raw 跳过，删除路径时一起删掉
+        assert_eq!(load_raw(&ctx).unwrap().len(), 2);
+        assert_eq!(remove_path(&ctx, "/w/a b").unwrap(), 2);
+        assert_eq!(read_lines(&ctx.paths.history_raw), vec!["1002\t/w/c"]);
+
+        let _ = fs::remove_dir_all(root);
+    }
 }
@@ -0,0 +1,346 @@
+// src/ignore.rs
+//! 记录时的忽略规则：命中的目录 `cdh log` 直接跳过，不会写进任何历史文件。
+//!
+//! 规则来源（`IgnoreRules::load`；每个进程只加载一次，见 `AppContext::ignore_rules`）：
+//!   - 配置 `log_ignore_re`（字符串或数组；环境变量 `CDH_LOG_IGNORE_RE`）：正则，在完整绝对路径里查找；
+//!   - 配置 `log_ignore`（字符串或数组；环境变量 `CDH_LOG_IGNORE`，`:` 分隔）：glob；
+//!   - CONFIG/ignore：gitignore 风格，一行一个 glob，`#` 开头为注释，`!` 开头表示重新放行。
+//!
+//! glob 语法：`*` / `?` 不跨 `/`，`**` 跨任意层，`[abc]` / `[!abc]` 字符集；`~/` 展开为 `$HOME`。
+//! 以 `/` 开头的规则从根目录匹配完整路径，其余规则匹配路径末尾的若干段
+//! （`node_modules` 命中任何叫这个名字的目录，`clients/acme` 命中任何 `.../clients/acme`）。
+//!
+//! 规则命中某个目录时，它下面的子目录一起忽略。glob 按顺序（先配置、后文件）判断，最后命中的一条说了算，
+//! 所以 `!` 可以从前面忽略的目录里放行一部分；正则命中则总是忽略。
+//!
+//! 无效的规则（不合法的正则 / glob）直接跳过，不在 cd 钩子里报错；`cdh ignore check` 会把它们列出来。
+//! daemon 在启动时加载一次，改了规则要重启 daemon。
+//!
+//! `cdh ignore purge` 把同样的规则应用到已有的历史（raw / uniq / 选择反馈 / 搜索历史）。
+
+use crate::feedback;
+use crate::history::{parse_raw_line, with_history_lock, write_lines_atomic};
+use crate::queries;
+use crate::{AppContext, EffectiveConfig, Paths};
+use regex::Regex;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 一条 glob 规则
+#[derive(Debug, Clone)]
+struct Glob {
+    re: Regex,
+    /// `!` 开头：重新放行
+    negate: bool,
+}
+
+/// 记录时的忽略规则
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    regexes: Vec<Regex>,
+    globs: Vec<Glob>,
+}
+
+/// gitignore 风格的规则文件：CONFIG/ignore
+pub fn ignore_file_path(paths: &Paths) -> PathBuf {
+    paths.config_dir.join("ignore")
+}
+
+impl IgnoreRules {
+    /// 配置里的规则 + CONFIG/ignore；无效的规则跳过，连同错误说明一起返回（由调用方决定是否报告）
+    pub fn load(paths: &Paths, config: &EffectiveConfig) -> (Self, Vec<String>) {
+        let mut rules = Self::default();
+        let mut invalid = Vec::new();
+        for re in &config.log_ignore_re {
+            match Regex::new(re) {
+                Ok(rx) => rules.regexes.push(rx),
+                Err(e) => invalid.push(format!("log_ignore_re {re}: {}", brief(&e))),
+            }
+        }
+        let home = std::env::var("HOME").unwrap_or_default();
+        let file = fs::read_to_string(ignore_file_path(paths)).unwrap_or_default();
+        for line in config
+            .log_ignore
+            .iter()
+            .map(String::as_str)
+            .chain(file.lines())
+        {
+            if let Err(e) = rules.add_glob(line, &home) {
+                invalid.push(format!("{}: {}", line.trim(), brief(&e)));
+            }
+        }
+        (rules, invalid)
+    }
+
+    /// 追加一行 glob 规则（空行 / 注释忽略）
+    fn add_glob(&mut self, line: &str, home: &str) -> Result<(), regex::Error> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+        let (negate, pat) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let mut pat = pat.trim_end_matches('/').to_string();
+        if pat == "~" || pat.starts_with("~/") {
+            pat = format!("{home}{}", &pat[1..]);
+        }
+        if pat.is_empty() {
+            return Ok(());
+        }
+        let re = match pat.strip_prefix('/') {
+            Some(rest) => format!("^/{}$", glob_to_regex(rest)),
+            None => format!("^(?:.*/)?{}$", glob_to_regex(&pat)),
+        };
+        self.globs.push(Glob {
+            re: Regex::new(&re)?,
+            negate,
+        });
+        Ok(())
+    }
+
+    /// 没有任何规则
+    pub fn is_empty(&self) -> bool {
+        self.regexes.is_empty() && self.globs.is_empty()
+    }
+
+    /// path（绝对路径）是否被忽略
+    pub fn is_ignored(&self, path: &str) -> bool {
+        if self.regexes.iter().any(|re| re.is_match(path)) {
+            return true;
+        }
+        let ancestors: Vec<&str> = Path::new(path)
+            .ancestors()
+            .filter_map(Path::to_str)
+            .filter(|p| !p.is_empty() && *p != "/")
+            .collect();
+        self.globs
+            .iter()
+            .rev()
+            .find(|g| ancestors.iter().any(|p| g.re.is_match(p)))
+            .is_some_and(|g| !g.negate)
+    }
+}
+
+/// regex 的错误信息是带插图的多行文本：只取最后一行的原因
+fn brief(e: &regex::Error) -> String {
+    let msg = e.to_string();
+    let last = msg.lines().last().unwrap_or_default();
+    last.strip_prefix("error: ").unwrap_or(last).to_string()
+}
+
+/// glob → 正则（不含首尾锚点）
+fn glob_to_regex(glob: &str) -> String {
+    let mut out = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    out.push_str("(?:.*/)?");
+                } else {
+                    out.push_str(".*");
+                }
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' => {
+                let class: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                let class = match class.strip_prefix('!') {
+                    Some(rest) => format!("^{rest}"),
+                    None => class,
+                };
+                out.push('[');
+                out.push_str(&class.replace('\\', "\\\\").replace('[', "\\["));
+                out.push(']');
+            }
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    out
+}
+
+/// 一次清理删掉的行数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PurgeStats {
+    /// history_raw（访问行与摘要行）
+    pub raw: usize,
+    /// history_uniq
+    pub uniq: usize,
+    /// 选择反馈 DATA/selections
+    pub selections: usize,
+    /// 搜索历史 STATE/queries
+    pub queries: usize,
+}
+
+impl PurgeStats {
+    pub fn total(&self) -> usize {
+        self.raw + self.uniq + self.selections + self.queries
+    }
+}
+
+/// 把规则应用到已有历史：删掉涉及被忽略目录的行；dry_run 时只统计不写入
+pub fn purge(ctx: &AppContext, rules: &IgnoreRules, dry_run: bool) -> io::Result<PurgeStats> {
+    let ignored = |p: &str| rules.is_ignored(p.trim());
+    with_history_lock(ctx, || {
+        Ok(PurgeStats {
+            raw: drop_lines(&ctx.paths.history_raw, dry_run, |l| {
+                parse_raw_line(l).is_some_and(|r| ignored(r.path()))
+            })?,
+            uniq: drop_lines(&ctx.paths.history_uniq, dry_run, ignored)?,
+            // `<ts>\t<名次>\t<查询>\t<选中的路径>[\t<跳过的路径>...]`：任何一个路径被忽略就整行删掉
+            selections: drop_lines(&feedback::feedback_path(ctx), dry_run, |l| {
+                l.split('\t').skip(3).any(ignored)
+            })?,
+            // `<ts>\t<查询>\t<选中的路径>`
+            queries: drop_lines(&queries::queries_path(ctx), dry_run, |l| {
+                l.splitn(3, '\t').nth(2).is_some_and(ignored)
+            })?,
+        })
+    })
+}
+
+/// 删掉 hit 返回 true 的行，返回删除的行数；文件不存在视为空，没有要删的行时不改动文件
+fn drop_lines(path: &Path, dry_run: bool, mut hit: impl FnMut(&str) -> bool) -> io::Result<usize> {
+    let text = match fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    let (dropped, kept): (Vec<&str>, Vec<&str>) = text.lines().partition(|l| hit(l));
+    if !dry_run && !dropped.is_empty() {
+        let kept: Vec<String> = kept.into_iter().map(str::to_string).collect();
+        write_lines_atomic(path, &kept)?;
+    }
+    Ok(dropped.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(lines: &[&str]) -> IgnoreRules {
+        let mut r = IgnoreRules::default();
+        for l in lines {
+            r.add_glob(l, "/home/me").unwrap();
+        }
+        r
+    }
+
+    #[test]
+    fn globs_match_paths_and_their_subdirectories() {
+        let r = rules(&[
+            "# 注释",
+            "/tmp",
+            "node_modules/",
+            "clients/acme*",
+            "~/secrets/**",
+            "*.priv",
+            "/mnt/[!p]*",
+        ]);
+        for p in [
+            "/tmp",
+            "/tmp/build/x",
+            "/w/app/node_modules/pkg",
+            "/w/clients/acme-corp/src",
+            "/home/me/secrets/k8s",
+            "/w/notes.priv",
+            "/mnt/vault",
+        ] {
+            assert!(r.is_ignored(p), "{p}");
+        }
+        for p in [
+            "/tmpfiles",
+            "/w/tmp",
+            "/w/clients/other",
+            "/home/me/secrets",
+            "/home/me/src",
+            "/mnt/public/x",
+        ] {
+            assert!(!r.is_ignored(p), "{p}");
+        }
+    }
+
+    #[test]
+    fn later_negation_wins_and_regexes_always_ignore() {
+        let mut r = rules(&["/w/clients", "!/w/clients/open-source"]);
+        assert!(r.is_ignored("/w/clients/acme"));
+        assert!(!r.is_ignored("/w/clients/open-source/lib"));
+        r.regexes.push(Regex::new("/lib$").unwrap());
+        assert!(r.is_ignored("/w/clients/open-source/lib"));
+        assert!(IgnoreRules::default().add_glob("[z-a]", "").is_err());
+    }
+
+    #[test]
+    fn log_visit_skips_and_purge_removes_ignored_paths() {
+        let (root, mut ctx) = AppContext::for_test("log_and_purge");
+        let keep = root.join("work");
+        let secret = root.join("vault").join("keys");
+        fs::create_dir_all(&keep).unwrap();
+        fs::create_dir_all(&secret).unwrap();
+
+        // 先在没有规则时记下两个目录，再加规则
+        crate::history::log_visit(&ctx, keep.to_str().unwrap()).unwrap();
+        crate::history::log_visit(&ctx, secret.to_str().unwrap()).unwrap();
+        let s = |p: &Path| p.to_string_lossy().into_owned();
+        feedback::record_at(
+            &ctx,
+            &feedback::Selection {
+                path: s(&keep),
+                rank: 1,
+                query: String::new(),
+                passed_over: vec![s(&secret)],
+            },
+            100,
+        )
+        .unwrap();
+        queries::record_at(&ctx, "ke", &[s(&secret)], 100).unwrap();
+
+        fs::write(ignore_file_path(&ctx.paths), "# 私密目录\nvault\n[z-a]\n").unwrap();
+        ctx.config.log_ignore_re = vec!["/never$".into(), "(".into()];
+        let (rules, invalid) = IgnoreRules::load(&ctx.paths, &ctx.config);
+        assert!(rules.is_ignored(&s(&secret)));
+        assert_eq!(invalid.len(), 2, "{invalid:?}");
+        assert!(invalid[0].starts_with("log_ignore_re ("));
+        assert!(invalid[1].starts_with("[z-a]: "));
+
+        // 规则每个进程只加载一次：这个上下文还在用旧的（空）规则
+        crate::history::log_visit(&ctx, keep.to_str().unwrap()).unwrap();
+        assert!(!ctx.ignore_rules().is_ignored(&s(&secret)));
+
+        // 新进程（新的上下文）之后的访问不会写入
+        let ctx = AppContext::new(ctx.paths.clone(), ctx.config.clone());
+        crate::history::log_visit(&ctx, secret.to_str().unwrap()).unwrap();
+        let raw = fs::read_to_string(&ctx.paths.history_raw).unwrap();
+        assert_eq!(raw.lines().count(), 3);
+
+        let dry = purge(&ctx, &rules, true).unwrap();
+        assert_eq!(
+            dry,
+            PurgeStats {
+                raw: 1,
+                uniq: 1,
+                selections: 1,
+                queries: 1,
+            }
+        );
+        assert_eq!(fs::read_to_string(&ctx.paths.history_raw).unwrap(), raw);
+
+        assert_eq!(purge(&ctx, &rules, false).unwrap(), dry);
+        for file in [
+            ctx.paths.history_raw.clone(),
+            ctx.paths.history_uniq.clone(),
+            feedback::feedback_path(&ctx),
+            queries::queries_path(&ctx),
+        ] {
+            let text = fs::read_to_string(&file).unwrap_or_default();
+            assert!(!text.contains("vault"), "{}: {text}", file.display());
+        }
+        assert_eq!(purge(&ctx, &rules, false).unwrap().total(), 0);
+        let _ = fs::remove_dir_all(root);
+    }
+}
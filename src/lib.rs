@@ -1,16 +1,30 @@
 //! cdh library entry: re-export modules and public APIs.
 
 pub mod app;
+pub mod bookmarks;
 pub mod config;
 pub mod controller;
+#[cfg(unix)]
+pub mod daemon;
+pub mod feedback;
 pub mod frecency;
 pub mod history;
+pub mod ignore;
 pub mod paths;
 pub mod picker;
+pub mod pins;
+pub mod proximity;
+pub mod queries;
 pub mod recommend;
+pub mod retention;
+pub mod scorer;
+pub mod temporal;
 
 pub use app::AppContext;
 pub use config::EffectiveConfig;
-pub use frecency::{Frecency, FrecencyIndex, FrecencyState};
+pub use frecency::{Frecency, FrecencyIndex, FrecencyKind, FrecencyState};
 pub use paths::Paths;
-pub use recommend::{recommend, recommend_paths, RecommendOpt, Recommendation};
+pub use recommend::{
+    recommend, recommend_paths, recommend_with_scorers, RecommendOpt, Recommendation,
+};
+pub use scorer::{Normalize, ScoreContext, Scorer};
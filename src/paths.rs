@@ -12,6 +12,8 @@
 //!   DATA/history/history_uniq
 
 use std::env;
+#[cfg(test)]
+use std::path::Path;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
@@ -76,4 +78,18 @@ impl Paths {
             history_uniq,
         }
     }
+
+    /// 测试用：所有目录都放在 root 下（config / data / state / cache，历史文件在 data/history/ 下）
+    #[cfg(test)]
+    pub(crate) fn under(root: &Path) -> Self {
+        let history_dir = root.join("data").join("history");
+        Self {
+            config_dir: root.join("config"),
+            data_dir: root.join("data"),
+            state_dir: root.join("state"),
+            cache_dir: root.join("cache"),
+            history_raw: history_dir.join("history_raw"),
+            history_uniq: history_dir.join("history_uniq"),
+        }
+    }
 }
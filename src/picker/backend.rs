@@ -0,0 +1,75 @@
+//! 终端后端：picker 主循环只通过这两个 trait 接触终端
+//! - `EventSource`：按键 / 鼠标 / 尺寸变化从哪来（真实终端为 crossterm）
+//! - `DrawTarget`：转义序列画到哪去、终端多大（真实终端为 stderr）
+//!
+//! 测试时换成脚本事件 + 虚拟屏幕（见 `headless`），不需要 TTY 就能驱动整个 picker。
+
+use crossterm::{event, terminal};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+pub(crate) use crossterm::event::Event;
+
+/// 事件来源
+pub(crate) trait EventSource {
+    /// 最多等待 timeout，返回是否有事件可读
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool>;
+    /// 读取下一个事件（poll 返回 true 后调用，不会阻塞）
+    fn read(&mut self) -> io::Result<Event>;
+    /// 当前时刻（无操作超时按它计时；测试用的事件源可以自己推进时间）
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// 绘制目标：接收 crossterm 输出的转义序列
+pub(crate) trait DrawTarget: Write {
+    /// 终端尺寸（列, 行）
+    fn size(&self) -> io::Result<(u16, u16)>;
+
+    /// 终端尺寸变化（真实终端自己知道，虚拟屏幕需要跟着调整缓冲区）
+    fn resized(&mut self, _w: u16, _h: u16) {}
+
+    /// 一帧画完，把缓冲的输出交给终端
+    fn present(&mut self) -> io::Result<()> {
+        self.flush()
+    }
+}
+
+/// 真实终端的事件（crossterm 读取 stdin 或 /dev/tty）
+pub(crate) struct TermEvents;
+
+impl EventSource for TermEvents {
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        event::poll(timeout)
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        event::read()
+    }
+}
+
+/// 画到 stderr（stdout 留给选中结果）
+pub(crate) struct StderrTarget(io::Stderr);
+
+impl StderrTarget {
+    pub(crate) fn new() -> Self {
+        Self(io::stderr())
+    }
+}
+
+impl Write for StderrTarget {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl DrawTarget for StderrTarget {
+    fn size(&self) -> io::Result<(u16, u16)> {
+        terminal::size()
+    }
+}
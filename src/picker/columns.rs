@@ -0,0 +1,211 @@
+//! 列表附加列：融合分（数字 / 条形）、访问次数、最近访问距今多久
+//!
+//! ```toml
+//! [picker]
+//! columns = ["bar", "visits", "age"]   # score / bar / visits / age；不写则默认隐藏
+//! ```
+//!
+//! 环境变量 `CDH_COLUMNS=bar,visits,age` 优先于配置文件；`none` 表示启动时隐藏。
+//! 运行时用 `c`（搜索模式 `F3`）显示 / 隐藏；没有配置时显示 bar / visits / age。
+
+use super::PickItem;
+use crate::config::ConfigFile;
+
+/// 条形图宽度（字符）
+const BAR_WIDTH: usize = 8;
+/// 部分填充的方块：1/8 .. 8/8
+const BAR_EIGHTHS: [char; 8] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Column {
+    /// 融合分，两位小数
+    Score,
+    /// 融合分，条形图
+    Bar,
+    /// 访问次数
+    Visits,
+    /// 最近访问距今多久
+    Age,
+}
+
+impl Column {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim() {
+            "score" => Some(Column::Score),
+            "bar" => Some(Column::Bar),
+            "visits" => Some(Column::Visits),
+            "age" => Some(Column::Age),
+            _ => None,
+        }
+    }
+
+    /// 列宽（显示宽度，不含分隔空格）
+    fn width(self) -> usize {
+        match self {
+            Column::Score => 4,
+            Column::Bar => BAR_WIDTH,
+            Column::Visits => 5,
+            Column::Age => 8,
+        }
+    }
+
+    /// 某条目在这一列的内容（已按列宽对齐）；没有元数据时留空
+    fn cell(self, item: &PickItem, now: i64, ascii: bool) -> String {
+        let text = match self {
+            Column::Score => item.score.map(|s| format!("{s:.2}")),
+            Column::Bar => item.score.map(|s| score_bar(s, ascii)),
+            Column::Visits => item.visits.filter(|&n| n > 0).map(|n| format!("×{n}")),
+            Column::Age => item.last_visit.map(|t| format_age(now - t)),
+        };
+        let text = text.unwrap_or_default();
+        let pad = self.width().saturating_sub(super::display_width(&text));
+        match self {
+            // 条形图左对齐，其余右对齐
+            Column::Bar => format!("{text}{}", " ".repeat(pad)),
+            _ => format!("{}{text}", " ".repeat(pad)),
+        }
+    }
+}
+
+/// 要显示的列 + 当前是否显示
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Columns {
+    pub enabled: Vec<Column>,
+    pub visible: bool,
+}
+
+impl Default for Columns {
+    fn default() -> Self {
+        Self {
+            enabled: vec![Column::Bar, Column::Visits, Column::Age],
+            visible: false,
+        }
+    }
+}
+
+impl Columns {
+    /// 配置文件 `[picker] columns` + `CDH_COLUMNS`；写了列就在启动时显示
+    pub fn from_config(file: &ConfigFile) -> Self {
+        let mut cols = Self::default();
+        let names = match std::env::var("CDH_COLUMNS") {
+            Ok(s) if !s.is_empty() => s.split(',').map(str::to_string).collect(),
+            _ => match file.get("picker", "columns") {
+                Some(v) => v.as_strings(),
+                None => return cols,
+            },
+        };
+        let mut enabled = Vec::new();
+        for name in &names {
+            match Column::from_name(name) {
+                Some(c) if !enabled.contains(&c) => enabled.push(c),
+                Some(_) => {}
+                None if name.trim() == "none" => {}
+                None => eprintln!("cdh: 未知的列: {name}（score / bar / visits / age）"),
+            }
+        }
+        if !enabled.is_empty() {
+            cols.enabled = enabled;
+            cols.visible = true;
+        }
+        cols
+    }
+
+    /// 附加列拼成的前缀（每列后跟两个空格）；隐藏时为空
+    pub(crate) fn render(&self, item: &PickItem, now: i64, ascii: bool) -> String {
+        if !self.visible {
+            return String::new();
+        }
+        self.enabled
+            .iter()
+            .map(|c| format!("{}  ", c.cell(item, now, ascii)))
+            .collect()
+    }
+}
+
+/// 0..1 的分数画成 BAR_WIDTH 宽的条形（按 1/8 字符精度）；ASCII 模式只用整格 `#`
+fn score_bar(score: f64, ascii: bool) -> String {
+    let score = score.clamp(0.0, 1.0);
+    if ascii {
+        return "#".repeat((score * BAR_WIDTH as f64).round() as usize);
+    }
+    let eighths = (score * (BAR_WIDTH * 8) as f64).round() as usize;
+    let mut s = "█".repeat(eighths / 8);
+    if eighths % 8 > 0 {
+        s.push(BAR_EIGHTHS[eighths % 8 - 1]);
+    }
+    s
+}
+
+/// 距今多久：刚刚 / N分钟前 / N小时前 / N天前 / N周前 / N个月前 / N年前
+fn format_age(secs: i64) -> String {
+    const MIN: i64 = 60;
+    const HOUR: i64 = 60 * MIN;
+    const DAY: i64 = 24 * HOUR;
+    match secs.max(0) {
+        s if s < MIN => "刚刚".to_string(),
+        s if s < HOUR => format!("{}分钟前", s / MIN),
+        s if s < DAY => format!("{}小时前", s / HOUR),
+        s if s < 7 * DAY => format!("{}天前", s / DAY),
+        s if s < 30 * DAY => format!("{}周前", s / (7 * DAY)),
+        s if s < 365 * DAY => format!("{}个月前", s / (30 * DAY)),
+        s => format!("{}年前", s / (365 * DAY)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_are_aligned_and_blank_without_metadata() {
+        let item = PickItem {
+            path: "/tmp".into(),
+            score: Some(0.5),
+            visits: Some(12),
+            last_visit: Some(1000),
+            bookmark: None,
+        };
+        let cols = Columns {
+            enabled: vec![Column::Score, Column::Bar, Column::Visits, Column::Age],
+            visible: true,
+        };
+        assert_eq!(
+            cols.render(&item, 1000 + 3 * 3600, false),
+            "0.50  ████        ×12   3小时前  "
+        );
+        assert_eq!(
+            cols.render(&PickItem::from("/tmp"), 0, false),
+            format!(
+                "{}  {}  {}  {}  ",
+                " ".repeat(4),
+                " ".repeat(8),
+                " ".repeat(5),
+                " ".repeat(8)
+            )
+        );
+        let hidden = Columns::default();
+        assert_eq!(hidden.render(&item, 0, false), "");
+    }
+
+    #[test]
+    fn bar_and_age_formatting() {
+        assert_eq!(score_bar(1.0, false), "████████");
+        assert_eq!(score_bar(0.0, false), "");
+        assert_eq!(score_bar(1.0 / 16.0, false), "▌");
+        assert_eq!(score_bar(0.5, true), "####");
+        assert_eq!(format_age(5), "刚刚");
+        assert_eq!(format_age(90), "1分钟前");
+        assert_eq!(format_age(3 * 86400), "3天前");
+        assert_eq!(format_age(400 * 86400), "1年前");
+    }
+
+    #[test]
+    fn config_enables_columns() {
+        let file =
+            ConfigFile::parse("[picker]\ncolumns = [\"score\", \"age\", \"bogus\"]\n").unwrap();
+        let cols = Columns::from_config(&file);
+        assert_eq!(cols.enabled, vec![Column::Score, Column::Age]);
+        assert!(cols.visible);
+        assert!(!Columns::from_config(&ConfigFile::default()).visible);
+    }
+}
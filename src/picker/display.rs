@@ -0,0 +1,155 @@
+//! 路径的显示变换（只影响显示，选中结果始终是真实的绝对路径）
+//! - `$HOME` 前缀显示为 `~`
+//! - 可选：当前目录之下的路径显示为相对路径（`./src`）
+//! - 放不下时按路径分段省略中间部分，保留开头几段（项目根）与最后一段
+//!
+//! ```toml
+//! [picker]
+//! tilde = true       # CDH_TILDE=0 关闭
+//! relative = false   # CDH_RELATIVE=1 打开
+//! ```
+
+use super::{display_width, trim_mid};
+use crate::config::ConfigFile;
+use std::env;
+
+/// 省略中间时，除前缀（`~` / `/` / `.`）外至少保留开头的段数
+const KEEP_HEAD: usize = 2;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PathDisplay {
+    /// 替换成 `~` 的家目录（None 表示不替换）
+    home: Option<String>,
+    /// 显示相对路径时的基准目录（None 表示不启用）
+    cwd: Option<String>,
+}
+
+impl PathDisplay {
+    pub fn new(home: Option<String>, cwd: Option<String>) -> Self {
+        let clean = |s: String| {
+            let t = s.trim_end_matches('/');
+            (!t.is_empty()).then(|| t.to_string())
+        };
+        Self {
+            home: home.and_then(clean),
+            cwd: cwd.and_then(clean),
+        }
+    }
+
+    /// 配置文件 `[picker] tilde / relative` + 环境变量 `CDH_TILDE` / `CDH_RELATIVE`
+    pub fn from_config(file: &ConfigFile) -> Self {
+        let flag = |env_key: &str, key: &str, default: bool| match env::var(env_key) {
+            Ok(v) if !v.is_empty() => v == "1" || v.eq_ignore_ascii_case("true"),
+            _ => file.get_bool("picker", key).unwrap_or(default),
+        };
+        let home = flag("CDH_TILDE", "tilde", true)
+            .then(|| env::var("HOME").ok())
+            .flatten();
+        let cwd = flag("CDH_RELATIVE", "relative", false)
+            .then(|| env::current_dir().ok())
+            .flatten()
+            .map(|p| p.to_string_lossy().into_owned());
+        Self::new(home, cwd)
+    }
+
+    /// 缩写后的路径（相对路径优先，其次 `~`）
+    pub fn shorten(&self, path: &str) -> String {
+        if let Some(rest) = self.cwd.as_deref().and_then(|c| strip_dir(path, c)) {
+            return if rest.is_empty() {
+                ".".to_string()
+            } else {
+                format!("./{rest}")
+            };
+        }
+        if let Some(rest) = self.home.as_deref().and_then(|h| strip_dir(path, h)) {
+            return if rest.is_empty() {
+                "~".to_string()
+            } else {
+                format!("~/{rest}")
+            };
+        }
+        path.to_string()
+    }
+
+    /// 缩写并省略到 width 以内
+    pub fn render(&self, path: &str, width: usize) -> String {
+        elide(&self.shorten(path), width)
+    }
+}
+
+/// `path` 是 `dir` 本身或其子路径时，返回 `dir/` 之后的部分
+fn strip_dir<'a>(path: &'a str, dir: &str) -> Option<&'a str> {
+    let rest = path.strip_prefix(dir)?;
+    if rest.is_empty() {
+        Some("")
+    } else {
+        rest.strip_prefix('/')
+    }
+}
+
+/// 按段省略中间部分：`~/code/proj/a/b/c/leaf` → `~/code/proj/…/c/leaf`
+///
+/// 先从靠近开头的中间段开始删（保留离末段最近的父目录），
+/// 仍放不下时再删开头的段，最后退化为按字符省略中间。
+pub(crate) fn elide(path: &str, width: usize) -> String {
+    if display_width(path) <= width {
+        return path.to_string();
+    }
+    let parts: Vec<&str> = path.split('/').collect();
+    // 前缀段：绝对路径的空串、`~`、`.`
+    let lead = usize::from(matches!(parts.first(), Some(&"" | &"~" | &".")));
+    let last = parts.len() - 1;
+    if last > lead {
+        let head_end = (lead + KEEP_HEAD).min(last);
+        let fits = |head: &[&str], tail: &[&str]| {
+            let s = format!("{}/…/{}", head.join("/"), tail.join("/"));
+            (display_width(&s) <= width).then_some(s)
+        };
+        // 逐个删掉中间段
+        for cut in head_end + 1..=last {
+            if let Some(s) = fits(&parts[..head_end], &parts[cut..]) {
+                return s;
+            }
+        }
+        // 再逐个删掉开头的段（保留前缀）
+        for keep in (lead.max(1)..head_end).rev() {
+            if let Some(s) = fits(&parts[..keep], &parts[last..]) {
+                return s;
+            }
+        }
+    }
+    trim_mid(path, width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tilde_and_relative_are_display_only() {
+        let d = PathDisplay::new(Some("/home/u/".into()), None);
+        assert_eq!(d.shorten("/home/u"), "~");
+        assert_eq!(d.shorten("/home/u/code/cdh"), "~/code/cdh");
+        assert_eq!(d.shorten("/home/user2"), "/home/user2");
+
+        let d = PathDisplay::new(Some("/home/u".into()), Some("/home/u/code".into()));
+        assert_eq!(d.shorten("/home/u/code"), ".");
+        assert_eq!(d.shorten("/home/u/code/cdh/src"), "./cdh/src");
+        assert_eq!(d.shorten("/home/u/notes"), "~/notes");
+
+        assert_eq!(PathDisplay::default().shorten("/home/u"), "/home/u");
+    }
+
+    #[test]
+    fn elide_keeps_root_and_leaf() {
+        let p = "~/code/proj/src/picker/deep/leaf";
+        assert_eq!(elide(p, 100), p);
+        assert_eq!(elide(p, 28), "~/code/proj/…/deep/leaf");
+        assert_eq!(elide(p, 22), "~/code/proj/…/leaf");
+        assert_eq!(elide(p, 14), "~/code/…/leaf");
+        assert_eq!(elide(p, 8), "~/…/leaf");
+        assert_eq!(elide("/srv/data/projects/x/y/z", 20), "/srv/data/…/x/y/z");
+        // 最后一段本身就放不下：按字符省略
+        assert_eq!(display_width(&elide("/a/very-long-directory-name", 10)), 10);
+    }
+}
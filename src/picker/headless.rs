@@ -0,0 +1,765 @@
+//! 无终端后端（仅测试）：脚本化的事件序列 + 解析转义序列的虚拟屏幕
+//!
+//! 虚拟屏幕只理解 picker 用到的那部分 ANSI：光标定位、清行 / 清屏、SGR（只记录是否高亮）、
+//! BEL；每次 `present` 把当前屏幕存成一帧纯文本，供快照测试比对。
+
+use super::backend::{DrawTarget, Event, EventSource};
+use super::{event_loop, PickItem, PickOpt, PickOutcome, Ui};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+use unicode_width::UnicodeWidthChar;
+
+/// 按顺序吐出预先写好的事件；用完后返回错误，避免测试卡在事件循环里
+///
+/// 自带一个虚拟时钟：只有空等时才前进（每次 poll 前进 poll 的 timeout），不依赖真实时间。
+pub(crate) struct ScriptedEvents {
+    events: VecDeque<Event>,
+    /// 虚拟时钟的起点
+    start: Instant,
+    /// 虚拟时钟已经走过的时间
+    elapsed: Duration,
+    /// 用完后再空等到 elapsed 到达这里（测试无操作超时），之后再报错
+    idle_until: Option<Duration>,
+}
+
+impl ScriptedEvents {
+    pub(crate) fn new(events: impl IntoIterator<Item = Event>) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+            start: Instant::now(),
+            elapsed: Duration::ZERO,
+            idle_until: None,
+        }
+    }
+
+    /// 事件用完后再空等 d（期间 poll 都返回没有事件，时钟随之前进）
+    pub(crate) fn then_idle(mut self, d: Duration) -> Self {
+        self.idle_until = Some(self.elapsed + d);
+        self
+    }
+}
+
+impl EventSource for ScriptedEvents {
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        if self.events.is_empty() {
+            if let Some(left) = self
+                .idle_until
+                .and_then(|t| t.checked_sub(self.elapsed))
+                .filter(|left| !left.is_zero())
+            {
+                self.elapsed += timeout.min(left);
+                return Ok(false);
+            }
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "事件脚本已用完",
+            ));
+        }
+        Ok(true)
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        self.events
+            .pop_front()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "事件脚本已用完"))
+    }
+
+    fn now(&self) -> Instant {
+        self.start + self.elapsed
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Cell {
+    ch: char,
+    /// 宽字符的右半格（输出文本时跳过）
+    cont: bool,
+    /// 有背景色或反色
+    hl: bool,
+}
+
+const BLANK: Cell = Cell {
+    ch: ' ',
+    cont: false,
+    hl: false,
+};
+
+/// 转义序列解析状态
+enum Parse {
+    Text,
+    Esc,
+    Csi(String),
+}
+
+/// 虚拟终端屏幕
+pub(crate) struct VirtualScreen {
+    w: u16,
+    h: u16,
+    cells: Vec<Vec<Cell>>,
+    col: u16,
+    row: u16,
+    bg: bool,
+    reverse: bool,
+    parse: Parse,
+    /// UTF-8 字符被拆到两次 write 时暂存的字节
+    pending: Vec<u8>,
+    /// 每次 present 时的屏幕文本
+    pub(crate) frames: Vec<String>,
+    pub(crate) beeps: usize,
+}
+
+impl VirtualScreen {
+    pub(crate) fn new(w: u16, h: u16) -> Self {
+        Self {
+            w,
+            h,
+            cells: vec![vec![BLANK; w as usize]; h as usize],
+            col: 0,
+            row: 0,
+            bg: false,
+            reverse: false,
+            parse: Parse::Text,
+            pending: Vec::new(),
+            frames: Vec::new(),
+            beeps: 0,
+        }
+    }
+
+    /// 当前屏幕文本：每行去掉行尾空格，行之间用 `\n` 连接
+    pub(crate) fn text(&self) -> String {
+        self.cells
+            .iter()
+            .map(|line| {
+                let s: String = line.iter().filter(|c| !c.cont).map(|c| c.ch).collect();
+                s.trim_end().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 带高亮（背景色 / 反色）的行号
+    pub(crate) fn highlighted_rows(&self) -> Vec<usize> {
+        (0..self.cells.len())
+            .filter(|&r| self.cells[r].iter().any(|c| c.hl && c.ch != ' '))
+            .collect()
+    }
+
+    fn put(&mut self, ch: char) {
+        match ch {
+            '\r' => self.col = 0,
+            '\n' => self.row = (self.row + 1).min(self.h.saturating_sub(1)),
+            '\x07' => self.beeps += 1,
+            c if c.is_control() => {}
+            c => {
+                let cw = c.width().unwrap_or(0) as u16;
+                if cw == 0 {
+                    return;
+                }
+                // 超出右边界的部分直接丢弃（picker 不依赖自动换行）
+                if self.row < self.h && self.col + cw <= self.w {
+                    let hl = self.bg || self.reverse;
+                    let line = &mut self.cells[self.row as usize];
+                    line[self.col as usize] = Cell {
+                        ch: c,
+                        cont: false,
+                        hl,
+                    };
+                    if cw == 2 {
+                        line[self.col as usize + 1] = Cell {
+                            ch: ' ',
+                            cont: true,
+                            hl,
+                        };
+                    }
+                }
+                self.col = self.col.saturating_add(cw);
+            }
+        }
+    }
+
+    fn clear_cells(&mut self, row: u16, from: u16, to: u16) {
+        if let Some(line) = self.cells.get_mut(row as usize) {
+            for c in line.iter_mut().take(to as usize).skip(from as usize) {
+                *c = BLANK;
+            }
+        }
+    }
+
+    fn csi(&mut self, params: &str, fin: char) {
+        // 私有序列（?25l 隐藏光标、?1049h 备用屏幕、?1000h 鼠标……）与屏幕内容无关
+        if params.starts_with('?') {
+            return;
+        }
+        let nums: Vec<u16> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+        let arg =
+            |i: usize, default: u16| nums.get(i).copied().filter(|&n| n > 0).unwrap_or(default);
+        match fin {
+            'H' | 'f' => {
+                self.row = (arg(0, 1) - 1).min(self.h.saturating_sub(1));
+                self.col = (arg(1, 1) - 1).min(self.w);
+            }
+            'A' => self.row = self.row.saturating_sub(arg(0, 1)),
+            'B' => self.row = (self.row + arg(0, 1)).min(self.h.saturating_sub(1)),
+            'C' => self.col = (self.col + arg(0, 1)).min(self.w),
+            'D' => self.col = self.col.saturating_sub(arg(0, 1)),
+            'G' => self.col = (arg(0, 1) - 1).min(self.w),
+            'K' => match nums[0] {
+                1 => self.clear_cells(self.row, 0, self.col + 1),
+                2 => self.clear_cells(self.row, 0, self.w),
+                _ => self.clear_cells(self.row, self.col, self.w),
+            },
+            'J' => match nums[0] {
+                2 | 3 => {
+                    for r in 0..self.h {
+                        self.clear_cells(r, 0, self.w);
+                    }
+                }
+                _ => {
+                    self.clear_cells(self.row, self.col, self.w);
+                    for r in self.row + 1..self.h {
+                        self.clear_cells(r, 0, self.w);
+                    }
+                }
+            },
+            'm' => self.sgr(&nums),
+            _ => {}
+        }
+    }
+
+    /// 只关心能让一行“看起来被选中”的属性：背景色与反色
+    fn sgr(&mut self, nums: &[u16]) {
+        let mut i = 0;
+        while i < nums.len() {
+            match nums[i] {
+                0 => {
+                    self.bg = false;
+                    self.reverse = false;
+                }
+                7 => self.reverse = true,
+                27 => self.reverse = false,
+                40..=47 | 100..=107 => self.bg = true,
+                49 => self.bg = false,
+                n @ (38 | 48) => {
+                    // 38;5;n / 38;2;r;g;b
+                    let skip = if nums.get(i + 1) == Some(&2) { 4 } else { 2 };
+                    if n == 48 {
+                        self.bg = true;
+                    }
+                    i += skip;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    fn feed(&mut self, s: &str) {
+        for ch in s.chars() {
+            match std::mem::replace(&mut self.parse, Parse::Text) {
+                Parse::Text if ch == '\x1b' => self.parse = Parse::Esc,
+                Parse::Text => self.put(ch),
+                Parse::Esc if ch == '[' => self.parse = Parse::Csi(String::new()),
+                // 其他 ESC 序列（如 ESC 7 / ESC 8）忽略
+                Parse::Esc => {}
+                Parse::Csi(params) if ('\x40'..='\x7e').contains(&ch) => self.csi(&params, ch),
+                Parse::Csi(mut params) => {
+                    params.push(ch);
+                    self.parse = Parse::Csi(params);
+                }
+            }
+        }
+    }
+}
+
+impl Write for VirtualScreen {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(s) => s.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        };
+        let bytes: Vec<u8> = self.pending.drain(..valid).collect();
+        self.feed(std::str::from_utf8(&bytes).expect("已校验"));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl DrawTarget for VirtualScreen {
+    fn size(&self) -> io::Result<(u16, u16)> {
+        Ok((self.w, self.h))
+    }
+
+    fn resized(&mut self, w: u16, h: u16) {
+        self.w = w;
+        self.h = h;
+        self.cells.resize(h as usize, vec![BLANK; w as usize]);
+        for line in &mut self.cells {
+            line.resize(w as usize, BLANK);
+        }
+        self.row = self.row.min(h.saturating_sub(1));
+        self.col = self.col.min(w);
+    }
+
+    fn present(&mut self) -> io::Result<()> {
+        self.frames.push(self.text());
+        Ok(())
+    }
+}
+
+/// 在 w×h 的虚拟屏幕上用脚本事件跑一遍 picker（备用屏幕模式，鼠标开启）
+pub(crate) fn run_headless(
+    items: &[&str],
+    opt: &PickOpt,
+    size: (u16, u16),
+    events: impl IntoIterator<Item = Event>,
+) -> (io::Result<PickOutcome>, VirtualScreen) {
+    run_scripted(items, opt, size, ScriptedEvents::new(events))
+}
+
+/// 同 `run_headless`，事件来源由调用方构造（如 `then_idle`）
+pub(crate) fn run_scripted(
+    items: &[&str],
+    opt: &PickOpt,
+    (w, h): (u16, u16),
+    events: ScriptedEvents,
+) -> (io::Result<PickOutcome>, VirtualScreen) {
+    let items = items.iter().map(|s| PickItem::from(*s)).collect();
+    run_items(items, opt, (w, h), events)
+}
+
+/// 同 `run_scripted`，条目带完整信息（书签名、分数等）
+pub(crate) fn run_items(
+    items: Vec<PickItem>,
+    opt: &PickOpt,
+    (w, h): (u16, u16),
+    mut events: ScriptedEvents,
+) -> (io::Result<PickOutcome>, VirtualScreen) {
+    let mut screen = VirtualScreen::new(w, h);
+    let mut ui = Ui::new(items, opt, w, h, 0);
+    let res = event_loop(&mut ui, &mut events, &mut screen, true);
+    (res, screen)
+}
+
+pub(crate) fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+pub(crate) fn chars(s: &str) -> Vec<Event> {
+    s.chars().map(|c| key(KeyCode::Char(c))).collect()
+}
+
+pub(crate) fn click(column: u16, row: u16) -> Event {
+    Event::Mouse(MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column,
+        row,
+        modifiers: KeyModifiers::NONE,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::picker::{PickEffect, QueryHistory, QueryPick, Watchdog};
+
+    fn items() -> Vec<String> {
+        (1..=25).map(|i| format!("/srv/proj/item{i:02}")).collect()
+    }
+
+    fn run(events: Vec<Event>) -> (io::Result<PickOutcome>, VirtualScreen) {
+        let items = items();
+        let refs: Vec<&str> = items.iter().map(String::as_str).collect();
+        run_headless(&refs, &PickOpt::default(), (40, 10), events)
+    }
+
+    /// 快照比对：期望值首行的换行只为排版，不参与比较
+    #[track_caller]
+    fn assert_frame(actual: &str, expected: &str) {
+        let expected = expected.strip_prefix('\n').unwrap_or(expected);
+        assert!(
+            actual == expected,
+            "画面不一致\n--- 实际 ---\n{actual}\n--- 期望 ---\n{expected}\n"
+        );
+    }
+
+    #[test]
+    fn virtual_screen_parses_cursor_and_clear() {
+        let mut s = VirtualScreen::new(8, 2);
+        write!(
+            s,
+            "\x1b[2;3H中x\x1b[1;1Habcdef\x1b[1;4H\x1b[K\x1b[7mZ\x1b[0m\x07"
+        )
+        .unwrap();
+        assert_eq!(s.text(), "abcZ\n  中x");
+        assert_eq!(s.highlighted_rows(), vec![0]);
+        assert_eq!(s.beeps, 1);
+    }
+
+    #[test]
+    fn snapshot_initial_and_paging() {
+        let mut ev = vec![key(KeyCode::Char('n')), key(KeyCode::Down)];
+        ev.push(key(KeyCode::Enter));
+        let (res, screen) = run(ev);
+        let out = res.unwrap();
+        assert_eq!(out.selected, vec!["/srv/proj/item08"]);
+        assert_eq!(out.rank, Some(7));
+        assert_eq!(out.passed_over.len(), 7);
+        assert_frame(
+            &screen.frames[0],
+            "
+
+
+╭ cdh • 第 1/5 页 • 共 25 条 ──────────╮
+│ 0 ) /srv/proj/item01                 │
+│ 1 ) /srv/proj/item02                 │
+│ 2 ) /srv/proj/item03                 │
+│ 3 ) /srv/proj/item04                 │
+│ 4 ) /srv/proj/item05                 │
+│ 5 ) /srv/proj/item06                 │
+╰ Enter 选 · q 退出 · h 帮助 · i 搜索  ╯",
+        );
+        assert_frame(
+            &screen.frames[2],
+            "
+
+
+╭ cdh • 第 2/5 页 • 共 25 条 ──────────╮
+│ 0 ) /srv/proj/item07                 │
+│ 1 ) /srv/proj/item08                 │
+│ 2 ) /srv/proj/item09                 │
+│ 3 ) /srv/proj/item10                 │
+│ 4 ) /srv/proj/item11                 │
+│ 5 ) /srv/proj/item12                 │
+╰ Enter 选 · q 退出 · h 帮助 · i 搜索  ╯",
+        );
+        assert_eq!(screen.highlighted_rows(), vec![4]);
+    }
+
+    #[test]
+    fn snapshot_search() {
+        let mut ev = vec![key(KeyCode::Char('i'))];
+        ev.extend(chars("2"));
+        ev.push(key(KeyCode::Down));
+        ev.push(key(KeyCode::Enter));
+        let (res, screen) = run(ev);
+        assert_frame(
+            &screen.frames[2],
+            "
+
+
+╭ cdh • 搜索 8/25 条 • 第 1/2 页 ──────╮
+│ 0 ) /srv/proj/item02                 │
+│ 1 ) /srv/proj/item12                 │
+│ 2 ) /srv/proj/item20                 │
+│ 3 ) /srv/proj/item21                 │
+│ 4 ) /srv/proj/item22                 │
+│ 5 ) /srv/proj/item23                 │
+╰ 搜索: 2▌ · Esc 返回 · Enter 选       ╯",
+        );
+        let out = res.unwrap();
+        assert_eq!(out.selected, vec!["/srv/proj/item12"]);
+        // 名次按过滤后的列表计算
+        assert_eq!(out.rank, Some(1));
+        assert_eq!(out.passed_over, vec!["/srv/proj/item02"]);
+    }
+
+    #[test]
+    fn paste_filters_and_caret_inserts_mid_query() {
+        let mut ev = vec![Event::Paste("item2\n".into()), key(KeyCode::Left)];
+        ev.extend(chars("1"));
+        ev.push(key(KeyCode::Enter));
+        let (res, screen) = run(ev);
+        assert_frame(
+            &screen.frames[3],
+            "
+
+
+╭ cdh • 搜索 1/25 条 • 第 1/1 页 ──────╮
+│ 0 ) /srv/proj/item12                 │
+│ 1 )                                  │
+│ 2 )                                  │
+│ 3 )                                  │
+│ 4 )                                  │
+│ 5 )                                  │
+╰ 搜索: item1▌2 · Esc 返回 · Enter 选  ╯",
+        );
+        assert_eq!(res.unwrap().selected, vec!["/srv/proj/item12"]);
+    }
+
+    #[test]
+    fn query_history_recall_and_remembered_focus() {
+        let opt = PickOpt {
+            queries: QueryHistory {
+                recent: vec!["item1".into(), "proj".into()],
+                picks: vec![QueryPick {
+                    query: "item1".into(),
+                    path: "/srv/proj/item14".into(),
+                    count: 3,
+                }],
+            },
+            ..PickOpt::default()
+        };
+        let items = items();
+        let refs: Vec<&str> = items.iter().map(String::as_str).collect();
+        // 空输入行 ↑ 召回最近一条，高亮落在以前用它选中的路径上
+        let ev = vec![
+            key(KeyCode::Char('i')),
+            key(KeyCode::Up),
+            key(KeyCode::Enter),
+        ];
+        let (res, screen) = run_headless(&refs, &opt, (40, 10), ev);
+        let out = res.unwrap();
+        assert_eq!(out.selected, vec!["/srv/proj/item14"]);
+        assert_eq!(out.query, "item1");
+        assert_frame(
+            &screen.frames[2],
+            "
+
+
+╭ cdh • 搜索 10/25 条 • 第 1/2 页 ─────╮
+│ 0 ) /srv/proj/item10                 │
+│ 1 ) /srv/proj/item11                 │
+│ 2 ) /srv/proj/item12                 │
+│ 3 ) /srv/proj/item13                 │
+│ 4 ) /srv/proj/item14                 │
+│ 5 ) /srv/proj/item15                 │
+╰ 搜索: item1▌ · Esc 返回 · Enter 选   ╯",
+        );
+        // Ctrl-R 依次往更早的查询走
+        let ctrl_r = Event::Key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        let ev = vec![
+            key(KeyCode::Char('i')),
+            ctrl_r.clone(),
+            ctrl_r,
+            key(KeyCode::Enter),
+        ];
+        let (res, _) = run_headless(&refs, &opt, (40, 10), ev);
+        assert_eq!(res.unwrap().query, "proj");
+    }
+
+    #[test]
+    fn watchdog_counts_down_then_times_out() {
+        // 默认 15 秒：事件源的虚拟时钟只在空等时前进，不用真的等
+        let opt = PickOpt {
+            watchdog: Watchdog::default(),
+            ..PickOpt::default()
+        };
+        let items = items();
+        let refs: Vec<&str> = items.iter().map(String::as_str).collect();
+        let events = ScriptedEvents::new([]).then_idle(Duration::from_secs(60));
+        let (res, screen) = run_scripted(&refs, &opt, (40, 10), events);
+        let out = res.unwrap();
+        assert!(out.timed_out);
+        assert!(out.selected.is_empty());
+        // 最后 5 秒每秒重画一次底栏
+        let countdown: Vec<&str> = screen
+            .frames
+            .iter()
+            .filter_map(|f| f.lines().last()?.split(" 秒后").next()?.rsplit(' ').next())
+            .filter(|s| s.parse::<u64>().is_ok())
+            .collect();
+        assert_eq!(countdown, ["5", "4", "3", "2", "1"]);
+        assert_frame(
+            screen.frames.last().unwrap(),
+            "
+
+
+╭ cdh • 第 1/5 页 • 共 25 条 ──────────╮
+│ 0 ) /srv/proj/item01                 │
+│ 1 ) /srv/proj/item02                 │
+│ 2 ) /srv/proj/item03                 │
+│ 3 ) /srv/proj/item04                 │
+│ 4 ) /srv/proj/item05                 │
+│ 5 ) /srv/proj/item06                 │
+╰ 1 秒后无操作自动退出 · 按任意键继续  ╯",
+        );
+
+        // 有过按键就不再计时：空等结束后是事件脚本用完，而不是超时
+        let events = ScriptedEvents::new([key(KeyCode::Down)]).then_idle(Duration::from_secs(60));
+        let (res, _) = run_scripted(&refs, &opt, (40, 10), events);
+        assert_eq!(res.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn search_without_matches_beeps() {
+        let mut ev = vec![key(KeyCode::Char('i'))];
+        ev.extend(chars("zz"));
+        ev.push(key(KeyCode::Enter));
+        ev.push(key(KeyCode::Esc));
+        ev.push(key(KeyCode::Char('q')));
+        let (res, screen) = run(ev);
+        assert!(res.unwrap().selected.is_empty());
+        assert_eq!(screen.beeps, 1);
+    }
+
+    #[test]
+    fn snapshot_resize() {
+        let ev = vec![
+            key(KeyCode::Char('n')),
+            Event::Resize(30, 14),
+            key(KeyCode::Char('q')),
+        ];
+        let (res, screen) = run(ev);
+        assert!(res.unwrap().selected.is_empty());
+        assert_frame(
+            &screen.frames[2],
+            "
+
+
+╭ cdh • 第 1/3 页 • 共 25 条 ╮
+│ 0 ) /srv/proj/item01       │
+│ 1 ) /srv/proj/item02       │
+│ 2 ) /srv/proj/item03       │
+│ 3 ) /srv/proj/item04       │
+│ 4 ) /srv/proj/item05       │
+│ 5 ) /srv/proj/item06       │
+│ 6 ) /srv/proj/item07       │
+│ 7 ) /srv/proj/item08       │
+│ 8 ) /srv/proj/item09       │
+│ 9 ) /srv/proj/item10       │
+╰ Enter 选 · q 退出 · h 帮助…╯",
+        );
+        // 光标仍在第 7 个条目上，只是换成了更高的一页
+        assert_eq!(screen.highlighted_rows(), vec![9]);
+    }
+
+    #[test]
+    fn snapshot_help_overlay() {
+        let ev = vec![
+            key(KeyCode::Char('h')),
+            key(KeyCode::Esc),
+            key(KeyCode::Char('q')),
+        ];
+        let (_, screen) = run(ev);
+        assert_frame(
+            &screen.frames[1],
+            "
+   ┌────────────────────────────────┐
+   │ 帮助（按键预设：default）      │
+   │                                │
+   │ 主界面：                       │
+   │   ↑ / k       上移（越界翻页） │
+   │   ↓ / j       下移（越界翻页） │
+   │   ← / p       上一页           │
+   │   → / n       下一页           │
+   │   Home        第一条           │
+   └────────────────────────────────┘",
+        );
+        assert_eq!(screen.frames[2], screen.frames[0]);
+    }
+
+    #[test]
+    fn mouse_double_click_selects() {
+        // 列表第一行在第 3 行（0 起）：第 4 个条目在第 6 行
+        let ev = vec![click(5, 6), click(5, 6)];
+        let (res, screen) = run(ev);
+        assert_eq!(res.unwrap().selected, vec!["/srv/proj/item04"]);
+        assert_eq!(screen.frames.len(), 2);
+        assert_eq!(screen.highlighted_rows(), vec![6]);
+        assert_frame(
+            &screen.frames[1],
+            "
+
+
+╭ cdh • 第 1/5 页 • 共 25 条 ──────────╮
+│ 0 ) /srv/proj/item01                 │
+│ 1 ) /srv/proj/item02                 │
+│ 2 ) /srv/proj/item03                 │
+│ 3 ) /srv/proj/item04                 │
+│ 4 ) /srv/proj/item05                 │
+│ 5 ) /srv/proj/item06                 │
+╰ Enter 选 · q 退出 · h 帮助 · i 搜索  ╯",
+        );
+    }
+
+    #[test]
+    fn pinned_rows_are_marked() {
+        let items = items();
+        let refs: Vec<&str> = items.iter().map(String::as_str).collect();
+        let opt = PickOpt {
+            actions: true,
+            pinned: vec!["/srv/proj/item01".into()],
+            ..PickOpt::default()
+        };
+        let mut ev = vec![key(KeyCode::Down), key(KeyCode::Down)];
+        ev.extend(chars("tq"));
+        let (res, screen) = run_headless(&refs, &opt, (40, 10), ev);
+        assert_eq!(
+            res.unwrap().effects,
+            vec![PickEffect::Pin("/srv/proj/item03".into())]
+        );
+        assert_frame(
+            &screen.frames[0],
+            "
+
+
+╭ cdh • 第 1/5 页 • 共 25 条 ──────────╮
+│▲0 ) /srv/proj/item01                 │
+│ 1 ) /srv/proj/item02                 │
+│ 2 ) /srv/proj/item03                 │
+│ 3 ) /srv/proj/item04                 │
+│ 4 ) /srv/proj/item05                 │
+│ 5 ) /srv/proj/item06                 │
+╰ Enter 选 · q 退出 · h 帮助 · i 搜索  ╯",
+        );
+        // 置顶后移到最前，同样带上标记
+        assert_frame(
+            &screen.frames[3],
+            "
+
+
+╭ cdh • 第 1/5 页 • 共 25 条 ──────────╮
+│▲0 ) /srv/proj/item03                 │
+│▲1 ) /srv/proj/item01                 │
+│ 2 ) /srv/proj/item02                 │
+│ 3 ) /srv/proj/item04                 │
+│ 4 ) /srv/proj/item05                 │
+│ 5 ) /srv/proj/item06                 │
+╰ 已置顶 /srv/proj/item03              ╯",
+        );
+    }
+
+    #[test]
+    fn bookmarks_are_tagged_and_searchable() {
+        let mut items: Vec<PickItem> = ["/w/alpha", "/w/platform/services/api"]
+            .map(PickItem::from)
+            .to_vec();
+        items[1].bookmark = Some("api".into());
+        let mut ev = vec![key(KeyCode::Char('i'))];
+        ev.extend(chars("@ap"));
+        ev.push(key(KeyCode::Enter));
+        let (res, screen) = run_items(items, &PickOpt::default(), (40, 8), ScriptedEvents::new(ev));
+        assert_frame(
+            &screen.frames[0],
+            "
+
+
+╭ cdh • 第 1/1 页 • 共 2 条 ───────────╮
+│ 0 ) /w/alpha                         │
+│ 1 ) @api /w/platform/services/api    │
+│ 2 )                                  │
+│ 3 )                                  │
+╰ Enter 选 · q 退出 · h 帮助 · i 搜索  ╯",
+        );
+        assert_frame(
+            &screen.frames[4],
+            "
+
+
+╭ cdh • 搜索 1/2 条 • 第 1/1 页 ───────╮
+│ 0 ) @api /w/platform/services/api    │
+│ 1 )                                  │
+│ 2 )                                  │
+│ 3 )                                  │
+╰ 搜索: @ap▌ · Esc 返回 · Enter 选     ╯",
+        );
+        assert_eq!(res.unwrap().selected, vec!["/w/platform/services/api"]);
+    }
+}
@@ -0,0 +1,754 @@
+//! 按键映射：按键组合（chord）→ 命名动作
+//! - 内置三套预设：default（原有按键）、vi、emacs
+//! - 配置文件 `[keys]` 覆盖主界面、`[keys.search]` 覆盖搜索模式；写了某个动作就整体替换该动作的按键
+//! - 帮助页和底栏提示都从这里生成，保证与实际行为一致
+//!
+//! ```toml
+//! [keys]
+//! preset = "vi"              # default / vi / emacs（也可用 CDH_KEYMAP 覆盖）
+//! quit = ["q", "ctrl-c"]
+//! page-next = "space"
+//!
+//! [keys.search]
+//! up = ["ctrl-k", "up"]
+//! ```
+
+use crate::config::ConfigFile;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// picker 里可以绑定按键的动作
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Up,
+    Down,
+    PagePrev,
+    PageNext,
+    First,
+    Last,
+    Select,
+    Search,
+    /// 退出搜索，回到主界面
+    Back,
+    Help,
+    Quit,
+    Preview,
+    Delete,
+    Pin,
+    Demote,
+    /// 显示 / 隐藏分数等附加列
+    Columns,
+    /// 多选模式下标记 / 取消标记条目
+    Mark,
+    // 以下只用于搜索输入行
+    CursorLeft,
+    CursorRight,
+    LineStart,
+    LineEnd,
+    KillToStart,
+    KillToEnd,
+    DeleteWord,
+    /// 召回更早的搜索查询（循环）
+    History,
+}
+
+/// 帮助页中的展示顺序
+const ACTIONS: [Action; 25] = [
+    Action::Up,
+    Action::Down,
+    Action::PagePrev,
+    Action::PageNext,
+    Action::First,
+    Action::Last,
+    Action::Select,
+    Action::Search,
+    Action::Back,
+    Action::Help,
+    Action::Quit,
+    Action::Preview,
+    Action::Delete,
+    Action::Pin,
+    Action::Demote,
+    Action::Columns,
+    Action::Mark,
+    Action::CursorLeft,
+    Action::CursorRight,
+    Action::LineStart,
+    Action::LineEnd,
+    Action::KillToStart,
+    Action::KillToEnd,
+    Action::DeleteWord,
+    Action::History,
+];
+
+impl Action {
+    /// 配置文件中使用的名字
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::PagePrev => "page-prev",
+            Action::PageNext => "page-next",
+            Action::First => "first",
+            Action::Last => "last",
+            Action::Select => "select",
+            Action::Search => "search",
+            Action::Back => "back",
+            Action::Help => "help",
+            Action::Quit => "quit",
+            Action::Preview => "preview",
+            Action::Delete => "delete",
+            Action::Pin => "pin",
+            Action::Demote => "demote",
+            Action::Columns => "columns",
+            Action::Mark => "mark",
+            Action::CursorLeft => "cursor-left",
+            Action::CursorRight => "cursor-right",
+            Action::LineStart => "line-start",
+            Action::LineEnd => "line-end",
+            Action::KillToStart => "kill-to-start",
+            Action::KillToEnd => "kill-to-end",
+            Action::DeleteWord => "delete-word",
+            Action::History => "history",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        ACTIONS.into_iter().find(|a| a.name() == name)
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Action::Up => "上移（越界翻页）",
+            Action::Down => "下移（越界翻页）",
+            Action::PagePrev => "上一页",
+            Action::PageNext => "下一页",
+            Action::First => "第一条",
+            Action::Last => "最后一条",
+            Action::Select => "选中并退出",
+            Action::Search => "进入搜索模式",
+            Action::Back => "返回主界面",
+            Action::Help => "打开帮助",
+            Action::Quit => "退出程序",
+            Action::Preview => "开关预览面板",
+            Action::Delete => "从历史删除（y 确认）",
+            Action::Pin => "置顶 / 取消置顶",
+            Action::Demote => "降级 / 取消降级",
+            Action::Columns => "显示 / 隐藏分数、次数、最近访问列",
+            Action::Mark => "标记 / 取消标记（多选）",
+            Action::CursorLeft => "光标左移",
+            Action::CursorRight => "光标右移",
+            Action::LineStart => "光标到行首",
+            Action::LineEnd => "光标到行尾",
+            Action::KillToStart => "删除到行首",
+            Action::KillToEnd => "删除到行尾",
+            Action::DeleteWord => "删除前一个词（空格、/ 分隔）",
+            Action::History => "上一条搜索历史（输入行为空时 ↑/↓ 也可以）",
+        }
+    }
+}
+
+/// 一个按键组合：键 + Ctrl/Alt（字符键忽略 Shift，大小写本身已区分）
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Chord {
+    code: KeyCode,
+    ctrl: bool,
+    alt: bool,
+}
+
+impl Chord {
+    const fn key(code: KeyCode) -> Self {
+        Self {
+            code,
+            ctrl: false,
+            alt: false,
+        }
+    }
+
+    const fn ch(c: char) -> Self {
+        Self::key(KeyCode::Char(c))
+    }
+
+    const fn ctrl(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            ctrl: true,
+            alt: false,
+        }
+    }
+
+    const fn alt(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            ctrl: false,
+            alt: true,
+        }
+    }
+
+    fn matches(&self, k: &KeyEvent) -> bool {
+        let (code, ctrl, alt) = normalize(k);
+        self.code == code && self.ctrl == ctrl && self.alt == alt
+    }
+
+    /// 解析 "j" / "ctrl-n" / "alt-<" / "shift-tab" / "f2" / "pagedown" 等写法
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut rest = s.trim();
+        let (mut ctrl, mut alt, mut shift) = (false, false, false);
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            // 至少留一个字符给按键本身（"ctrl--" 表示 Ctrl + '-'）
+            let strip = |p: &str| (lower.starts_with(p) && rest.len() > p.len()).then_some(p.len());
+            if let Some(n) = strip("ctrl-").or_else(|| strip("c-")) {
+                ctrl = true;
+                rest = &rest[n..];
+            } else if let Some(n) = strip("alt-").or_else(|| strip("m-")) {
+                alt = true;
+                rest = &rest[n..];
+            } else if let Some(n) = strip("shift-") {
+                shift = true;
+                rest = &rest[n..];
+            } else {
+                break;
+            }
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(if shift { c.to_ascii_uppercase() } else { c }),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" if shift => KeyCode::BackTab,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" | "bs" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                "space" => KeyCode::Char(' '),
+                f if f.starts_with('f') => KeyCode::F(f[1..].parse().ok().filter(|n| *n >= 1)?),
+                _ => return None,
+            },
+        };
+        let code = match code {
+            // Ctrl 组合统一用小写字母
+            KeyCode::Char(c) if ctrl => KeyCode::Char(c.to_ascii_lowercase()),
+            other => other,
+        };
+        Some(Self { code, ctrl, alt })
+    }
+
+    /// 帮助页 / 底栏里的显示名
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) if self.ctrl => c.to_ascii_uppercase().to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::BackTab => "Shift+Tab".to_string(),
+            KeyCode::Backspace => "Backspace".to_string(),
+            KeyCode::Delete => "Delete".to_string(),
+            KeyCode::Insert => "Insert".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::F(n) => format!("F{n}"),
+            other => format!("{other:?}"),
+        };
+        let mut out = String::new();
+        if self.ctrl {
+            out.push_str("Ctrl+");
+        }
+        if self.alt {
+            out.push_str("Alt+");
+        }
+        out.push_str(&key);
+        out
+    }
+}
+
+/// 把终端送来的按键规整成 (键, ctrl, alt)：字符键忽略 Shift，Ctrl+字母统一小写
+fn normalize(k: &KeyEvent) -> (KeyCode, bool, bool) {
+    let ctrl = k.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = k.modifiers.contains(KeyModifiers::ALT);
+    let code = match k.code {
+        KeyCode::Char(c) if ctrl => KeyCode::Char(c.to_ascii_lowercase()),
+        other => other,
+    };
+    (code, ctrl, alt)
+}
+
+/// 按键映射需要区分的两种输入上下文
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KeyContext {
+    /// 主界面：字符键可以直接绑定
+    Normal,
+    /// 搜索模式：可打印字符都进入查询，只有功能键 / 组合键会被查表
+    Search,
+}
+
+/// 一套完整的按键映射
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    /// 预设名（显示在帮助页标题里）
+    preset: String,
+    normal: Vec<(Action, Vec<Chord>)>,
+    search: Vec<(Action, Vec<Chord>)>,
+    /// 多选模式：标记键优先于与之共用按键的动作（如搜索模式的 Tab 选中）
+    multi: bool,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::preset("default").expect("内置预设")
+    }
+}
+
+impl Keymap {
+    /// 内置预设：default / vi / emacs
+    pub fn preset(name: &str) -> Option<Self> {
+        use Action::*;
+        use KeyCode::{Delete as Del, Down as D, End as E, Enter as Ent, Esc as X, Home as H};
+        use KeyCode::{Left as L, PageDown as PD, PageUp as PU, Right as R, Tab as T, Up as U};
+        let k = Chord::key;
+        let c = Chord::ch;
+        let ctrl = Chord::ctrl;
+        let alt = Chord::alt;
+        let f2 = k(KeyCode::F(2));
+        let f3 = k(KeyCode::F(3));
+
+        // 搜索模式的公共部分：字符都留给查询，只用功能键与 Ctrl 组合；
+        // ←/→ 在输入行里移动光标，翻页改用 PageUp/PageDown
+        let search_common = |extra: Vec<(Action, Vec<Chord>)>| {
+            let mut v = vec![
+                (CursorLeft, vec![k(L)]),
+                (CursorRight, vec![k(R)]),
+                (LineStart, vec![ctrl('a')]),
+                (LineEnd, vec![ctrl('e')]),
+                (KillToStart, vec![ctrl('u')]),
+                (KillToEnd, vec![ctrl('k')]),
+                (DeleteWord, vec![ctrl('w')]),
+                (History, vec![ctrl('r')]),
+                (Select, vec![k(Ent), k(T)]),
+                (Back, vec![k(X)]),
+                (Quit, vec![ctrl('c')]),
+                (Preview, vec![f2]),
+                (Delete, vec![k(Del)]),
+                (Pin, vec![ctrl('t')]),
+                (Demote, vec![ctrl('b')]),
+                (Columns, vec![f3]),
+                (Mark, vec![k(T)]),
+            ];
+            v.splice(0..0, extra);
+            v
+        };
+
+        let (normal, search) = match name {
+            "default" => (
+                vec![
+                    (Up, vec![k(U), c('k')]),
+                    (Down, vec![k(D), c('j')]),
+                    (PagePrev, vec![k(L), c('p')]),
+                    (PageNext, vec![k(R), c('n')]),
+                    (First, vec![k(H)]),
+                    (Last, vec![k(E)]),
+                    (Select, vec![k(Ent)]),
+                    (Search, vec![c('i')]),
+                    (Help, vec![c('h')]),
+                    (Quit, vec![c('q'), ctrl('c')]),
+                    (Preview, vec![c('v'), f2]),
+                    (Delete, vec![c('d'), k(Del)]),
+                    (Pin, vec![c('t')]),
+                    (Demote, vec![c('b')]),
+                    (Columns, vec![c('c'), f3]),
+                    (Mark, vec![c(' '), k(T)]),
+                ],
+                search_common(vec![
+                    (Up, vec![k(U), ctrl('p')]),
+                    (Down, vec![k(D), ctrl('n')]),
+                    (PagePrev, vec![k(PU)]),
+                    (PageNext, vec![k(PD)]),
+                    (First, vec![k(H)]),
+                    (Last, vec![k(E)]),
+                ]),
+            ),
+            "vi" => (
+                vec![
+                    (Up, vec![c('k'), k(U)]),
+                    (Down, vec![c('j'), k(D)]),
+                    (PagePrev, vec![c('h'), ctrl('u'), ctrl('b'), k(L), k(PU)]),
+                    (PageNext, vec![c('l'), ctrl('d'), ctrl('f'), k(R), k(PD)]),
+                    (First, vec![c('g'), k(H)]),
+                    (Last, vec![c('G'), k(E)]),
+                    (Select, vec![k(Ent)]),
+                    (Search, vec![c('/'), c('i')]),
+                    (Help, vec![c('?')]),
+                    (Quit, vec![c('q'), k(X), ctrl('c')]),
+                    (Preview, vec![c('v'), f2]),
+                    (Delete, vec![c('x'), k(Del)]),
+                    (Pin, vec![c('t')]),
+                    (Demote, vec![c('b')]),
+                    (Columns, vec![c('c'), f3]),
+                    (Mark, vec![c(' '), k(T)]),
+                ],
+                search_common(vec![
+                    (Up, vec![ctrl('p'), k(U)]),
+                    (Down, vec![ctrl('j'), ctrl('n'), k(D)]),
+                    (PagePrev, vec![k(PU)]),
+                    (PageNext, vec![ctrl('d'), k(PD)]),
+                    (First, vec![k(H)]),
+                    (Last, vec![k(E)]),
+                ]),
+            ),
+            "emacs" => (
+                vec![
+                    (Up, vec![ctrl('p'), k(U)]),
+                    (Down, vec![ctrl('n'), k(D)]),
+                    (PagePrev, vec![alt('v'), k(PU), k(L)]),
+                    (PageNext, vec![ctrl('v'), k(PD), k(R)]),
+                    (First, vec![alt('<'), k(H)]),
+                    (Last, vec![alt('>'), k(E)]),
+                    (Select, vec![k(Ent), ctrl('j')]),
+                    (Search, vec![ctrl('s'), c('/')]),
+                    (Help, vec![c('?'), k(KeyCode::F(1))]),
+                    (Quit, vec![ctrl('g'), ctrl('c'), c('q')]),
+                    (Preview, vec![alt('p'), f2]),
+                    (Delete, vec![ctrl('d'), k(Del)]),
+                    (Pin, vec![alt('t')]),
+                    (Demote, vec![alt('b')]),
+                    (Columns, vec![alt('c'), f3]),
+                    (Mark, vec![c(' '), k(T)]),
+                ],
+                vec![
+                    (Up, vec![ctrl('p'), k(U)]),
+                    (Down, vec![ctrl('n'), k(D)]),
+                    (PagePrev, vec![alt('v'), k(PU)]),
+                    (PageNext, vec![ctrl('v'), k(PD)]),
+                    (First, vec![alt('<'), k(H)]),
+                    (Last, vec![alt('>'), k(E)]),
+                    (CursorLeft, vec![k(L), ctrl('b')]),
+                    (CursorRight, vec![k(R), ctrl('f')]),
+                    (LineStart, vec![ctrl('a')]),
+                    (LineEnd, vec![ctrl('e')]),
+                    (KillToStart, vec![ctrl('u')]),
+                    (KillToEnd, vec![ctrl('k')]),
+                    (DeleteWord, vec![ctrl('w')]),
+                    (History, vec![ctrl('r')]),
+                    (Select, vec![k(Ent), ctrl('j'), k(T)]),
+                    (Back, vec![ctrl('g'), k(X)]),
+                    (Quit, vec![ctrl('c')]),
+                    (Preview, vec![alt('p'), f2]),
+                    (Delete, vec![k(Del)]),
+                    (Pin, vec![alt('t')]),
+                    (Demote, vec![alt('b')]),
+                    (Columns, vec![f3]),
+                    (Mark, vec![k(T)]),
+                ],
+            ),
+            _ => return None,
+        };
+        Some(Self {
+            preset: name.to_string(),
+            normal,
+            search,
+            multi: false,
+        })
+    }
+
+    /// 预设（配置文件 `[keys] preset`，`CDH_KEYMAP` 优先）+ 配置文件中的逐项覆盖
+    ///
+    /// 无法识别的预设 / 动作 / 按键只打印 warning 并跳过。
+    pub fn from_config(file: &ConfigFile) -> Self {
+        let name = std::env::var("CDH_KEYMAP")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .or_else(|| file.get_str("keys", "preset").map(str::to_string))
+            .unwrap_or_else(|| "default".to_string());
+        let mut km = Self::preset(&name.to_lowercase()).unwrap_or_else(|| {
+            eprintln!("cdh: 未知的按键预设 {name}（可选 default / vi / emacs）");
+            Self::default()
+        });
+
+        for (section, ctx) in [
+            ("keys", KeyContext::Normal),
+            ("keys.search", KeyContext::Search),
+        ] {
+            for (key, value) in file.section(section) {
+                if section == "keys" && key == "preset" {
+                    continue;
+                }
+                let Some(action) = Action::from_name(key) else {
+                    eprintln!("cdh: [{section}] 未知的动作 {key}");
+                    continue;
+                };
+                let chords: Vec<Chord> = value
+                    .as_strings()
+                    .iter()
+                    .filter_map(|s| {
+                        let chord = Chord::parse(s);
+                        if chord.is_none() {
+                            eprintln!("cdh: [{section}] {key}: 无法识别的按键 {s}");
+                        }
+                        chord
+                    })
+                    .collect();
+                km.bind(ctx, action, chords);
+            }
+        }
+        km
+    }
+
+    /// 替换某动作的全部按键；同一按键原先绑定的其他动作会被解除
+    pub fn bind(&mut self, ctx: KeyContext, action: Action, chords: Vec<Chord>) {
+        let table = self.table_mut(ctx);
+        for (_, cs) in table.iter_mut() {
+            cs.retain(|c| !chords.contains(c));
+        }
+        match table.iter_mut().find(|(a, _)| *a == action) {
+            Some((_, cs)) => *cs = chords,
+            None => table.push((action, chords)),
+        }
+    }
+
+    /// 打开 / 关闭多选模式（关闭时 mark 不参与查表，也不出现在帮助里）
+    pub fn set_multi(&mut self, on: bool) {
+        self.multi = on;
+    }
+
+    fn table(&self, ctx: KeyContext) -> &[(Action, Vec<Chord>)] {
+        match ctx {
+            KeyContext::Normal => &self.normal,
+            KeyContext::Search => &self.search,
+        }
+    }
+
+    fn table_mut(&mut self, ctx: KeyContext) -> &mut Vec<(Action, Vec<Chord>)> {
+        match ctx {
+            KeyContext::Normal => &mut self.normal,
+            KeyContext::Search => &mut self.search,
+        }
+    }
+
+    /// 查表：按键 → 动作
+    ///
+    /// 搜索模式下不带 Ctrl/Alt 的可打印字符永远进入查询，不会被当成动作。
+    pub fn lookup(&self, ctx: KeyContext, k: &KeyEvent) -> Option<Action> {
+        if ctx == KeyContext::Search {
+            let (code, ctrl, alt) = normalize(k);
+            if matches!(code, KeyCode::Char(c) if !c.is_control()) && !ctrl && !alt {
+                return None;
+            }
+        }
+        let hits = |(_, cs): &&(Action, Vec<Chord>)| cs.iter().any(|c| c.matches(k));
+        let table = self.table(ctx);
+        if self.multi {
+            if let Some((a, _)) = table.iter().filter(|(a, _)| *a == Action::Mark).find(hits) {
+                return Some(*a);
+            }
+        }
+        table
+            .iter()
+            .filter(|(a, _)| *a != Action::Mark)
+            .find(hits)
+            .map(|(a, _)| *a)
+    }
+
+    /// 某动作的第一个按键（用于底栏提示）；未绑定时返回 None
+    pub fn hint(&self, ctx: KeyContext, action: Action) -> Option<String> {
+        if action == Action::Mark && !self.multi {
+            return None;
+        }
+        self.table(ctx)
+            .iter()
+            .find(|(a, _)| *a == action)
+            .and_then(|(_, cs)| cs.first())
+            .map(Chord::label)
+    }
+
+    /// 帮助页中的按键说明（每个已绑定动作一行）
+    pub fn help_lines(&self, ctx: KeyContext, actions_enabled: bool) -> Vec<String> {
+        let table = self.table(ctx);
+        let rows: Vec<(String, &str)> = ACTIONS
+            .iter()
+            .filter(|a| {
+                actions_enabled || !matches!(a, Action::Delete | Action::Pin | Action::Demote)
+            })
+            .filter(|a| self.multi || **a != Action::Mark)
+            .filter_map(|a| {
+                let (_, cs) = table.iter().find(|(b, _)| b == a)?;
+                if cs.is_empty() {
+                    return None;
+                }
+                let keys: Vec<String> = cs.iter().map(Chord::label).collect();
+                Some((keys.join(" / "), a.describe()))
+            })
+            .collect();
+        let width = rows
+            .iter()
+            .map(|(k, _)| unicode_width::UnicodeWidthStr::width(k.as_str()))
+            .max()
+            .unwrap_or(0);
+        rows.into_iter()
+            .map(|(k, d)| {
+                let pad = width - unicode_width::UnicodeWidthStr::width(k.as_str());
+                format!("  {k}{}  {d}", " ".repeat(pad))
+            })
+            .collect()
+    }
+
+    pub fn preset_name(&self) -> &str {
+        &self.preset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, mods: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, mods)
+    }
+
+    #[test]
+    fn parse_and_label_chords() {
+        assert_eq!(Chord::parse("j"), Some(Chord::ch('j')));
+        assert_eq!(Chord::parse("Ctrl-N"), Some(Chord::ctrl('n')));
+        assert_eq!(Chord::parse("alt-<"), Some(Chord::alt('<')));
+        assert_eq!(Chord::parse("shift-g"), Some(Chord::ch('G')));
+        assert_eq!(Chord::parse("ctrl--"), Some(Chord::ctrl('-')));
+        assert_eq!(Chord::parse("f2"), Some(Chord::key(KeyCode::F(2))));
+        assert_eq!(Chord::parse("space"), Some(Chord::ch(' ')));
+        assert_eq!(Chord::parse("bogus"), None);
+        assert_eq!(Chord::parse("f0"), None);
+        assert_eq!(Chord::ctrl('t').label(), "Ctrl+T");
+        assert_eq!(Chord::key(KeyCode::Up).label(), "↑");
+    }
+
+    #[test]
+    fn search_line_editing_keys() {
+        let none = KeyModifiers::NONE;
+        let ctrl = KeyModifiers::CONTROL;
+        for name in ["default", "vi", "emacs"] {
+            let km = Keymap::preset(name).unwrap();
+            let at = |code, mods| km.lookup(KeyContext::Search, &key(code, mods));
+            assert_eq!(at(KeyCode::Left, none), Some(Action::CursorLeft), "{name}");
+            assert_eq!(
+                at(KeyCode::PageDown, none),
+                Some(Action::PageNext),
+                "{name}"
+            );
+            assert_eq!(
+                at(KeyCode::Char('a'), ctrl),
+                Some(Action::LineStart),
+                "{name}"
+            );
+            assert_eq!(
+                at(KeyCode::Char('u'), ctrl),
+                Some(Action::KillToStart),
+                "{name}"
+            );
+            assert_eq!(
+                at(KeyCode::Char('k'), ctrl),
+                Some(Action::KillToEnd),
+                "{name}"
+            );
+            assert_eq!(
+                at(KeyCode::Char('w'), ctrl),
+                Some(Action::DeleteWord),
+                "{name}"
+            );
+            assert_eq!(
+                at(KeyCode::Char('r'), ctrl),
+                Some(Action::History),
+                "{name}"
+            );
+            // 主界面没有输入行
+            let km_normal = km.lookup(KeyContext::Normal, &key(KeyCode::Char('w'), ctrl));
+            assert_ne!(km_normal, Some(Action::DeleteWord));
+        }
+    }
+
+    #[test]
+    fn presets_resolve_keys() {
+        let vi = Keymap::preset("vi").unwrap();
+        let g = key(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(vi.lookup(KeyContext::Normal, &g), Some(Action::Last));
+        let cd = key(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(vi.lookup(KeyContext::Normal, &cd), Some(Action::PageNext));
+
+        let def = Keymap::default();
+        let q = key(KeyCode::Char('q'), KeyModifiers::NONE);
+        assert_eq!(def.lookup(KeyContext::Normal, &q), Some(Action::Quit));
+        // 搜索模式下普通字符不查表
+        assert_eq!(def.lookup(KeyContext::Search, &q), None);
+        let cn = key(KeyCode::Char('n'), KeyModifiers::CONTROL);
+        assert_eq!(def.lookup(KeyContext::Search, &cn), Some(Action::Down));
+
+        let emacs = Keymap::preset("emacs").unwrap();
+        let av = key(KeyCode::Char('v'), KeyModifiers::ALT);
+        assert_eq!(
+            emacs.lookup(KeyContext::Normal, &av),
+            Some(Action::PagePrev)
+        );
+        assert!(Keymap::preset("nope").is_none());
+    }
+
+    #[test]
+    fn mark_only_active_in_multi_mode() {
+        let mut km = Keymap::default();
+        let tab = key(KeyCode::Tab, KeyModifiers::NONE);
+        let space = key(KeyCode::Char(' '), KeyModifiers::NONE);
+        assert_eq!(km.lookup(KeyContext::Search, &tab), Some(Action::Select));
+        assert_eq!(km.lookup(KeyContext::Normal, &space), None);
+        assert!(km.hint(KeyContext::Normal, Action::Mark).is_none());
+
+        km.set_multi(true);
+        assert_eq!(km.lookup(KeyContext::Search, &tab), Some(Action::Mark));
+        assert_eq!(km.lookup(KeyContext::Normal, &space), Some(Action::Mark));
+        // 搜索模式下空格仍然输入到查询里
+        assert_eq!(km.lookup(KeyContext::Search, &space), None);
+        let help = km.help_lines(KeyContext::Normal, true).join("\n");
+        assert!(help.contains("多选"), "{help}");
+    }
+
+    #[test]
+    fn config_overrides_replace_and_steal_bindings() {
+        let file = ConfigFile::parse(
+            "[keys]\npreset = \"default\"\nhelp = [\"?\", \"f1\"]\nquit = \"h\"\n\n[keys.search]\nup = \"ctrl-k\"\n",
+        )
+        .unwrap();
+        let km = Keymap::from_config(&file);
+        let h = key(KeyCode::Char('h'), KeyModifiers::NONE);
+        let qm = key(KeyCode::Char('?'), KeyModifiers::NONE);
+        let q = key(KeyCode::Char('q'), KeyModifiers::NONE);
+        assert_eq!(km.lookup(KeyContext::Normal, &h), Some(Action::Quit));
+        assert_eq!(km.lookup(KeyContext::Normal, &qm), Some(Action::Help));
+        // quit 整体被替换，q 不再退出
+        assert_eq!(km.lookup(KeyContext::Normal, &q), None);
+
+        let up = key(KeyCode::Up, KeyModifiers::NONE);
+        let ck = key(KeyCode::Char('k'), KeyModifiers::CONTROL);
+        assert_eq!(km.lookup(KeyContext::Search, &ck), Some(Action::Up));
+        assert_eq!(km.lookup(KeyContext::Search, &up), None);
+
+        assert_eq!(
+            km.hint(KeyContext::Normal, Action::Help).as_deref(),
+            Some("?")
+        );
+        let help = km.help_lines(KeyContext::Normal, true).join("\n");
+        assert!(help.contains("? / F1"), "{help}");
+    }
+}
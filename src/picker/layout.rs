@@ -0,0 +1,207 @@
+//! 列表布局选项：面板高度（随终端高度 / 占满全屏 / 内联）、每屏最多行数、翻页还是连续滚动
+//!
+//! ```toml
+//! [picker]
+//! height = "auto"        # auto：跟随终端高度，最多 max_rows 行；full：占满整个终端
+//!                        # 15 / "40%"：内联模式，不切备用屏幕，只占用提示符下方这么多行
+//! max_rows = 20
+//! scroll = "page"        # page：按页翻（0..9 数字直达）；continuous：光标到边缘时逐行滚动
+//! ```
+//!
+//! 环境变量 `CDH_HEIGHT` / `CDH_MAX_ROWS` / `CDH_SCROLL` 优先于配置文件，`--height` 又优先于环境变量。
+
+use crate::config::{ConfigFile, ConfigValue};
+
+/// 默认每屏最多行数（auto 模式下的上限）
+const DEFAULT_MAX_ROWS: usize = 20;
+/// 内联模式最少占用的行数（上下边框 + 至少 3 行列表）
+const MIN_INLINE_ROWS: u16 = 5;
+
+/// 面板高度策略
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Height {
+    /// 跟随终端高度，留两行给上方上下文，最多 `max_rows` 行
+    Auto,
+    /// 占满整个终端（忽略 `max_rows`）
+    Full,
+    /// 内联：在提示符下方保留固定行数（含边框）
+    Lines(u16),
+    /// 内联：保留终端高度的百分比
+    Percent(u16),
+}
+
+impl Height {
+    /// `auto` / `full` / `15` / `40%`
+    pub fn parse(s: &str) -> Option<Height> {
+        let s = s.trim();
+        match s {
+            "auto" => return Some(Height::Auto),
+            "full" => return Some(Height::Full),
+            _ => {}
+        }
+        if let Some(p) = s.strip_suffix('%') {
+            return match p.trim().parse::<u16>() {
+                Ok(p @ 1..=100) => Some(Height::Percent(p)),
+                _ => None,
+            };
+        }
+        match s.parse::<u16>() {
+            Ok(n) if n > 0 => Some(Height::Lines(n)),
+            _ => None,
+        }
+    }
+
+    /// 是否内联（不进入备用屏幕）
+    pub fn is_inline(self) -> bool {
+        matches!(self, Height::Lines(_) | Height::Percent(_))
+    }
+}
+
+/// 光标越过可见区域时的行为
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScrollMode {
+    /// 整页翻动（原有行为）
+    Page,
+    /// 逐行滚动，光标保持可见
+    Continuous,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutOpt {
+    pub height: Height,
+    pub max_rows: usize,
+    pub scroll: ScrollMode,
+}
+
+impl Default for LayoutOpt {
+    fn default() -> Self {
+        Self {
+            height: Height::Auto,
+            max_rows: DEFAULT_MAX_ROWS,
+            scroll: ScrollMode::Page,
+        }
+    }
+}
+
+impl LayoutOpt {
+    /// 配置文件 `[picker]` + 环境变量；无法识别的值打印 warning 后用默认值
+    pub fn from_config(file: &ConfigFile) -> Self {
+        let mut opt = Self::default();
+        let env = |k: &str| std::env::var(k).ok().filter(|s| !s.is_empty());
+
+        // 配置文件里既可以写 height = 15，也可以写 height = "40%"
+        let height = env("CDH_HEIGHT").or_else(|| {
+            file.get("picker", "height").and_then(|v| match v {
+                ConfigValue::Num(n) => Some(n.to_string()),
+                other => other.as_str().map(str::to_string),
+            })
+        });
+        if let Some(h) = height {
+            match Height::parse(&h) {
+                Some(h) => opt.height = h,
+                None => eprintln!("cdh: picker height 无效: {h}（auto / full / 行数 / 百分比）"),
+            }
+        }
+
+        let max_rows = env("CDH_MAX_ROWS")
+            .and_then(|s| s.parse::<usize>().ok())
+            .or_else(|| file.get_usize("picker", "max_rows"));
+        if let Some(n) = max_rows {
+            opt.max_rows = n.max(1);
+        }
+
+        let scroll =
+            env("CDH_SCROLL").or_else(|| file.get_str("picker", "scroll").map(str::to_string));
+        match scroll.as_deref() {
+            None | Some("page") => {}
+            Some("continuous") => opt.scroll = ScrollMode::Continuous,
+            Some(other) => eprintln!("cdh: picker scroll 无效: {other}（page / continuous）"),
+        }
+        opt
+    }
+
+    /// picker 占用的终端行数：内联模式按行数 / 百分比计算（不超过终端高度），其余为整个终端
+    pub(crate) fn region_h(&self, term_h: u16) -> u16 {
+        let h = match self.height {
+            Height::Lines(n) => n,
+            Height::Percent(p) => (u32::from(term_h) * u32::from(p) / 100) as u16,
+            Height::Auto | Height::Full => return term_h,
+        };
+        h.max(MIN_INLINE_ROWS).min(term_h)
+    }
+
+    /// 给定可用高度与额外占用的行数（如顶部输入框），计算列表可见行数（至少 1 行）
+    pub(crate) fn rows(&self, avail_h: u16, chrome: u16) -> usize {
+        // 上下边框 + 额外行
+        let room = avail_h.saturating_sub(2 + chrome) as usize;
+        let rows = match self.height {
+            // 留两行给 shell 提示符等上下文；终端很矮时至少保留 3 行
+            Height::Auto => room.saturating_sub(2).max(room.min(3)).min(self.max_rows),
+            // 内联区域本身就是用户指定的高度，全部用来显示列表
+            Height::Full | Height::Lines(_) | Height::Percent(_) => room,
+        };
+        rows.max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_follow_terminal_height() {
+        let auto = LayoutOpt::default();
+        assert_eq!(auto.rows(12, 0), 8);
+        assert_eq!(auto.rows(60, 0), DEFAULT_MAX_ROWS);
+        assert_eq!(auto.rows(60, 1), DEFAULT_MAX_ROWS);
+        assert_eq!(auto.rows(5, 0), 3);
+        assert_eq!(auto.rows(3, 0), 1);
+
+        let full = LayoutOpt {
+            height: Height::Full,
+            max_rows: 5,
+            ..LayoutOpt::default()
+        };
+        assert_eq!(full.rows(60, 1), 57);
+    }
+
+    #[test]
+    fn config_selects_height_and_scroll() {
+        let file = ConfigFile::parse(
+            "[picker]\nheight = \"full\"\nmax_rows = 7\nscroll = \"continuous\"\n",
+        )
+        .unwrap();
+        let opt = LayoutOpt::from_config(&file);
+        assert_eq!(opt.height, Height::Full);
+        assert_eq!(opt.max_rows, 7);
+        assert_eq!(opt.scroll, ScrollMode::Continuous);
+    }
+
+    #[test]
+    fn inline_height_reserves_region() {
+        assert_eq!(Height::parse("15"), Some(Height::Lines(15)));
+        assert_eq!(Height::parse("40%"), Some(Height::Percent(40)));
+        assert_eq!(Height::parse("0"), None);
+        assert_eq!(Height::parse("120%"), None);
+        assert!(!Height::Full.is_inline());
+
+        let lines = LayoutOpt {
+            height: Height::Lines(12),
+            ..LayoutOpt::default()
+        };
+        assert_eq!(lines.region_h(40), 12);
+        assert_eq!(lines.region_h(8), 8);
+        assert_eq!(lines.rows(12, 0), 10);
+
+        let pct = LayoutOpt {
+            height: Height::Percent(40),
+            ..LayoutOpt::default()
+        };
+        assert_eq!(pct.region_h(50), 20);
+        assert_eq!(pct.region_h(10), 5);
+        assert_eq!(LayoutOpt::default().region_h(50), 50);
+
+        let file = ConfigFile::parse("[picker]\nheight = 15\n").unwrap();
+        assert_eq!(LayoutOpt::from_config(&file).height, Height::Lines(15));
+    }
+}
@@ -0,0 +1,1778 @@
+//! 交互式列表选择器（默认贴底，也可内联显示在提示符下方；搜索 i/ESC；q 仅主界面退出；鼠标单击移动/双击选中）
+//! - 按键由 keymap 决定（default / vi / emacs 预设 + 配置文件覆盖），下面是 default 预设
+//! - 主界面：↑/↓/k/j 移动；←/→/p/n 翻页；0..9 数字直达；Enter 选；q 退；h 帮助；i 搜索；v 预览
+//! - 搜索模式：字符均加入查询（含 j/k/p/n/q/数字）；↑/↓ 移动；PageUp/PageDown 翻页；Ctrl+N/P 下/上；Enter/Tab 选；Esc 返回
+//! - 搜索输入行：←/→ 移动光标；Ctrl+A/E 行首/行尾；Ctrl+U/K/W 删到行首/行尾/删词；括号粘贴整段进入查询
+//! - 搜索优化：粘性焦点 + 单结果回车直接选中 + 结果为 0 时 Beep
+//! - 预览面板：v（任意模式 F2）开关；右侧/底部显示目录内容、git 状态、README（后台线程计算）
+//! - 附加列：c（搜索模式 F3）显示 / 隐藏融合分、访问次数、最近访问
+//! - 条目操作：d/Delete 删出历史（需 y 确认）；t 置顶；b 降级 —— 列表立即更新，副作用由 PickOutcome 交给调用方落盘
+//! - 无操作超时：打开后一直没有按键就退出（默认 15 秒，可配置 / 关闭），最后几秒底栏倒计时
+
+mod backend;
+pub mod columns;
+pub mod display;
+#[cfg(test)]
+mod headless;
+pub mod keymap;
+pub mod layout;
+mod preview;
+mod query;
+pub mod theme;
+pub mod watchdog;
+
+use crate::recommend::Recommendation;
+use backend::{DrawTarget, Event, EventSource, StderrTarget, TermEvents};
+pub use columns::Columns;
+use crossterm::{
+    cursor::{position, Hide, MoveTo, Show},
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
+    style::Print,
+    terminal::{
+        disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+    ExecutableCommand, QueueableCommand,
+};
+pub use display::PathDisplay;
+pub use keymap::Keymap;
+use keymap::{Action, KeyContext};
+pub use layout::LayoutOpt;
+use layout::ScrollMode;
+use preview::{preview_from_env, PreviewPos, Previewer};
+use query::Query;
+pub use query::{QueryHistory, QueryPick};
+use std::collections::HashSet;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use theme::Style;
+pub use theme::Theme;
+use unicode_width::UnicodeWidthStr;
+pub use watchdog::Watchdog;
+
+const CARET_BLINK_MS: u64 = 500;
+const DOUBLE_CLICK_MS: u64 = 300;
+/// 预览结果未到时，事件轮询改用更短的间隔，尽快画出结果
+const PREVIEW_POLL_MS: u64 = 30;
+/// 预览放右侧时的最小终端宽度（Auto 模式下不足则放底部）
+const PREVIEW_RIGHT_MIN_W: u16 = 100;
+/// 预览放底部时最多占用的行数
+const PREVIEW_BOTTOM_ROWS: u16 = 10;
+
+fn mouse_enabled() -> bool {
+    env::var("CDH_MOUSE")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(true)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Mode {
+    Normal,
+    Help,
+    Search,
+    /// 破坏性操作的 y/N 确认
+    Confirm,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum InputPos {
+    Bottom,
+    Top,
+    Title,
+    Overlay,
+}
+fn input_pos_from_env() -> InputPos {
+    match env::var("CDH_INPUT_POS").map(|s| s.to_lowercase()) {
+        Ok(s) if s == "top" => InputPos::Top,
+        Ok(s) if s == "title" => InputPos::Title,
+        Ok(s) if s == "overlay" => InputPos::Overlay,
+        _ => InputPos::Bottom,
+    }
+}
+
+// ---------------- UI 守卫（切到 stderr） ----------------
+struct UiGuard {
+    active: bool,
+    mouse: bool,
+    /// 内联模式占用的区域（起始行, 行数）；None 表示使用备用屏幕
+    inline: Option<(u16, u16)>,
+}
+impl UiGuard {
+    /// `inline_h` 为 Some 时不进入备用屏幕，而是在光标下方预留这么多行
+    fn new(mouse: bool, inline_h: Option<u16>) -> io::Result<Self> {
+        enable_raw_mode()?;
+        let mut err = io::stderr();
+        let inline = match inline_h {
+            Some(h) => Some(reserve_inline(&mut err, h)?),
+            None => {
+                err.execute(EnterAlternateScreen)?;
+                None
+            }
+        };
+        err.execute(Hide)?;
+        err.execute(EnableBracketedPaste)?;
+        if mouse {
+            err.execute(EnableMouseCapture)?;
+        }
+        err.flush()?;
+        Ok(Self {
+            active: true,
+            mouse,
+            inline,
+        })
+    }
+
+    /// 内联区域的起始行（备用屏幕为 0）
+    fn origin(&self) -> u16 {
+        self.inline.map(|(top, _)| top).unwrap_or(0)
+    }
+}
+
+/// 从当前光标所在行起预留 h 行：靠近底部时输出换行让终端上滚，返回区域起始行
+fn reserve_inline<W: Write>(err: &mut W, h: u16) -> io::Result<(u16, u16)> {
+    // 提示符后面还有内容时先另起一行，不覆盖它
+    if position()?.0 > 0 {
+        err.queue(Print("\r\n"))?;
+    }
+    err.queue(Print("\n".repeat(h.saturating_sub(1) as usize)))?;
+    err.flush()?;
+    let row = position()?.1;
+    Ok((row.saturating_sub(h.saturating_sub(1)), h))
+}
+impl Drop for UiGuard {
+    fn drop(&mut self) {
+        if !self.active {
+            return;
+        }
+        let mut err = io::stderr();
+        let _ = err.execute(Show);
+        let _ = err.execute(DisableBracketedPaste);
+        if self.mouse {
+            let _ = err.execute(DisableMouseCapture);
+        }
+        match self.inline {
+            // 擦掉自己画过的区域，光标回到区域起点，终端其余内容原样保留
+            Some((top, h)) => {
+                let _ = clear_region(&mut err, top, h);
+                let _ = err.execute(MoveTo(0, top));
+            }
+            None => {
+                let _ = err.execute(LeaveAlternateScreen);
+            }
+        }
+        let _ = err.flush();
+        let _ = disable_raw_mode();
+    }
+}
+
+// ---------------- 对外 API ----------------
+
+/// 用户在 picker 里对条目做的操作；picker 只更新自己的列表，落盘由调用方完成
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PickEffect {
+    /// 从历史中删除（已经过 y 确认）
+    Delete(String),
+    Pin(String),
+    Unpin(String),
+    Demote(String),
+    Undemote(String),
+}
+
+/// picker 选项
+#[derive(Clone, Debug, Default)]
+pub struct PickOpt {
+    /// 是否启用条目操作（删除/置顶/降级）
+    pub actions: bool,
+    /// 当前已置顶的路径（用于判断 t 是置顶还是取消置顶）
+    pub pinned: Vec<String>,
+    /// 当前已降级的路径
+    pub demoted: Vec<String>,
+    /// 按键映射（默认为 default 预设）
+    pub keymap: Keymap,
+    /// 配色与边框字形
+    pub theme: Theme,
+    /// 面板高度与滚动方式
+    pub layout: LayoutOpt,
+    /// 分数 / 访问次数 / 最近访问 附加列
+    pub columns: Columns,
+    /// 多选：Space / Tab 标记多个条目，Enter 一次返回
+    pub multi: bool,
+    /// stdin 被候选数据占用时（`cdh pick`），从控制终端 /dev/tty 读取按键
+    pub keys_from_tty: bool,
+    /// 路径显示方式（`~` 缩写 / 相对路径）
+    pub display: PathDisplay,
+    /// 搜索历史：输入行为空时 ↑/↓ 或 Ctrl-R 召回；输入时优先高亮以前用同样查询选中过的路径
+    pub queries: QueryHistory,
+    /// 无操作超时（默认 15 秒）
+    pub watchdog: Watchdog,
+}
+
+/// 一个候选条目：路径 + 可选的排序信息（用于附加列显示；只有路径时都为 None）
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PickItem {
+    pub path: String,
+    /// 融合分（0~1）
+    pub score: Option<f64>,
+    pub visits: Option<u64>,
+    /// 最近一次访问的时间戳（秒）
+    pub last_visit: Option<i64>,
+    /// 书签名（不含 `@`）：显示为路径前的 `@name` 标签，搜索时也能匹配
+    pub bookmark: Option<String>,
+}
+
+impl PickItem {
+    /// 搜索匹配的文本（小写）：路径，以及书签的 `@name`
+    fn search_keys(&self) -> impl Iterator<Item = String> + '_ {
+        let tag = self
+            .bookmark
+            .as_ref()
+            .map(|n| format!("@{n}").to_lowercase());
+        std::iter::once(self.path.to_lowercase()).chain(tag)
+    }
+}
+
+impl AsRef<str> for PickItem {
+    fn as_ref(&self) -> &str {
+        &self.path
+    }
+}
+
+impl From<&str> for PickItem {
+    fn from(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+impl From<Recommendation> for PickItem {
+    fn from(r: Recommendation) -> Self {
+        Self {
+            path: r.path,
+            score: Some(r.score),
+            visits: Some(r.visits),
+            last_visit: r.last_visit,
+            bookmark: None,
+        }
+    }
+}
+
+/// picker 结果：选中项 + 按发生顺序记录的条目操作（取消选择时操作同样有效）
+#[derive(Clone, Debug, Default)]
+pub struct PickOutcome {
+    /// 选中的路径：单选时至多一个；多选时按标记顺序；取消时为空
+    pub selected: Vec<String>,
+    pub effects: Vec<PickEffect>,
+    /// 选中时搜索输入行里的查询（不是在搜索模式下选中的为空），供调用方记入搜索历史
+    pub query: String,
+    /// 因无操作超时而退出（此时 selected 为空）
+    pub timed_out: bool,
+    /// 只选中一项时：它在当时列表（搜索时为过滤后的列表）中的位置，从 0 开始
+    pub rank: Option<usize>,
+    /// 只选中一项时：排在它前面、被跳过的路径（按列表顺序）
+    pub passed_over: Vec<String>,
+}
+
+pub fn pick<S: AsRef<str>>(items: &[S]) -> io::Result<Option<String>> {
+    pick_with(items, &PickOpt::default()).map(|o| o.selected.into_iter().next())
+}
+
+pub fn pick_with<S: AsRef<str>>(items: &[S], opt: &PickOpt) -> io::Result<PickOutcome> {
+    let items = items.iter().map(|s| PickItem::from(s.as_ref())).collect();
+    pick_items(items, opt)
+}
+
+/// 带排序信息的变体：附加列可以显示分数 / 访问次数 / 最近访问
+pub fn pick_items(items: Vec<PickItem>, opt: &PickOpt) -> io::Result<PickOutcome> {
+    // 非交互：保留旧逻辑 —— 直接返回第一条
+    let keyboard = io::stdin().is_terminal() || (opt.keys_from_tty && tty_available());
+    if !io::stderr().is_terminal() || !keyboard {
+        return Ok(PickOutcome {
+            selected: items.into_iter().take(1).map(|it| it.path).collect(),
+            ..PickOutcome::default()
+        });
+    }
+    run_ui(items, opt)
+}
+
+// ---------------- 主循环（渲染到 stderr） ----------------
+fn run_ui(items: Vec<PickItem>, opt: &PickOpt) -> io::Result<PickOutcome> {
+    let mut out = StderrTarget::new();
+    let (w, h) = out.size()?;
+    ensure(h >= 5, "终端高度至少需要 5 行")?;
+
+    let inline_h = opt
+        .layout
+        .height
+        .is_inline()
+        .then(|| opt.layout.region_h(h));
+    let mouse = mouse_enabled();
+    let mut guard = UiGuard::new(mouse, inline_h)?;
+
+    let mut ui = Ui::new(items, opt, w, h, guard.origin());
+    let res = event_loop(&mut ui, &mut TermEvents, &mut out, mouse);
+    // 内联区域可能随终端尺寸移动过：退出时擦掉的是最后所在的位置
+    if guard.inline.is_some() {
+        guard.inline = Some((ui.origin, ui.h));
+    }
+    res
+}
+
+/// 事件循环：从 events 读事件、画到 out，直到选中 / 取消 / 超时
+fn event_loop<E: EventSource, T: DrawTarget>(
+    ui: &mut Ui,
+    events: &mut E,
+    out: &mut T,
+    mouse: bool,
+) -> io::Result<PickOutcome> {
+    ui.sync_preview();
+    ui.redraw(out)?;
+    out.present()?;
+
+    let started = events.now();
+    let mut seen_key = false;
+
+    loop {
+        let mut dirty = false;
+        // 超时只针对“打开后一直没动过”，有过任何输入就不再计时
+        if !seen_key {
+            let idle = events.now().saturating_duration_since(started);
+            if ui.watchdog.expired(idle) {
+                return Ok(PickOutcome {
+                    timed_out: true,
+                    ..ui.finish(Vec::new())
+                });
+            }
+            let countdown = ui.watchdog.countdown(idle);
+            if countdown != ui.countdown {
+                ui.countdown = countdown;
+                dirty = true;
+            }
+        }
+        if ui.mode == Mode::Search
+            && ui.last_blink.elapsed() >= Duration::from_millis(CARET_BLINK_MS)
+        {
+            ui.caret_visible = !ui.caret_visible;
+            ui.last_blink = Instant::now();
+            dirty = true;
+        }
+        if ui.poll_preview() {
+            dirty = true;
+        }
+
+        let timeout = if ui.preview_pending() {
+            Duration::from_millis(PREVIEW_POLL_MS)
+        } else {
+            Duration::from_millis(250)
+        };
+        if events.poll(timeout)? {
+            match events.read()? {
+                Event::Resize(w1, h1) => {
+                    out.resized(w1, h1);
+                    ui.resize(w1, h1);
+                    dirty = true;
+                }
+                Event::Key(k) => {
+                    seen_key = true;
+                    ui.countdown = None;
+                    if let Flow::Exit(sel) = ui.on_key(k, out)? {
+                        return Ok(ui.finish(sel));
+                    }
+                    dirty = true;
+                }
+                Event::Paste(text) => {
+                    seen_key = true;
+                    ui.countdown = None;
+                    ui.on_paste(&text);
+                    dirty = true;
+                }
+                Event::Mouse(me) if mouse && ui.mode != Mode::Help => {
+                    seen_key = true;
+                    ui.countdown = None;
+                    if let Flow::Exit(sel) = ui.on_mouse(me) {
+                        return Ok(ui.finish(sel));
+                    }
+                    dirty = true;
+                }
+                _ => {}
+            }
+        }
+
+        if dirty {
+            ui.sync_preview();
+            ui.redraw(out)?;
+            out.present()?;
+        }
+    }
+}
+
+/// 按键/鼠标处理结果：继续循环，或带着选择结果退出（空表示取消）
+enum Flow {
+    Continue,
+    Exit(Vec<String>),
+}
+
+/// 屏幕上的矩形区域（列/行均从 0 开始）
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Rect {
+    x: u16,
+    y: u16,
+    w: u16,
+    h: u16,
+}
+
+/// 一次 picker 会话的全部状态：布局 + 模式 + 视图/光标 + 搜索输入 + 预览 + 条目操作
+struct Ui {
+    /// 条目列表（删除/置顶/降级会直接修改它）
+    items: Vec<PickItem>,
+
+    // 布局（origin/h 为 picker 占用的区域：备用屏幕下是整个终端，内联模式下是预留的几行）
+    w: u16,
+    h: u16,
+    origin: u16,
+    panel_h: u16,
+    top_margin: u16,
+    /// 列表面板宽度（右侧预览打开时小于 w）
+    list_w: u16,
+    preview_rect: Option<Rect>,
+
+    input_pos: InputPos,
+    mode: Mode,
+    view: View,
+    st: State,
+
+    // 搜索输入
+    query: Query,
+    /// 搜索历史及正在召回的位置（None 表示没有在翻历史）
+    history: QueryHistory,
+    hist_pos: Option<usize>,
+    caret_visible: bool,
+    last_blink: Instant,
+
+    // 双击
+    last_click_at: Option<Instant>,
+    last_click_abs: Option<usize>,
+
+    // 预览（首次打开时才启动后台线程）
+    preview_on: bool,
+    preview_pos: PreviewPos,
+    previewer: Option<Previewer>,
+
+    // 条目操作
+    actions: bool,
+    pinned: HashSet<String>,
+    demoted: HashSet<String>,
+    effects: Vec<PickEffect>,
+    /// 等待确认删除的条目（绝对索引）及确认前的模式
+    confirm: Option<(usize, Mode)>,
+    /// 底栏临时提示（下一次按键后清除）
+    status: Option<String>,
+    /// 无操作超时及底栏倒计时（剩余秒数）
+    watchdog: Watchdog,
+    countdown: Option<u64>,
+
+    keymap: Keymap,
+    theme: Theme,
+    layout: LayoutOpt,
+    columns: Columns,
+    display: PathDisplay,
+    /// 多选模式及已标记的路径（按标记顺序）
+    multi: bool,
+    marked: Vec<String>,
+    /// 计算“最近访问距今”用的当前时刻
+    now: i64,
+}
+
+impl Ui {
+    fn new(items: Vec<PickItem>, opt: &PickOpt, w: u16, term_h: u16, origin: u16) -> Self {
+        let view = View::new(items.len());
+        let st = State::new(1, opt.layout.scroll);
+        let (preview_on, preview_pos) = preview_from_env();
+        let mut ui = Self {
+            items,
+            w,
+            h: opt.layout.region_h(term_h),
+            origin,
+            panel_h: 5,
+            top_margin: 0,
+            list_w: w,
+            preview_rect: None,
+            input_pos: input_pos_from_env(),
+            mode: Mode::Normal,
+            view,
+            st,
+            query: Query::default(),
+            history: opt.queries.clone(),
+            hist_pos: None,
+            caret_visible: true,
+            last_blink: Instant::now(),
+            last_click_at: None,
+            last_click_abs: None,
+            preview_on,
+            preview_pos,
+            previewer: None,
+            actions: opt.actions,
+            pinned: opt.pinned.iter().cloned().collect(),
+            demoted: opt.demoted.iter().cloned().collect(),
+            effects: Vec::new(),
+            confirm: None,
+            status: None,
+            watchdog: opt.watchdog,
+            countdown: None,
+            keymap: {
+                let mut km = opt.keymap.clone();
+                km.set_multi(opt.multi);
+                km
+            },
+            theme: opt.theme.clone(),
+            layout: opt.layout.clone(),
+            columns: opt.columns.clone(),
+            display: opt.display.clone(),
+            multi: opt.multi,
+            marked: Vec::new(),
+            now: now_secs(),
+        };
+        ui.layout();
+        ui
+    }
+
+    fn finish(&mut self, selected: Vec<String>) -> PickOutcome {
+        let query = if self.mode == Mode::Search && !selected.is_empty() {
+            self.query.as_str().trim().to_string()
+        } else {
+            String::new()
+        };
+        let (rank, passed_over) = match selected.as_slice() {
+            [one] => self.rank_of(one),
+            _ => (None, Vec::new()),
+        };
+        PickOutcome {
+            selected,
+            effects: std::mem::take(&mut self.effects),
+            query,
+            timed_out: false,
+            rank,
+            passed_over,
+        }
+    }
+
+    /// 路径在当前视图中的位置，以及排在它前面的路径
+    fn rank_of(&self, path: &str) -> (Option<usize>, Vec<String>) {
+        let shown: Vec<&str> = (0..self.view.view_len())
+            .filter_map(|i| self.view.abs_at(i))
+            .map(|abs| self.items[abs].path.as_str())
+            .collect();
+        match shown.iter().position(|p| *p == path) {
+            Some(rank) => (
+                Some(rank),
+                shown[..rank].iter().map(|p| p.to_string()).collect(),
+            ),
+            None => (None, Vec::new()),
+        }
+    }
+
+    /// 根据终端尺寸与预览开关计算面板位置与每屏行数（光标所在条目保持不变）
+    fn layout(&mut self) {
+        let (w, h) = (self.w, self.h);
+        let right = self.preview_on
+            && match self.preview_pos {
+                PreviewPos::Right => w >= 40,
+                PreviewPos::Bottom => false,
+                PreviewPos::Auto => w >= PREVIEW_RIGHT_MIN_W,
+            };
+        // 底部预览先预留自己的高度，列表面板用剩下的部分
+        let avail_h = if self.preview_on && !right {
+            h - PREVIEW_BOTTOM_ROWS.min(h / 2)
+        } else {
+            h
+        };
+        let chrome = self.header_lines();
+        let rows = self.layout.rows(avail_h, chrome);
+        self.st.set_rows(rows, &self.view);
+        self.panel_h = (rows as u16 + 2 + chrome).min(h);
+        self.top_margin = self.origin + compute_top_margin_bottom(h, self.panel_h);
+        self.list_w = w;
+        self.preview_rect = None;
+        if !self.preview_on {
+            return;
+        }
+
+        if right {
+            let pw = w / 2;
+            self.list_w = w - pw;
+            self.preview_rect = Some(Rect {
+                x: self.list_w,
+                y: self.top_margin,
+                w: pw,
+                h: self.panel_h,
+            });
+        } else {
+            let ph = h.saturating_sub(self.panel_h).min(PREVIEW_BOTTOM_ROWS);
+            if ph >= 3 {
+                self.top_margin = self.origin + h - self.panel_h - ph;
+                self.preview_rect = Some(Rect {
+                    x: 0,
+                    y: self.top_margin + self.panel_h,
+                    w,
+                    h: ph,
+                });
+            }
+        }
+    }
+
+    fn resize(&mut self, w: u16, term_h: u16) {
+        self.w = w;
+        self.h = self.layout.region_h(term_h);
+        // 内联区域不能超出终端底部
+        self.origin = self.origin.min(term_h - self.h);
+        self.layout();
+    }
+
+    /// 顶栏与列表之间额外占用的行数（输入框在顶部时常驻一行，切换搜索时面板高度不跳动）
+    fn header_lines(&self) -> u16 {
+        u16::from(self.input_pos == InputPos::Top)
+    }
+
+    /// picker 占用的整个区域
+    fn area(&self) -> Rect {
+        Rect {
+            x: 0,
+            y: self.origin,
+            w: self.w,
+            h: self.h,
+        }
+    }
+
+    fn highlighted_abs(&self) -> Option<usize> {
+        self.view.abs_at(self.st.sel)
+    }
+
+    fn toggle_preview(&mut self) {
+        self.preview_on = !self.preview_on;
+        self.layout();
+    }
+
+    /// 预览打开时，为当前高亮条目发起（后台）预览请求
+    fn sync_preview(&mut self) {
+        if !self.preview_on {
+            return;
+        }
+        let Some(abs) = self.highlighted_abs() else {
+            return;
+        };
+        let previewer = self.previewer.get_or_insert_with(Previewer::new);
+        previewer.request(&self.items[abs].path);
+    }
+
+    fn poll_preview(&mut self) -> bool {
+        self.previewer.as_mut().map(|p| p.poll()).unwrap_or(false)
+    }
+
+    fn preview_pending(&self) -> bool {
+        self.preview_on && self.previewer.as_ref().is_some_and(|p| p.is_pending())
+    }
+
+    fn redraw<W: Write>(&self, err: &mut W) -> io::Result<()> {
+        if self.mode == Mode::Help {
+            return redraw_help(err, &self.theme, self.area(), &self.keymap, self.actions);
+        }
+        redraw_main(err, self)?;
+        if let Some(rect) = self.preview_rect {
+            let abs = self.highlighted_abs();
+            let lines = abs.and_then(|a| {
+                self.previewer
+                    .as_ref()
+                    .and_then(|p| p.get(&self.items[a].path))
+            });
+            draw_preview(err, &self.theme, rect, lines, abs.is_some())?;
+        }
+        Ok(())
+    }
+
+    fn enter_search(&mut self) {
+        self.mode = Mode::Search;
+        self.query.clear();
+        self.hist_pos = None;
+        self.view.apply_filter(&self.items, self.query.as_str());
+        self.st.set_sel(0, &self.view);
+        self.caret_visible = true;
+        self.last_blink = Instant::now();
+    }
+
+    fn leave_search(&mut self) {
+        self.mode = Mode::Normal;
+        self.query.clear();
+        self.hist_pos = None;
+        self.view.clear_filter(self.items.len());
+        self.st.set_sel(0, &self.view);
+        self.caret_visible = true;
+    }
+
+    /// 用户编辑查询：结束历史召回，重新过滤
+    fn edit_query(&mut self, f: impl FnOnce(&mut Query)) {
+        self.hist_pos = None;
+        self.refilter_with(f);
+    }
+
+    /// 修改查询后重新过滤（粘性焦点；以前用同样查询选中过的路径优先）
+    fn refilter_with(&mut self, f: impl FnOnce(&mut Query)) {
+        let anchor = self.highlighted_abs();
+        f(&mut self.query);
+        reposition_after_filter(
+            &self.items,
+            &mut self.view,
+            &mut self.st,
+            self.query.as_str(),
+            anchor,
+        );
+        self.focus_remembered();
+        self.caret_visible = true;
+        self.last_blink = Instant::now();
+    }
+
+    /// 高亮搜索历史里这个查询最常选中、且在当前结果中的路径
+    fn focus_remembered(&mut self) {
+        for path in self.history.remembered(self.query.as_str()) {
+            let pos = self
+                .items
+                .iter()
+                .position(|it| it.path == path)
+                .and_then(|abs| self.view.pos_of_abs(abs));
+            if let Some(pos) = pos {
+                self.st.set_sel(pos, &self.view);
+                return;
+            }
+        }
+    }
+
+    /// 输入行为空（或正在翻历史）时 ↑/↓ 用来召回历史
+    fn recalling(&self) -> bool {
+        self.mode == Mode::Search
+            && !self.history.recent.is_empty()
+            && (self.query.as_str().is_empty() || self.hist_pos.is_some())
+    }
+
+    /// 召回搜索历史：older 为 true 时往更早翻（wrap 时翻到头再从最新开始），
+    /// 往回翻过最新一条时清空输入行
+    fn recall(&mut self, older: bool, wrap: bool) {
+        let n = self.history.recent.len();
+        if n == 0 {
+            return;
+        }
+        let next = match (self.hist_pos, older) {
+            (None, true) => Some(0),
+            (Some(i), true) if i + 1 < n => Some(i + 1),
+            (Some(_), true) if wrap => Some(0),
+            (Some(i), true) => Some(i),
+            (Some(0), false) | (None, false) => None,
+            (Some(i), false) => Some(i - 1),
+        };
+        self.hist_pos = next;
+        let text = next
+            .map(|i| self.history.recent[i].clone())
+            .unwrap_or_default();
+        self.refilter_with(|q| q.set(&text));
+    }
+
+    /// 只移动光标，查询不变（不需要重新过滤）
+    fn move_caret(&mut self, f: impl FnOnce(&mut Query)) {
+        f(&mut self.query);
+        self.caret_visible = true;
+        self.last_blink = Instant::now();
+    }
+
+    /// 括号粘贴：整段文字插入查询，不当作按键解释；主界面下先进入搜索
+    fn on_paste(&mut self, text: &str) {
+        match self.mode {
+            Mode::Normal => self.enter_search(),
+            Mode::Search => {}
+            Mode::Help | Mode::Confirm => return,
+        }
+        self.status = None;
+        self.edit_query(|q| q.insert(text));
+    }
+
+    /// 选中并退出：多选模式下已有标记时返回全部标记（按标记顺序），否则返回 abs 这一项
+    fn select(&self, abs: usize) -> Flow {
+        if !self.marked.is_empty() {
+            return Flow::Exit(self.marked.clone());
+        }
+        Flow::Exit(
+            self.items
+                .get(abs)
+                .map(|it| it.path.clone())
+                .into_iter()
+                .collect(),
+        )
+    }
+
+    /// 多选：标记 / 取消标记高亮条目，然后下移一行（方便连续标记）
+    fn toggle_mark(&mut self) {
+        let Some(abs) = self.highlighted_abs() else {
+            return;
+        };
+        let path = &self.items[abs].path;
+        match self.marked.iter().position(|p| p == path) {
+            Some(i) => {
+                self.marked.remove(i);
+            }
+            None => self.marked.push(path.clone()),
+        }
+        self.st.move_down(&self.view);
+    }
+
+    /// 从列表中移除某条目后重新过滤，光标停在原位置（越界则夹到末尾）
+    fn refilter_after_remove(&mut self) {
+        self.view.apply_filter(&self.items, self.query.as_str());
+        self.st.clamp(&self.view);
+    }
+
+    /// 把条目移动到 `to`（绝对索引），并让光标跟随它
+    fn move_item(&mut self, abs: usize, to: usize) {
+        let item = self.items.remove(abs);
+        let to = to.min(self.items.len());
+        self.items.insert(to, item);
+        self.view.apply_filter(&self.items, self.query.as_str());
+        if let Some(pos) = self.view.pos_of_abs(to) {
+            self.st.set_sel(pos, &self.view);
+        }
+    }
+
+    fn ask_delete(&mut self) {
+        if let Some(abs) = self.highlighted_abs() {
+            self.confirm = Some((abs, self.mode));
+            self.mode = Mode::Confirm;
+        }
+    }
+
+    fn delete_item(&mut self, abs: usize) {
+        let path = self.items.remove(abs).path;
+        self.marked.retain(|p| *p != path);
+        self.pinned.remove(&path);
+        self.demoted.remove(&path);
+        self.status = Some(format!("已从历史删除 {}", self.display.shorten(&path)));
+        self.effects.push(PickEffect::Delete(path));
+        self.refilter_after_remove();
+    }
+
+    /// 置顶 / 取消置顶（置顶时移到列表最前）
+    fn toggle_pin(&mut self) {
+        let Some(abs) = self.highlighted_abs() else {
+            return;
+        };
+        let path = self.items[abs].path.clone();
+        if self.pinned.remove(&path) {
+            self.status = Some(format!("已取消置顶 {}", self.display.shorten(&path)));
+            self.effects.push(PickEffect::Unpin(path));
+        } else {
+            self.demoted.remove(&path);
+            self.pinned.insert(path.clone());
+            self.status = Some(format!("已置顶 {}", self.display.shorten(&path)));
+            self.effects.push(PickEffect::Pin(path));
+            self.move_item(abs, 0);
+        }
+    }
+
+    /// 降级 / 取消降级（降级时移到列表最后）
+    fn toggle_demote(&mut self) {
+        let Some(abs) = self.highlighted_abs() else {
+            return;
+        };
+        let path = self.items[abs].path.clone();
+        if self.demoted.remove(&path) {
+            self.status = Some(format!("已取消降级 {}", self.display.shorten(&path)));
+            self.effects.push(PickEffect::Undemote(path));
+        } else {
+            self.pinned.remove(&path);
+            self.demoted.insert(path.clone());
+            self.status = Some(format!("已降级 {}", self.display.shorten(&path)));
+            self.effects.push(PickEffect::Demote(path));
+            let last = self.items.len();
+            self.move_item(abs, last);
+        }
+    }
+
+    fn on_key<W: Write>(&mut self, k: KeyEvent, err: &mut W) -> io::Result<Flow> {
+        self.status = None;
+        match self.mode {
+            Mode::Confirm => {
+                if let Some((abs, back)) = self.confirm.take() {
+                    self.mode = back;
+                    if matches!(k.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                        self.delete_item(abs);
+                    }
+                }
+            }
+            Mode::Help => {
+                let close = matches!(
+                    self.keymap.lookup(KeyContext::Normal, &k),
+                    Some(Action::Quit | Action::Help | Action::Back)
+                );
+                if close || k.code == KeyCode::Esc {
+                    self.mode = Mode::Normal;
+                }
+            }
+            Mode::Search => {
+                if let Some(action) = self.keymap.lookup(KeyContext::Search, &k) {
+                    return self.run_action(action, err);
+                }
+                let plain = !k
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+                match k.code {
+                    KeyCode::Backspace => self.edit_query(Query::backspace),
+                    // 字符都加入查询（包含 j/k/p/n/q/数字）
+                    KeyCode::Char(c) if plain && !c.is_control() => {
+                        self.edit_query(|q| q.insert(c.encode_utf8(&mut [0; 4])))
+                    }
+                    _ => {}
+                }
+            }
+            Mode::Normal => {
+                if let Some(action) = self.keymap.lookup(KeyContext::Normal, &k) {
+                    return self.run_action(action, err);
+                }
+                // 未绑定的数字键：直达当前屏的前 10 行
+                if let KeyCode::Char(c @ '0'..='9') = k.code {
+                    self.st.jump_row((c as u8 - b'0') as usize, &self.view);
+                }
+            }
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// 执行 keymap 解析出的动作（主界面与搜索模式共用）
+    fn run_action<W: Write>(&mut self, action: Action, err: &mut W) -> io::Result<Flow> {
+        match action {
+            Action::Up if self.recalling() => self.recall(true, false),
+            Action::Down if self.recalling() && self.hist_pos.is_some() => {
+                self.recall(false, false)
+            }
+            Action::Up => self.st.move_up(&self.view),
+            Action::Down => self.st.move_down(&self.view),
+            Action::PagePrev => self.st.page_left(&self.view),
+            Action::PageNext => self.st.page_right(&self.view),
+            Action::First => self.st.set_sel(0, &self.view),
+            Action::Last => self
+                .st
+                .set_sel(self.view.view_len().saturating_sub(1), &self.view),
+            Action::Select if self.mode == Mode::Search => {
+                let n = self.view.view_len();
+                if n == 0 {
+                    beep(err)?;
+                    return Ok(Flow::Continue);
+                }
+                let abs = if n == 1 {
+                    self.view.abs_at(0).unwrap()
+                } else {
+                    match self.highlighted_abs() {
+                        Some(a) => a,
+                        None => self
+                            .view
+                            .best_focus(&self.items, self.query.as_str())
+                            .and_then(|i| self.view.abs_at(i))
+                            .unwrap_or(0),
+                    }
+                };
+                return Ok(self.select(abs));
+            }
+            Action::Select => {
+                if let Some(abs) = self.highlighted_abs() {
+                    return Ok(self.select(abs));
+                }
+            }
+            Action::Search if self.mode == Mode::Normal => self.enter_search(),
+            Action::Search => {}
+            Action::Back if self.mode == Mode::Search => self.leave_search(),
+            Action::Back => {}
+            Action::Help => self.mode = Mode::Help,
+            Action::Quit => return Ok(Flow::Exit(Vec::new())),
+            Action::Preview => self.toggle_preview(),
+            Action::Columns => self.columns.visible = !self.columns.visible,
+            Action::Mark if self.multi => self.toggle_mark(),
+            Action::Mark => {}
+            // 条目操作（未启用时忽略）
+            Action::Delete if self.actions => self.ask_delete(),
+            Action::Pin if self.actions => self.toggle_pin(),
+            Action::Demote if self.actions => self.toggle_demote(),
+            Action::Delete | Action::Pin | Action::Demote => {}
+            // 搜索输入行编辑（主界面没有输入行，忽略）
+            _ if self.mode != Mode::Search => {}
+            Action::CursorLeft => self.move_caret(Query::left),
+            Action::CursorRight => self.move_caret(Query::right),
+            Action::LineStart => self.move_caret(Query::home),
+            Action::LineEnd => self.move_caret(Query::end),
+            Action::KillToStart => self.edit_query(Query::kill_to_start),
+            Action::KillToEnd => self.edit_query(Query::kill_to_end),
+            Action::DeleteWord => self.edit_query(Query::delete_word),
+            Action::History => self.recall(true, true),
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// 底栏按键提示，如 “Enter 选 · q 退出”；未绑定的动作不显示
+    fn key_tips(&self, ctx: KeyContext, items: &[(Action, &str)]) -> String {
+        let parts: Vec<String> = items
+            .iter()
+            .filter_map(|(a, text)| self.keymap.hint(ctx, *a).map(|k| format!("{k} {text}")))
+            .collect();
+        format!(" {} ", parts.join(" · "))
+    }
+
+    fn on_mouse(&mut self, me: MouseEvent) -> Flow {
+        let content_top = self.top_margin + 1 + self.header_lines();
+        let Some(action) = handle_mouse(me, content_top, self.list_w, &self.st, &self.view) else {
+            return Flow::Continue;
+        };
+
+        match action {
+            MouseAction::MoveToRow(row) => {
+                self.st.jump_row(row, &self.view);
+
+                if let Some(abs) = self.highlighted_abs() {
+                    let now = Instant::now();
+                    let is_double = self.last_click_abs == Some(abs)
+                        && self
+                            .last_click_at
+                            .map(|t| {
+                                now.duration_since(t) <= Duration::from_millis(DOUBLE_CLICK_MS)
+                            })
+                            .unwrap_or(false);
+                    if is_double {
+                        return self.select(abs);
+                    }
+                    self.last_click_abs = Some(abs);
+                    self.last_click_at = Some(now);
+                }
+            }
+            MouseAction::ScrollUp => self.st.move_up(&self.view),
+            MouseAction::ScrollDown => self.st.move_down(&self.view),
+        }
+        Flow::Continue
+    }
+}
+
+// ---------------- 视图 & 状态 ----------------
+#[derive(Clone, Debug)]
+struct View {
+    total_len: usize,
+    filtered: Option<Vec<usize>>,
+}
+impl View {
+    fn new(total_len: usize) -> Self {
+        Self {
+            total_len,
+            filtered: None,
+        }
+    }
+    fn clear_filter(&mut self, total_len: usize) {
+        self.total_len = total_len;
+        self.filtered = None;
+    }
+    fn view_len(&self) -> usize {
+        self.filtered
+            .as_ref()
+            .map(|v| v.len())
+            .unwrap_or(self.total_len)
+    }
+    /// 视图下标 → 条目绝对下标
+    fn abs_at(&self, idx: usize) -> Option<usize> {
+        if idx >= self.view_len() {
+            return None;
+        }
+        if let Some(map) = &self.filtered {
+            Some(map[idx])
+        } else {
+            Some(idx)
+        }
+    }
+    fn apply_filter(&mut self, items: &[PickItem], q: &str) {
+        self.total_len = items.len();
+        let q = q.trim();
+        if q.is_empty() {
+            self.filtered = None;
+            return;
+        }
+        let q_lower = q.to_lowercase();
+        let mut out = Vec::with_capacity(items.len());
+        for (i, it) in items.iter().enumerate() {
+            if it.search_keys().any(|k| k.contains(&q_lower)) {
+                out.push(i);
+            }
+        }
+        self.filtered = Some(out);
+    }
+
+    /// 条目绝对下标 → 视图下标（被过滤掉时返回 None）
+    fn pos_of_abs(&self, abs: usize) -> Option<usize> {
+        if let Some(map) = &self.filtered {
+            map.iter().position(|&a| a == abs)
+        } else if abs < self.total_len {
+            Some(abs)
+        } else {
+            None
+        }
+    }
+
+    fn best_focus(&self, items: &[PickItem], q: &str) -> Option<usize> {
+        if self.view_len() == 0 {
+            return None;
+        }
+        let ql = q.to_lowercase();
+
+        let iter: Box<dyn Iterator<Item = (usize, usize)>> = if let Some(map) = &self.filtered {
+            Box::new(map.iter().enumerate().map(|(i, &abs)| (i, abs)))
+        } else {
+            Box::new((0..self.total_len).enumerate())
+        };
+
+        let mut exact: Option<usize> = None;
+        let mut prefix: Option<usize> = None;
+
+        for (i, abs) in iter {
+            for sl in items[abs].search_keys() {
+                if exact.is_none() && sl == ql {
+                    exact = Some(i);
+                }
+                if prefix.is_none() && sl.starts_with(&ql) {
+                    prefix = Some(i);
+                }
+            }
+            if exact.is_some() && prefix.is_some() {
+                break;
+            }
+        }
+
+        Some(exact.or(prefix).unwrap_or(0))
+    }
+}
+
+/// 光标与视口：都用视图下标表示，和每屏行数无关，
+/// 因此终端尺寸变化时光标始终停在同一个条目上
+#[derive(Clone, Debug)]
+struct State {
+    /// 光标所在的视图下标
+    sel: usize,
+    /// 视口第一行对应的视图下标
+    top: usize,
+    /// 每屏可见行数
+    rows: usize,
+    scroll: ScrollMode,
+}
+impl State {
+    fn new(rows: usize, scroll: ScrollMode) -> Self {
+        Self {
+            sel: 0,
+            top: 0,
+            rows: rows.max(1),
+            scroll,
+        }
+    }
+    /// 当前页码（1-based）；连续滚动时按视口顶部估算
+    fn page(&self) -> usize {
+        self.top / self.rows + 1
+    }
+    fn page_count(&self, view: &View) -> usize {
+        ((view.view_len() + self.rows - 1) / self.rows).max(1)
+    }
+    /// 视口里实际有条目的行数
+    fn visible_len(&self, view: &View) -> usize {
+        view.view_len().saturating_sub(self.top).min(self.rows)
+    }
+    /// 光标在视口中的行号
+    fn row(&self) -> usize {
+        self.sel - self.top
+    }
+    fn set_rows(&mut self, rows: usize, view: &View) {
+        self.rows = rows.max(1);
+        self.clamp(view);
+    }
+    /// 把光标夹回视图范围，并让视口跟随光标
+    fn clamp(&mut self, view: &View) {
+        let n = view.view_len();
+        self.sel = self.sel.min(n.saturating_sub(1));
+        match self.scroll {
+            ScrollMode::Page => self.top = self.sel / self.rows * self.rows,
+            ScrollMode::Continuous => {
+                if self.sel < self.top {
+                    self.top = self.sel;
+                } else if self.sel >= self.top + self.rows {
+                    self.top = self.sel + 1 - self.rows;
+                }
+                // 末尾不留空行
+                self.top = self.top.min(n.saturating_sub(self.rows));
+            }
+        }
+    }
+    fn set_sel(&mut self, sel: usize, view: &View) {
+        self.sel = sel;
+        self.clamp(view);
+    }
+    fn move_up(&mut self, view: &View) {
+        self.set_sel(self.sel.saturating_sub(1), view);
+    }
+    fn move_down(&mut self, view: &View) {
+        self.set_sel(self.sel + 1, view);
+    }
+    fn page_left(&mut self, view: &View) {
+        match self.scroll {
+            // 翻页时保持光标所在行
+            ScrollMode::Page if self.top >= self.rows => {
+                let row = self.row();
+                self.top -= self.rows;
+                self.sel = self.top + row;
+            }
+            ScrollMode::Page => {}
+            ScrollMode::Continuous => {
+                self.top = self.top.saturating_sub(self.rows);
+                self.sel = self.sel.saturating_sub(self.rows);
+            }
+        }
+        self.clamp(view);
+    }
+    fn page_right(&mut self, view: &View) {
+        let n = view.view_len();
+        match self.scroll {
+            ScrollMode::Page if self.top + self.rows < n => {
+                let row = self.row();
+                self.top += self.rows;
+                self.sel = (self.top + row).min(n - 1);
+            }
+            ScrollMode::Page => {}
+            ScrollMode::Continuous => {
+                self.top += self.rows;
+                self.sel += self.rows;
+            }
+        }
+        self.clamp(view);
+    }
+    /// 跳到视口中的第 row 行（数字键 / 鼠标单击）
+    fn jump_row(&mut self, row: usize, view: &View) {
+        if row < self.visible_len(view) {
+            self.sel = self.top + row;
+        }
+    }
+}
+
+// 过滤后决定光标所在（粘性焦点 + 最佳焦点）
+fn reposition_after_filter(
+    items: &[PickItem],
+    view: &mut View,
+    st: &mut State,
+    q: &str,
+    anchor_abs: Option<usize>,
+) {
+    view.apply_filter(items, q);
+    let sel = anchor_abs
+        .and_then(|abs| view.pos_of_abs(abs))
+        .or_else(|| view.best_focus(items, q))
+        .unwrap_or(0);
+    st.set_sel(sel, view);
+}
+
+// ---------------- 鼠标 ----------------
+#[derive(Copy, Clone, Debug)]
+enum MouseAction {
+    /// 单击视口中的第 N 行
+    MoveToRow(usize),
+    ScrollUp,
+    ScrollDown,
+}
+fn handle_mouse(
+    me: MouseEvent,
+    content_top: u16,
+    list_w: u16,
+    st: &State,
+    view: &View,
+) -> Option<MouseAction> {
+    match me.kind {
+        MouseEventKind::ScrollUp => return Some(MouseAction::ScrollUp),
+        MouseEventKind::ScrollDown => return Some(MouseAction::ScrollDown),
+        MouseEventKind::Down(MouseButton::Right) => return None,
+        MouseEventKind::Down(MouseButton::Left) => {
+            let row = me.row;
+            // 点在右侧预览面板里不算
+            if me.column >= list_w {
+                return None;
+            }
+            if row >= content_top {
+                let idx = (row - content_top) as usize;
+                if idx < st.visible_len(view) {
+                    return Some(MouseAction::MoveToRow(idx));
+                }
+            }
+        }
+        _ => {}
+    }
+    None
+}
+
+// ---------------- 绘制（stderr） ----------------
+fn compute_top_margin_bottom(h: u16, panel_h: u16) -> u16 {
+    // 贴底：顶行 = 屏幕高 - 面板高
+    h.saturating_sub(panel_h)
+}
+/// 逐行清空 [top, top + h)：内联模式下不能整屏清除
+fn clear_region<W: Write>(err: &mut W, top: u16, h: u16) -> io::Result<()> {
+    for row in top..top.saturating_add(h) {
+        err.queue(MoveTo(0, row))?
+            .queue(Clear(ClearType::CurrentLine))?;
+    }
+    Ok(())
+}
+fn redraw_main<W: Write>(err: &mut W, ui: &Ui) -> io::Result<()> {
+    let (panel_h, top_margin, st, view, items) =
+        (ui.panel_h, ui.top_margin, &ui.st, &ui.view, &ui.items);
+    let (mode, input_pos) = (ui.mode, ui.input_pos);
+    let t = &ui.theme;
+    let b = t.panel;
+    // 查询文本，光标处插入（闪烁时用空格占位，文字不跳动）
+    let query = ui
+        .query
+        .render(if ui.caret_visible { t.caret } else { " " });
+
+    clear_region(err, ui.origin, ui.h)?;
+    let inner_width = ui.list_w.saturating_sub(2) as usize;
+
+    // 顶栏
+    err.queue(MoveTo(0, top_margin))?;
+    t.border.paint(err, &b.tl.to_string())?;
+    // 翻页模式显示页码，连续滚动显示光标位置
+    let position = match st.scroll {
+        ScrollMode::Page => format!("第 {}/{} 页", st.page(), st.page_count(view)),
+        ScrollMode::Continuous if view.view_len() == 0 => "0/0".to_string(),
+        ScrollMode::Continuous => format!("{}/{}", st.sel + 1, view.view_len()),
+    };
+    let marked = if ui.marked.is_empty() {
+        String::new()
+    } else {
+        format!("已标记 {} • ", ui.marked.len())
+    };
+    let title = match mode {
+        Mode::Search => format!(
+            " cdh • 搜索 {}/{} 条 • {marked}{position} ",
+            view.view_len(),
+            items.len(),
+        ),
+        _ => format!(" cdh • {marked}{position} • 共 {} 条 ", view.view_len()),
+    };
+    let title_line = if mode == Mode::Search && input_pos == InputPos::Title {
+        let prompt = format!("  搜索: {query}");
+        pad(&(title + &prompt), inner_width, b.h)
+    } else {
+        pad(&title, inner_width, b.h)
+    };
+    t.title.paint(err, &title_line)?;
+    t.border.paint(err, &b.tr.to_string())?;
+
+    let side = b.v.to_string();
+
+    // 顶部输入（可选；非搜索时留空行占位）
+    let header_lines = ui.header_lines();
+    if header_lines > 0 {
+        let prompt = if mode == Mode::Search {
+            format!(" 搜索: {query}")
+        } else {
+            String::new()
+        };
+        err.queue(MoveTo(0, top_margin + 1))?;
+        t.border.paint(err, &side)?;
+        t.prompt.paint(err, &fill_right(&prompt, inner_width))?;
+        t.border.paint(err, &side)?;
+    }
+
+    // 内容：视口内前 10 行带数字标号（数字键直达），其余行用空白对齐
+    let visible = st.visible_len(view);
+    let mark_glyph = if t.is_ascii() { "*" } else { "●" };
+    let pin_glyph = if t.is_ascii() { "^" } else { "▲" };
+    let content_start_row = top_margin + 1 + header_lines;
+
+    for i in 0..st.rows {
+        let row = content_start_row + i as u16;
+        err.queue(MoveTo(0, row))?;
+        t.border.paint(err, &side)?;
+
+        let abs = view.abs_at(st.top + i).filter(|_| i < visible);
+        // 多选时已标记的条目在行首打个标记；没有标记时置顶的条目显示置顶标记
+        let mark = match abs {
+            Some(a) if ui.marked.contains(&items[a].path) => mark_glyph,
+            Some(a) if ui.pinned.contains(&items[a].path) => pin_glyph,
+            _ => " ",
+        };
+        let label = if i < 10 {
+            format!("{mark}{i} ) ")
+        } else {
+            format!("{mark}    ")
+        };
+        let txt = match abs {
+            Some(abs) => {
+                let it = &items[abs];
+                let cols = ui.columns.render(it, ui.now, t.is_ascii());
+                // 书签条目在路径前显示 `@name`
+                let tag = it
+                    .bookmark
+                    .as_ref()
+                    .map(|n| format!("@{n} "))
+                    .unwrap_or_default();
+                // 路径只在显示时缩写 / 省略，选中的仍是真实路径
+                let room = inner_width.saturating_sub(
+                    display_width(&label) + display_width(&cols) + display_width(&tag),
+                );
+                format!("{label}{cols}{tag}{}", ui.display.render(&it.path, room))
+            }
+            _ => label,
+        };
+        let style = if i == st.row() && i < visible {
+            t.selected
+        } else {
+            Style::default()
+        };
+        style.paint(err, &pad(&txt, inner_width, ' '))?;
+        t.border.paint(err, &side)?;
+    }
+
+    // 底栏
+    let bottom_row = top_margin + panel_h - 1;
+    err.queue(MoveTo(0, bottom_row))?;
+    t.border.paint(err, &b.bl.to_string())?;
+    let search_in_footer = mode == Mode::Search && input_pos == InputPos::Bottom;
+    match mode {
+        Mode::Confirm => {
+            let path = ui
+                .confirm
+                .and_then(|(abs, _)| items.get(abs))
+                .map(|it| ui.display.shorten(&it.path))
+                .unwrap_or_default();
+            let prompt = format!(" 从历史中删除 {path} ？(y/N) ");
+            t.confirm.paint(err, &pad(&prompt, inner_width, ' '))?;
+        }
+        _ if ui.countdown.is_some() && !search_in_footer => {
+            let n = ui.countdown.unwrap_or(0);
+            let msg = format!(" {n} 秒后无操作自动退出 · 按任意键继续 ");
+            t.status.paint(err, &fill_right(&msg, inner_width))?;
+        }
+        // 条目操作后的临时提示（底栏是搜索输入时不覆盖）
+        _ if ui.status.is_some() && !search_in_footer => {
+            let msg = format!(" {} ", ui.status.as_deref().unwrap_or(""));
+            t.status.paint(err, &pad(&msg, inner_width, ' '))?;
+        }
+        Mode::Search => {
+            // 输入行为空且有历史时提示可以召回
+            let mut tips = vec![(Action::Back, "返回")];
+            if ui.query.as_str().is_empty() && !ui.history.recent.is_empty() {
+                tips.push((Action::History, "历史"));
+            }
+            tips.extend([(Action::Mark, "标记"), (Action::Select, "选")]);
+            let tips = ui.key_tips(KeyContext::Search, &tips);
+            if search_in_footer {
+                let prompt = format!(" 搜索: {query} ·{tips}");
+                t.prompt.paint(err, &fill_right(&prompt, inner_width))?;
+            } else {
+                t.hint.paint(err, &fill_right(&tips, inner_width))?;
+            }
+        }
+        _ => {
+            let prompt = ui.key_tips(
+                KeyContext::Normal,
+                &[
+                    (Action::Mark, "标记"),
+                    (Action::Select, "选"),
+                    (Action::Quit, "退出"),
+                    (Action::Help, "帮助"),
+                    (Action::Search, "搜索"),
+                ],
+            );
+            t.hint.paint(err, &fill_right(&prompt, inner_width))?;
+        }
+    }
+    t.border.paint(err, &b.br.to_string())?;
+
+    // 浮层输入
+    if mode == Mode::Search && input_pos == InputPos::Overlay {
+        let panel = Rect {
+            x: 0,
+            y: top_margin,
+            w: ui.list_w,
+            h: panel_h,
+        };
+        draw_overlay_input(err, t, panel, ui.area(), &query)?;
+    }
+
+    Ok(())
+}
+
+/// 画一个空心方框（帮助页 / 浮层输入共用），内部用空格清空
+fn draw_box<W: Write>(
+    err: &mut W,
+    t: &Theme,
+    left: u16,
+    top: u16,
+    w: usize,
+    h: u16,
+) -> io::Result<()> {
+    let b = t.popup;
+    let inner = w.saturating_sub(2);
+    let hline = b.h.to_string().repeat(inner);
+    err.queue(MoveTo(left, top))?;
+    t.border.paint(err, &format!("{}{hline}{}", b.tl, b.tr))?;
+    for i in 1..h.saturating_sub(1) {
+        err.queue(MoveTo(left, top + i))?;
+        t.border.paint(err, &b.v.to_string())?;
+        err.queue(Print(" ".repeat(inner)))?;
+        t.border.paint(err, &b.v.to_string())?;
+    }
+    err.queue(MoveTo(left, top + h.saturating_sub(1)))?;
+    t.border.paint(err, &format!("{}{hline}{}", b.bl, b.br))
+}
+
+/// 浮层输入框：优先放在列表面板上方，其次下方；区域内都放不下（如内联模式）时盖在列表顶部
+fn draw_overlay_input<W: Write>(
+    err: &mut W,
+    t: &Theme,
+    panel: Rect,
+    area: Rect,
+    query: &str,
+) -> io::Result<()> {
+    let text = format!(" 搜索: {query}");
+    let width = (display_width(&text) + 4).min(panel.w as usize - 4);
+    let bw = width as u16;
+
+    let left = panel.w.saturating_sub(bw).saturating_sub(2);
+    let top = if panel.y >= area.y + 4 {
+        panel.y - 3
+    } else if panel.y + panel.h + 4 <= area.y + area.h {
+        panel.y + panel.h + 1
+    } else {
+        panel.y + 1
+    };
+
+    draw_box(err, t, left, top, width, 3)?;
+    err.queue(MoveTo(left + 1, top + 1))?;
+    t.prompt.paint(err, &pad(&text, width - 2, ' '))
+}
+
+/// 预览面板：边框 + 标题，内容按行截断到面板宽度
+fn draw_preview<W: Write>(
+    err: &mut W,
+    t: &Theme,
+    rect: Rect,
+    lines: Option<&[String]>,
+    has_target: bool,
+) -> io::Result<()> {
+    if rect.w < 4 || rect.h < 3 {
+        return Ok(());
+    }
+    let b = t.panel;
+    let inner_w = (rect.w - 2) as usize;
+    let side = b.v.to_string();
+
+    err.queue(MoveTo(rect.x, rect.y))?;
+    t.border.paint(err, &b.tl.to_string())?;
+    t.title.paint(err, &pad(" 预览 ", inner_w, b.h))?;
+    t.border.paint(err, &b.tr.to_string())?;
+
+    let placeholder = if has_target { "加载中…" } else { "" };
+    let rows = (rect.h - 2) as usize;
+    for i in 0..rows {
+        let text = match lines {
+            Some(ls) => ls.get(i).map(String::as_str).unwrap_or(""),
+            None if i == 0 => placeholder,
+            None => "",
+        };
+        let style = if lines.is_none() {
+            t.dim
+        } else {
+            Style::default()
+        };
+        err.queue(MoveTo(rect.x, rect.y + 1 + i as u16))?;
+        t.border.paint(err, &side)?;
+        style.paint(err, &pad(&format!(" {text}"), inner_w, ' '))?;
+        t.border.paint(err, &side)?;
+    }
+
+    err.queue(MoveTo(rect.x, rect.y + rect.h - 1))?;
+    t.border.paint(
+        err,
+        &format!("{}{}{}", b.bl, b.h.to_string().repeat(inner_w), b.br),
+    )
+}
+
+fn redraw_help<W: Write>(
+    err: &mut W,
+    t: &Theme,
+    area: Rect,
+    keymap: &Keymap,
+    actions: bool,
+) -> io::Result<()> {
+    let (w, h) = (area.w, area.h);
+    clear_region(err, area.y, h)?;
+
+    // 按键说明由 keymap 生成，始终与实际绑定一致
+    let mut lines = vec![
+        format!("帮助（按键预设：{}）", keymap.preset_name()),
+        String::new(),
+        "主界面：".to_string(),
+    ];
+    lines.extend(keymap.help_lines(KeyContext::Normal, actions));
+    lines.push("  0..9  直达当前屏的前 10 行（未被绑定时）".to_string());
+    lines.push(String::new());
+    lines.push("搜索模式：".to_string());
+    lines.push("  输入任意字符进行过滤，Backspace 删除".to_string());
+    lines.extend(keymap.help_lines(KeyContext::Search, actions));
+    lines.push(String::new());
+    lines.push("鼠标：左键单击移动，双击选中（300ms），滚轮滚动".to_string());
+    // 终端太矮时截掉末尾几行，保证边框完整
+    lines.truncate(h.saturating_sub(2) as usize);
+
+    let width = lines.iter().map(|s| display_width(s)).max().unwrap_or(10) + 4;
+    let width = width.min(w as usize - 4);
+    let box_w = width as u16;
+    let box_h = lines.len() as u16 + 2;
+    let left = (w.saturating_sub(box_w)) / 2;
+    let top = area.y + (h.saturating_sub(box_h)) / 2;
+
+    draw_box(err, t, left, top, width, box_h)?;
+    for (i, line) in lines.iter().enumerate() {
+        let text = pad(line, width.saturating_sub(3), ' ');
+        err.queue(MoveTo(left + 2, top + 1 + i as u16))?;
+        if i == 0 {
+            t.title.paint(err, &text)?;
+        } else {
+            err.queue(Print(text))?;
+        }
+    }
+    err.flush()?;
+    Ok(())
+}
+
+// ---------------- 文本与小工具 ----------------
+fn display_width(s: &str) -> usize {
+    UnicodeWidthStr::width(s)
+}
+/// 右侧补空格到 width；超出时截掉末尾（以 … 结尾），不挤掉右边框
+fn fill_right(s: &str, width: usize) -> String {
+    let w = display_width(s);
+    if w <= width {
+        return format!("{s}{}", " ".repeat(width - w));
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for ch in s.chars() {
+        let cw = display_width(&ch.to_string());
+        if used + cw + 1 > width {
+            break;
+        }
+        used += cw;
+        out.push(ch);
+    }
+    out.push('…');
+    format!("{out}{}", " ".repeat(width.saturating_sub(used + 1)))
+}
+fn pad(s: &str, width: usize, fill: char) -> String {
+    let w = display_width(s);
+    if w > width {
+        trim_mid(s, width)
+    } else {
+        format!("{s}{}", fill.to_string().repeat(width - w))
+    }
+}
+fn trim_mid(s: &str, width: usize) -> String {
+    if width < 3 {
+        return "…".repeat(width);
+    }
+    let left = (width - 1) / 2;
+    let right = width - 1 - left;
+    let mut l = String::new();
+    let mut w = 0;
+    for ch in s.chars() {
+        let cw = display_width(&ch.to_string());
+        if w + cw > left {
+            break;
+        }
+        w += cw;
+        l.push(ch);
+    }
+    let mut r = String::new();
+    w = 0;
+    for ch in s.chars().rev() {
+        let cw = display_width(&ch.to_string());
+        if w + cw > right {
+            break;
+        }
+        w += cw;
+        r.insert(0, ch);
+    }
+    format!("{l}…{r}")
+}
+fn ensure(cond: bool, msg: &str) -> io::Result<()> {
+    if !cond {
+        Err(io::Error::new(io::ErrorKind::Other, msg.to_string()))
+    } else {
+        Ok(())
+    }
+}
+/// 有控制终端可以读按键（crossterm 在 stdin 不是终端时会改用 /dev/tty）
+fn tty_available() -> bool {
+    cfg!(unix) && std::fs::File::open("/dev/tty").is_ok()
+}
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+fn beep<W: Write>(err: &mut W) -> io::Result<()> {
+    err.queue(Print("\x07"))?.flush()?; // BEL
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resize_keeps_cursor_item() {
+        let view = View::new(100);
+        let mut st = State::new(10, ScrollMode::Page);
+        st.set_sel(37, &view);
+        assert_eq!((st.page(), st.row()), (4, 7));
+
+        // 变高：每屏 25 行，光标仍在第 37 项，只是换了页
+        st.set_rows(25, &view);
+        assert_eq!(st.sel, 37);
+        assert_eq!((st.page(), st.row()), (2, 12));
+
+        // 变矮再翻页：保持行号
+        st.set_rows(4, &view);
+        st.page_right(&view);
+        assert_eq!(st.sel, 41);
+        assert_eq!(st.row(), 1);
+    }
+
+    #[test]
+    fn continuous_scroll_follows_cursor() {
+        let view = View::new(30);
+        let mut st = State::new(5, ScrollMode::Continuous);
+        for _ in 0..6 {
+            st.move_down(&view);
+        }
+        assert_eq!((st.sel, st.top), (6, 2));
+        st.move_up(&view);
+        assert_eq!((st.sel, st.top), (5, 2));
+
+        st.set_sel(29, &view);
+        assert_eq!(st.top, 25);
+        // 视口贴底，不留空行
+        st.set_rows(8, &view);
+        assert_eq!((st.sel, st.top), (29, 22));
+        st.page_right(&view);
+        assert_eq!((st.sel, st.top), (29, 22));
+        st.page_left(&view);
+        assert_eq!((st.sel, st.top), (21, 14));
+    }
+}
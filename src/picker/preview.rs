@@ -0,0 +1,260 @@
+//! 预览面板：高亮目录的内容列表 + git 分支/脏状态 + README 开头几行
+//! - 在后台线程里计算，结果经 channel 送回主循环，不阻塞按键处理
+//! - 结果按路径缓存；快速滚动时只计算最新的请求
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// 目录列表最多展示的条目数
+const MAX_ENTRIES: usize = 40;
+/// README 最多展示的行数
+const README_LINES: usize = 20;
+/// README 最多读取的字节数（避免大文件拖慢预览）
+const README_BYTES: u64 = 8 * 1024;
+
+/// 预览面板位置
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PreviewPos {
+    /// 宽终端放右侧，窄终端放底部
+    Auto,
+    Right,
+    Bottom,
+}
+
+/// 读取 `CDH_PREVIEW`：right / bottom / auto 表示默认打开；其余（含未设置）默认关闭
+pub(crate) fn preview_from_env() -> (bool, PreviewPos) {
+    match std::env::var("CDH_PREVIEW").map(|s| s.to_lowercase()) {
+        Ok(s) if s == "right" => (true, PreviewPos::Right),
+        Ok(s) if s == "bottom" => (true, PreviewPos::Bottom),
+        Ok(s) if s == "auto" || s == "1" || s == "true" => (true, PreviewPos::Auto),
+        _ => (false, PreviewPos::Auto),
+    }
+}
+
+/// 后台预览：请求发给 worker 线程，结果放进缓存
+pub(crate) struct Previewer {
+    tx: Sender<String>,
+    rx: Receiver<(String, Vec<String>)>,
+    cache: HashMap<String, Vec<String>>,
+    pending: Option<String>,
+}
+
+impl Previewer {
+    pub(crate) fn new() -> Self {
+        let (req_tx, req_rx) = mpsc::channel::<String>();
+        let (res_tx, res_rx) = mpsc::channel();
+        thread::spawn(move || worker(req_rx, res_tx));
+        Self {
+            tx: req_tx,
+            rx: res_rx,
+            cache: HashMap::new(),
+            pending: None,
+        }
+    }
+
+    /// 请求某路径的预览（已缓存或正在计算则忽略）
+    pub(crate) fn request(&mut self, path: &str) {
+        if self.cache.contains_key(path) || self.pending.as_deref() == Some(path) {
+            return;
+        }
+        if self.tx.send(path.to_string()).is_ok() {
+            self.pending = Some(path.to_string());
+        }
+    }
+
+    /// 收取已完成的结果；有新结果时返回 true（调用方据此重绘）
+    pub(crate) fn poll(&mut self) -> bool {
+        let mut got = false;
+        while let Ok((path, lines)) = self.rx.try_recv() {
+            if self.pending.as_deref() == Some(path.as_str()) {
+                self.pending = None;
+            }
+            self.cache.insert(path, lines);
+            got = true;
+        }
+        got
+    }
+
+    pub(crate) fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub(crate) fn get(&self, path: &str) -> Option<&[String]> {
+        self.cache.get(path).map(|v| v.as_slice())
+    }
+}
+
+fn worker(rx: Receiver<String>, tx: Sender<(String, Vec<String>)>) {
+    while let Ok(mut path) = rx.recv() {
+        // 只算最新的请求：中间被滚过去的条目直接跳过
+        while let Ok(newer) = rx.try_recv() {
+            path = newer;
+        }
+        let lines = build_preview(Path::new(&path));
+        if tx.send((path, lines)).is_err() {
+            break;
+        }
+    }
+}
+
+/// 生成预览文本（每个元素一行，不含换行/控制字符）
+pub(crate) fn build_preview(dir: &Path) -> Vec<String> {
+    let mut out = Vec::new();
+
+    let entries = match fs::read_dir(dir) {
+        Ok(rd) => rd,
+        Err(e) => {
+            out.push(format!("无法读取目录: {e}"));
+            return out;
+        }
+    };
+
+    if let Some(git) = git_status(dir) {
+        out.push(git);
+        out.push(String::new());
+    }
+
+    // 目录在前、文件在后，各自按名字排序
+    let mut names: Vec<(bool, String)> = entries
+        .filter_map(Result::ok)
+        .map(|e| {
+            let is_dir = e.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            (is_dir, e.file_name().to_string_lossy().into_owned())
+        })
+        .collect();
+    names.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    let readme = names
+        .iter()
+        .find(|(is_dir, n)| !is_dir && n.to_lowercase().starts_with("readme"))
+        .map(|(_, n)| n.clone());
+
+    if names.is_empty() {
+        out.push("（空目录）".to_string());
+    }
+    for (is_dir, name) in names.iter().take(MAX_ENTRIES) {
+        let suffix = if *is_dir { "/" } else { "" };
+        out.push(sanitize(&format!("{name}{suffix}")));
+    }
+    if names.len() > MAX_ENTRIES {
+        out.push(format!("… 另有 {} 项", names.len() - MAX_ENTRIES));
+    }
+
+    if let Some(name) = readme {
+        if let Some(lines) = readme_head(&dir.join(&name)) {
+            out.push(String::new());
+            out.push(format!("── {name} ──"));
+            out.extend(lines);
+        }
+    }
+
+    out
+}
+
+/// `git status --porcelain --branch`：返回 “⎇ 分支 · 状态” 一行；非仓库 / 无 git 时返回 None
+fn git_status(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["status", "--porcelain=v1", "--branch"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut lines = text.lines();
+    let head = lines.next()?.strip_prefix("## ")?;
+    let branch = head
+        .strip_prefix("No commits yet on ")
+        .unwrap_or(head)
+        .split("...")
+        .next()
+        .unwrap_or(head)
+        .split(' ')
+        .next()
+        .unwrap_or(head);
+    let changed = lines.filter(|l| !l.is_empty()).count();
+    let state = if changed == 0 {
+        "✓ clean".to_string()
+    } else {
+        format!("● {changed} 处改动")
+    };
+    Some(sanitize(&format!("⎇ {branch} · {state}")))
+}
+
+fn readme_head(path: &Path) -> Option<Vec<String>> {
+    let mut buf = Vec::new();
+    fs::File::open(path)
+        .ok()?
+        .take(README_BYTES)
+        .read_to_end(&mut buf)
+        .ok()?;
+    if buf.contains(&0) {
+        return None; // 二进制
+    }
+    let text = String::from_utf8_lossy(&buf);
+    Some(text.lines().take(README_LINES).map(sanitize).collect())
+}
+
+/// 去掉控制字符，避免破坏终端绘制（Tab 展开为空格）
+fn sanitize(s: &str) -> String {
+    s.chars()
+        .filter_map(|c| match c {
+            '\t' => Some(' '),
+            c if c.is_control() => None,
+            c => Some(c),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn preview_lists_dirs_first_and_readme_head() {
+        let uniq = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("cdh_preview_test_{uniq}"));
+        fs::create_dir_all(root.join("zdir")).unwrap();
+        fs::write(root.join("a.txt"), "x").unwrap();
+        fs::write(root.join("README.md"), "# Title\n\tbody\n").unwrap();
+
+        let lines = build_preview(&root);
+        let zdir = lines.iter().position(|l| l == "zdir/").unwrap();
+        let a = lines.iter().position(|l| l == "a.txt").unwrap();
+        assert!(zdir < a);
+        assert!(lines.contains(&"── README.md ──".to_string()));
+        assert!(lines.contains(&"# Title".to_string()));
+        assert!(lines.contains(&" body".to_string()));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn previewer_delivers_results_in_background() {
+        let mut p = Previewer::new();
+        let dir = std::env::temp_dir();
+        let key = dir.to_string_lossy().into_owned();
+        p.request(&key);
+        assert!(p.is_pending());
+        for _ in 0..200 {
+            if p.poll() && !p.is_pending() {
+                break;
+            }
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(p.get(&key).is_some());
+    }
+}
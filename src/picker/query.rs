@@ -0,0 +1,220 @@
+//! 搜索输入行：查询文本 + 光标位置（按字符计），以及历史查询
+//!
+//! 删词（Ctrl-W）把空白和 `/` 都当作分隔符，删路径片段时一次删一段。
+//! 历史查询由调用方读出后通过 `PickOpt::queries` 传入，picker 只读不写。
+
+/// 某个查询选中过某个路径的次数
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryPick {
+    pub query: String,
+    pub path: String,
+    pub count: u32,
+}
+
+/// 搜索历史
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QueryHistory {
+    /// 最近用过的查询，新的在前，已去重
+    pub recent: Vec<String>,
+    /// 查询 → 选中路径，按次数从高到低（次数相同时最近的在前）
+    pub picks: Vec<QueryPick>,
+}
+
+impl QueryHistory {
+    /// 以 q 开头的历史查询选中过的路径（大小写不敏感）：完全相同的查询优先，其次按次数
+    pub(crate) fn remembered(&self, q: &str) -> Vec<&str> {
+        let q = q.to_lowercase();
+        if q.is_empty() {
+            return Vec::new();
+        }
+        let mut hits: Vec<(bool, &QueryPick)> = self
+            .picks
+            .iter()
+            .filter_map(|p| {
+                let pq = p.query.to_lowercase();
+                pq.starts_with(&q).then_some((pq == q, p))
+            })
+            .collect();
+        // 稳定排序：同一档内保持 picks 原有的次数顺序
+        hits.sort_by_key(|(exact, _)| !exact);
+        hits.into_iter().map(|(_, p)| p.path.as_str()).collect()
+    }
+}
+
+/// 搜索查询与光标
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Query {
+    text: String,
+    /// 光标前的字符数（0..=字符总数）
+    caret: usize,
+}
+
+impl Query {
+    pub(crate) fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.text.clear();
+        self.caret = 0;
+    }
+
+    /// 整体替换（召回历史查询），光标放到末尾
+    pub(crate) fn set(&mut self, text: &str) {
+        self.clear();
+        self.insert(text);
+    }
+
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    /// 第 n 个字符的字节偏移
+    fn byte_at(&self, n: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(n)
+            .map(|(i, _)| i)
+            .unwrap_or(self.text.len())
+    }
+
+    /// 在光标处插入；控制字符（粘贴进来的换行、Tab 等）被丢弃
+    pub(crate) fn insert(&mut self, s: &str) {
+        let clean: String = s.chars().filter(|c| !c.is_control()).collect();
+        let at = self.byte_at(self.caret);
+        self.text.insert_str(at, &clean);
+        self.caret += clean.chars().count();
+    }
+
+    /// 删除光标前一个字符
+    pub(crate) fn backspace(&mut self) {
+        if self.caret > 0 {
+            self.delete_range(self.caret - 1, self.caret);
+        }
+    }
+
+    pub(crate) fn left(&mut self) {
+        self.caret = self.caret.saturating_sub(1);
+    }
+
+    pub(crate) fn right(&mut self) {
+        self.caret = (self.caret + 1).min(self.len());
+    }
+
+    pub(crate) fn home(&mut self) {
+        self.caret = 0;
+    }
+
+    pub(crate) fn end(&mut self) {
+        self.caret = self.len();
+    }
+
+    /// Ctrl-U：删到行首
+    pub(crate) fn kill_to_start(&mut self) {
+        self.delete_range(0, self.caret);
+    }
+
+    /// Ctrl-K：删到行尾
+    pub(crate) fn kill_to_end(&mut self) {
+        self.delete_range(self.caret, self.len());
+    }
+
+    /// Ctrl-W：删掉光标前的一个词（先跳过分隔符，再删到下一个分隔符）
+    pub(crate) fn delete_word(&mut self) {
+        let chars: Vec<char> = self.text.chars().collect();
+        let is_sep = |c: char| c.is_whitespace() || c == '/';
+        let mut start = self.caret;
+        while start > 0 && is_sep(chars[start - 1]) {
+            start -= 1;
+        }
+        while start > 0 && !is_sep(chars[start - 1]) {
+            start -= 1;
+        }
+        self.delete_range(start, self.caret);
+    }
+
+    /// 删除字符区间 [from, to)，光标停在 from
+    fn delete_range(&mut self, from: usize, to: usize) {
+        let (a, b) = (self.byte_at(from), self.byte_at(to));
+        self.text.replace_range(a..b, "");
+        self.caret = from;
+    }
+
+    /// 显示用：在光标处插入光标字符
+    pub(crate) fn render(&self, caret: &str) -> String {
+        let at = self.byte_at(self.caret);
+        format!("{}{caret}{}", &self.text[..at], &self.text[at..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q(text: &str, caret: usize) -> Query {
+        Query {
+            text: text.to_string(),
+            caret,
+        }
+    }
+
+    #[test]
+    fn caret_editing() {
+        let mut s = Query::default();
+        s.insert("prj");
+        s.left();
+        s.left();
+        s.insert("界");
+        assert_eq!(s, q("p界rj", 2));
+        assert_eq!(s.render("▌"), "p界▌rj");
+        s.backspace();
+        s.right();
+        s.kill_to_end();
+        assert_eq!(s, q("pr", 2));
+        s.home();
+        s.backspace();
+        s.insert("a\nb\t");
+        assert_eq!(s, q("abpr", 2));
+        s.kill_to_start();
+        assert_eq!(s, q("pr", 0));
+        s.end();
+        s.right();
+        assert_eq!(s.caret, 2);
+    }
+
+    #[test]
+    fn remembered_prefers_exact_query() {
+        let pick = |query: &str, path: &str, count| QueryPick {
+            query: query.into(),
+            path: path.into(),
+            count,
+        };
+        let h = QueryHistory {
+            recent: vec![],
+            picks: vec![
+                pick("proj", "/w/proj", 5),
+                pick("pr", "/w/prime", 2),
+                pick("Proj", "/w/proj-old", 1),
+            ],
+        };
+        assert_eq!(
+            h.remembered("pr"),
+            vec!["/w/prime", "/w/proj", "/w/proj-old"]
+        );
+        assert_eq!(h.remembered("PRO"), vec!["/w/proj", "/w/proj-old"]);
+        assert!(h.remembered("").is_empty());
+        assert!(h.remembered("x").is_empty());
+    }
+
+    #[test]
+    fn delete_word_stops_at_slash_and_space() {
+        let mut s = q("src/picker/mod", 14);
+        s.delete_word();
+        assert_eq!(s, q("src/picker/", 11));
+        s.delete_word();
+        assert_eq!(s, q("src/", 4));
+        let mut s = q("foo bar  baz", 9);
+        s.delete_word();
+        assert_eq!(s, q("foo baz", 4));
+    }
+}
@@ -0,0 +1,496 @@
+//! 主题：配色（具名调色板，支持 16 色 / 256 色 / truecolor）+ 边框字形（含纯 ASCII 回退）
+//!
+//! 优先级：内置调色板 → 配置文件 `[theme]` / `[theme.colors]` → 环境变量
+//! - `CDH_THEME`：调色板名（default / mono / nord / gruvbox）
+//! - `NO_COLOR`（非空）或 `CDH_COLOR=0`：去掉所有颜色，只保留粗体 / 反色等属性
+//! - 终端不支持 truecolor（`COLORTERM` 不是 truecolor/24bit）时，#rrggbb 自动降级为最接近的 256 色
+//!
+//! ```toml
+//! [theme]
+//! name = "nord"
+//! borders = "ascii"          # rounded / sharp / ascii
+//!
+//! [theme.colors]
+//! selected = "#ffffff on 24 bold"
+//! hint = "dark_grey"
+//! ```
+
+use crate::config::ConfigFile;
+use crossterm::style::{
+    Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+};
+use crossterm::QueueableCommand;
+use std::io::{self, Write};
+
+/// 一种文字样式：前景 / 背景色 + 属性
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub underline: bool,
+    pub reverse: bool,
+}
+
+impl Style {
+    const fn fg(c: Color) -> Self {
+        Self {
+            fg: Some(c),
+            bg: None,
+            bold: false,
+            dim: false,
+            underline: false,
+            reverse: false,
+        }
+    }
+
+    const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    const fn on(mut self, c: Color) -> Self {
+        self.bg = Some(c);
+        self
+    }
+
+    fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 解析 "cyan bold" / "#eceff4 on #434c5e bold" / "214 underline" / "none"
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut st = Self::default();
+        let mut tokens = spec.split_whitespace();
+        while let Some(tok) = tokens.next() {
+            match tok.to_ascii_lowercase().as_str() {
+                "none" | "plain" => st = Self::default(),
+                "bold" => st.bold = true,
+                "dim" => st.dim = true,
+                "underline" => st.underline = true,
+                "reverse" => st.reverse = true,
+                "on" => {
+                    let c = tokens.next().ok_or("on 后面需要背景色")?;
+                    st.bg = Some(parse_color(c)?);
+                }
+                _ => st.fg = Some(parse_color(tok)?),
+            }
+        }
+        Ok(st)
+    }
+
+    /// 去掉颜色；原本靠背景色区分的（如选中行）改用反色，保证仍然看得出来
+    fn without_color(self) -> Self {
+        Self {
+            fg: None,
+            bg: None,
+            reverse: self.reverse || self.bg.is_some(),
+            ..self
+        }
+    }
+
+    /// 把 #rrggbb 降级为 256 色
+    fn downgrade(self) -> Self {
+        Self {
+            fg: self.fg.map(to_ansi256),
+            bg: self.bg.map(to_ansi256),
+            ..self
+        }
+    }
+
+    pub(crate) fn set<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        if let Some(c) = self.fg {
+            w.queue(SetForegroundColor(c))?;
+        }
+        if let Some(c) = self.bg {
+            w.queue(SetBackgroundColor(c))?;
+        }
+        for (on, attr) in [
+            (self.bold, Attribute::Bold),
+            (self.dim, Attribute::Dim),
+            (self.underline, Attribute::Underlined),
+            (self.reverse, Attribute::Reverse),
+        ] {
+            if on {
+                w.queue(SetAttribute(attr))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn reset<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        if !self.is_plain() {
+            w.queue(ResetColor)?.queue(SetAttribute(Attribute::Reset))?;
+        }
+        Ok(())
+    }
+
+    /// 以该样式输出一段文字，结束后恢复默认样式
+    pub(crate) fn paint<W: Write>(&self, w: &mut W, text: &str) -> io::Result<()> {
+        self.set(w)?;
+        w.queue(Print(text))?;
+        self.reset(w)
+    }
+}
+
+/// 颜色写法：名字（cyan / dark_grey …）、0..255（256 色）、#rrggbb（truecolor）
+pub fn parse_color(s: &str) -> Result<Color, String> {
+    if let Some(hex) = s.strip_prefix('#') {
+        let v = u32::from_str_radix(hex, 16).map_err(|_| format!("无效颜色 {s}"))?;
+        if hex.len() != 6 {
+            return Err(format!("无效颜色 {s}（需要 #rrggbb）"));
+        }
+        return Ok(Color::Rgb {
+            r: (v >> 16) as u8,
+            g: (v >> 8) as u8,
+            b: v as u8,
+        });
+    }
+    if let Ok(n) = s.parse::<u8>() {
+        return Ok(Color::AnsiValue(n));
+    }
+    let name: String = s
+        .chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .collect::<String>()
+        .to_ascii_lowercase();
+    Ok(match name.as_str() {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "darkred" => Color::DarkRed,
+        "green" => Color::Green,
+        "darkgreen" => Color::DarkGreen,
+        "yellow" => Color::Yellow,
+        "darkyellow" => Color::DarkYellow,
+        "blue" => Color::Blue,
+        "darkblue" => Color::DarkBlue,
+        "magenta" => Color::Magenta,
+        "darkmagenta" => Color::DarkMagenta,
+        "cyan" => Color::Cyan,
+        "darkcyan" => Color::DarkCyan,
+        "white" => Color::White,
+        "grey" | "gray" => Color::Grey,
+        "darkgrey" | "darkgray" => Color::DarkGrey,
+        "reset" | "default" => Color::Reset,
+        _ => return Err(format!("无效颜色 {s}")),
+    })
+}
+
+/// truecolor → xterm 256 色（6×6×6 色立方体或 24 级灰阶，取更接近的一个）
+fn to_ansi256(c: Color) -> Color {
+    let Color::Rgb { r, g, b } = c else {
+        return c;
+    };
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let nearest = |v: u8| {
+        (0..6)
+            .min_by_key(|&i| (LEVELS[i] as i32 - v as i32).abs())
+            .unwrap_or(0)
+    };
+    let (ri, gi, bi) = (nearest(r), nearest(g), nearest(b));
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+    let avg = (r as u32 + g as u32 + b as u32) / 3;
+    let gi_gray = ((avg.saturating_sub(8)) / 10).min(23) as u8;
+    let gray = 8 + gi_gray * 10;
+
+    let dist = |(x, y, z): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(x, r) + d(y, g) + d(z, b)
+    };
+    if dist((gray, gray, gray)) < dist(cube) {
+        Color::AnsiValue(232 + gi_gray)
+    } else {
+        Color::AnsiValue(16 + 36 * ri as u8 + 6 * gi as u8 + bi as u8)
+    }
+}
+
+/// 一套边框字形
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Borders {
+    pub tl: char,
+    pub tr: char,
+    pub bl: char,
+    pub br: char,
+    pub h: char,
+    pub v: char,
+}
+
+const ROUNDED: Borders = Borders {
+    tl: '╭',
+    tr: '╮',
+    bl: '╰',
+    br: '╯',
+    h: '─',
+    v: '│',
+};
+const SHARP: Borders = Borders {
+    tl: '┌',
+    tr: '┐',
+    bl: '└',
+    br: '┘',
+    h: '─',
+    v: '│',
+};
+const ASCII: Borders = Borders {
+    tl: '+',
+    tr: '+',
+    bl: '+',
+    br: '+',
+    h: '-',
+    v: '|',
+};
+
+/// picker 绘制用到的全部样式与字形
+#[derive(Clone, Debug)]
+pub struct Theme {
+    name: String,
+    /// 边框线
+    pub border: Style,
+    /// 顶栏标题
+    pub title: Style,
+    /// 当前高亮行
+    pub selected: Style,
+    /// 搜索输入
+    pub prompt: Style,
+    /// 底栏按键提示
+    pub hint: Style,
+    /// 条目操作后的提示
+    pub status: Style,
+    /// 删除确认
+    pub confirm: Style,
+    /// 次要文字（预览加载中等）
+    pub dim: Style,
+    /// 列表 / 预览面板的边框
+    pub panel: Borders,
+    /// 帮助页 / 浮层输入框的边框
+    pub popup: Borders,
+    /// 搜索输入的光标
+    pub caret: &'static str,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::palette("default").expect("内置调色板")
+    }
+}
+
+/// 配置 `[theme.colors]` 中可用的样式名
+const SLOTS: [&str; 8] = [
+    "border", "title", "selected", "prompt", "hint", "status", "confirm", "dim",
+];
+
+impl Theme {
+    /// 内置调色板：default / mono / nord / gruvbox
+    pub fn palette(name: &str) -> Option<Self> {
+        let rgb = |hex: u32| Color::Rgb {
+            r: (hex >> 16) as u8,
+            g: (hex >> 8) as u8,
+            b: hex as u8,
+        };
+        let plain = Style::default();
+        let bold = Style::default().bold();
+        let mut t = Self {
+            name: name.to_string(),
+            border: plain,
+            title: Style::fg(Color::Cyan).bold(),
+            selected: Style::fg(Color::White).on(Color::DarkBlue).bold(),
+            prompt: Style::fg(Color::Yellow).bold(),
+            hint: Style::fg(Color::DarkGrey).bold(),
+            status: Style::fg(Color::Green).bold(),
+            confirm: Style::fg(Color::Red).bold(),
+            dim: Style::fg(Color::DarkGrey),
+            panel: ROUNDED,
+            popup: SHARP,
+            caret: "▌",
+        };
+        match name {
+            "default" => {}
+            "mono" => {
+                t.title = bold;
+                t.selected = Style {
+                    reverse: true,
+                    ..bold
+                };
+                t.prompt = bold;
+                t.hint = plain;
+                t.status = bold;
+                t.confirm = bold;
+                t.dim = Style { dim: true, ..plain };
+            }
+            "nord" => {
+                t.border = Style::fg(rgb(0x4c566a));
+                t.title = Style::fg(rgb(0x88c0d0)).bold();
+                t.selected = Style::fg(rgb(0xeceff4)).on(rgb(0x434c5e)).bold();
+                t.prompt = Style::fg(rgb(0xebcb8b)).bold();
+                t.hint = Style::fg(rgb(0x616e88));
+                t.status = Style::fg(rgb(0xa3be8c)).bold();
+                t.confirm = Style::fg(rgb(0xbf616a)).bold();
+                t.dim = Style::fg(rgb(0x616e88));
+            }
+            "gruvbox" => {
+                t.border = Style::fg(rgb(0x665c54));
+                t.title = Style::fg(rgb(0x83a598)).bold();
+                t.selected = Style::fg(rgb(0xfbf1c7)).on(rgb(0x504945)).bold();
+                t.prompt = Style::fg(rgb(0xfabd2f)).bold();
+                t.hint = Style::fg(rgb(0x928374));
+                t.status = Style::fg(rgb(0xb8bb26)).bold();
+                t.confirm = Style::fg(rgb(0xfb4934)).bold();
+                t.dim = Style::fg(rgb(0x928374));
+            }
+            _ => return None,
+        }
+        Some(t)
+    }
+
+    /// 调色板（`CDH_THEME` 优先于配置 `[theme] name`）+ 配置覆盖 + 终端能力降级
+    ///
+    /// 无法识别的调色板 / 样式只打印 warning 并跳过。
+    pub fn from_config(file: &ConfigFile) -> Self {
+        let env = |k: &str| std::env::var(k).ok().filter(|s| !s.is_empty());
+        let name = env("CDH_THEME")
+            .or_else(|| file.get_str("theme", "name").map(str::to_string))
+            .unwrap_or_else(|| "default".to_string());
+        let mut t = Self::palette(&name.to_lowercase()).unwrap_or_else(|| {
+            eprintln!("cdh: 未知的主题 {name}（可选 default / mono / nord / gruvbox）");
+            Self::default()
+        });
+
+        match file.get_str("theme", "borders") {
+            None => {}
+            Some("rounded") => t.panel = ROUNDED,
+            Some("sharp") => t.panel = SHARP,
+            Some("ascii") => t.set_ascii(),
+            Some(other) => {
+                eprintln!("cdh: [theme] borders 无效: {other}（rounded / sharp / ascii）")
+            }
+        }
+        if file.get_bool("theme", "ascii") == Some(true) {
+            t.set_ascii();
+        }
+
+        for (slot, value) in file.section("theme.colors") {
+            let Some(spec) = value.as_str() else {
+                eprintln!("cdh: [theme.colors] {slot} 需要字符串");
+                continue;
+            };
+            match (t.slot_mut(slot), Style::parse(spec)) {
+                (Some(dst), Ok(st)) => *dst = st,
+                (None, _) => eprintln!(
+                    "cdh: [theme.colors] 未知的样式 {slot}（可选 {}）",
+                    SLOTS.join(" / ")
+                ),
+                (_, Err(e)) => eprintln!("cdh: [theme.colors] {slot}: {e}"),
+            }
+        }
+
+        let no_color = env("NO_COLOR").is_some()
+            || env("CDH_COLOR").is_some_and(|v| !(v == "1" || v.eq_ignore_ascii_case("true")))
+            || file.get_bool("theme", "color") == Some(false);
+        let truecolor = file
+            .get_bool("theme", "truecolor")
+            .unwrap_or_else(|| env("COLORTERM").is_some_and(|v| v == "truecolor" || v == "24bit"));
+        if no_color {
+            t.map_styles(Style::without_color);
+        } else if !truecolor {
+            t.map_styles(Style::downgrade);
+        }
+        t
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 是否只用 ASCII 字形（字体缺少制表符 / 方块字符时）
+    pub fn is_ascii(&self) -> bool {
+        self.panel == ASCII
+    }
+
+    /// 全部换成 ASCII 字形（边框 + 光标）
+    fn set_ascii(&mut self) {
+        self.panel = ASCII;
+        self.popup = ASCII;
+        self.caret = "_";
+    }
+
+    fn slot_mut(&mut self, slot: &str) -> Option<&mut Style> {
+        Some(match slot {
+            "border" => &mut self.border,
+            "title" => &mut self.title,
+            "selected" => &mut self.selected,
+            "prompt" => &mut self.prompt,
+            "hint" => &mut self.hint,
+            "status" => &mut self.status,
+            "confirm" => &mut self.confirm,
+            "dim" => &mut self.dim,
+            _ => return None,
+        })
+    }
+
+    fn map_styles(&mut self, f: impl Fn(Style) -> Style) {
+        for slot in SLOTS {
+            if let Some(st) = self.slot_mut(slot) {
+                *st = f(*st);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_styles_and_colors() {
+        let st = Style::parse("#eceff4 on 24 bold").unwrap();
+        assert_eq!(
+            st.fg,
+            Some(Color::Rgb {
+                r: 0xec,
+                g: 0xef,
+                b: 0xf4
+            })
+        );
+        assert_eq!(st.bg, Some(Color::AnsiValue(24)));
+        assert!(st.bold && !st.reverse);
+        assert_eq!(Style::parse("dark_grey").unwrap().fg, Some(Color::DarkGrey));
+        assert!(Style::parse("on").is_err());
+        assert!(Style::parse("#12345").is_err());
+        assert!(Style::parse("chartreuse").is_err());
+    }
+
+    #[test]
+    fn truecolor_downgrades_to_256() {
+        let c = |r, g, b| to_ansi256(Color::Rgb { r, g, b });
+        assert_eq!(c(0, 0, 0), Color::AnsiValue(16));
+        assert_eq!(c(255, 255, 255), Color::AnsiValue(231));
+        assert_eq!(c(255, 0, 0), Color::AnsiValue(196));
+        // 接近中灰时用灰阶
+        assert_eq!(c(128, 128, 128), Color::AnsiValue(244));
+        assert_eq!(to_ansi256(Color::Cyan), Color::Cyan);
+    }
+
+    #[test]
+    fn no_color_keeps_selection_visible() {
+        let t = Theme::palette("nord").unwrap();
+        let sel = t.selected.without_color();
+        assert_eq!((sel.fg, sel.bg), (None, None));
+        assert!(sel.reverse && sel.bold);
+    }
+
+    #[test]
+    fn config_overrides_palette_and_borders() {
+        let file = ConfigFile::parse(
+            "[theme]\nname = \"gruvbox\"\nborders = \"ascii\"\ntruecolor = true\n\n[theme.colors]\ntitle = \"red underline\"\n",
+        )
+        .unwrap();
+        let t = Theme::from_config(&file);
+        assert_eq!(t.name(), "gruvbox");
+        assert_eq!(t.panel, ASCII);
+        assert_eq!(t.caret, "_");
+        assert_eq!(t.title.fg, Some(Color::Red));
+        assert!(t.title.underline);
+        assert!(matches!(t.prompt.fg, Some(Color::Rgb { .. })));
+    }
+}
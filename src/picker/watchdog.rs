@@ -0,0 +1,127 @@
+//! 无操作超时：打开 picker 后一直没有按键（或鼠标 / 粘贴）就自动退出
+//!
+//! 防止 shell 卡在一个被遗忘的 picker 上；切走窗口再回来的用户可以调长或关掉：
+//!
+//! ```toml
+//! [picker]
+//! timeout = 15           # 秒；0 或 "off" 关闭
+//! ```
+//!
+//! 环境变量 `CDH_TIMEOUT` 优先于配置文件，`--timeout` 又优先于环境变量。
+//! 超时前最后几秒在底栏显示倒计时；一旦有过按键就不再计时。
+
+use crate::config::{ConfigFile, ConfigValue};
+use std::time::Duration;
+
+/// 默认超时
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
+/// 超时前多久开始在底栏倒计时
+const COUNTDOWN: Duration = Duration::from_secs(5);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Watchdog {
+    /// None 表示不超时
+    pub timeout: Option<Duration>,
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Self {
+            timeout: Some(DEFAULT_TIMEOUT),
+        }
+    }
+}
+
+impl Watchdog {
+    /// 不超时
+    pub fn off() -> Self {
+        Self { timeout: None }
+    }
+
+    /// 秒数（可带小数）；`0` / `off` / `never` 表示关闭
+    pub fn parse(s: &str) -> Option<Watchdog> {
+        let s = s.trim();
+        if matches!(s, "off" | "never") {
+            return Some(Self::off());
+        }
+        let secs = s
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite() && *n >= 0.0)?;
+        Some(Self {
+            timeout: (secs > 0.0).then(|| Duration::from_secs_f64(secs)),
+        })
+    }
+
+    /// 配置文件 `[picker] timeout` + `CDH_TIMEOUT`；无法识别的值打印 warning 后用默认值
+    pub fn from_config(file: &ConfigFile) -> Self {
+        let value = std::env::var("CDH_TIMEOUT")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .or_else(|| {
+                file.get("picker", "timeout").and_then(|v| match v {
+                    ConfigValue::Num(n) => Some(n.to_string()),
+                    ConfigValue::Bool(false) => Some("off".to_string()),
+                    other => other.as_str().map(str::to_string),
+                })
+            });
+        match value {
+            Some(v) => Self::parse(&v).unwrap_or_else(|| {
+                eprintln!("cdh: picker timeout 无效: {v}（秒数，0 / off 关闭）");
+                Self::default()
+            }),
+            None => Self::default(),
+        }
+    }
+
+    /// 空闲了 idle 之后是否该退出
+    pub(crate) fn expired(&self, idle: Duration) -> bool {
+        self.timeout.is_some_and(|t| idle >= t)
+    }
+
+    /// 倒计时：进入最后几秒时返回剩余秒数（向上取整），否则 None
+    pub(crate) fn countdown(&self, idle: Duration) -> Option<u64> {
+        let left = self.timeout?.checked_sub(idle)?;
+        if left > COUNTDOWN || left.is_zero() {
+            return None;
+        }
+        Some(((left.as_millis() + 999) / 1000) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_seconds_and_off() {
+        let secs = |s| Watchdog::parse(s).map(|w| w.timeout);
+        assert_eq!(secs("30"), Some(Some(Duration::from_secs(30))));
+        assert_eq!(secs("1.5"), Some(Some(Duration::from_millis(1500))));
+        assert_eq!(secs("0"), Some(None));
+        assert_eq!(secs("off"), Some(None));
+        assert_eq!(secs("-1"), None);
+        assert_eq!(secs("soon"), None);
+
+        let file = ConfigFile::parse("[picker]\ntimeout = 60\n").unwrap();
+        assert_eq!(
+            Watchdog::from_config(&file).timeout,
+            Some(Duration::from_secs(60))
+        );
+        let file = ConfigFile::parse("[picker]\ntimeout = false\n").unwrap();
+        assert_eq!(Watchdog::from_config(&file), Watchdog::off());
+    }
+
+    #[test]
+    fn countdown_only_in_last_seconds() {
+        let w = Watchdog::default();
+        assert_eq!(w.countdown(Duration::from_secs(3)), None);
+        assert_eq!(w.countdown(Duration::from_secs(10)), Some(5));
+        assert_eq!(w.countdown(Duration::from_millis(13_200)), Some(2));
+        assert_eq!(w.countdown(Duration::from_secs(15)), None);
+        assert!(!w.expired(Duration::from_millis(14_999)));
+        assert!(w.expired(Duration::from_secs(15)));
+        assert_eq!(Watchdog::off().countdown(Duration::from_secs(99)), None);
+        assert!(!Watchdog::off().expired(Duration::from_secs(99)));
+    }
+}
@@ -0,0 +1,151 @@
+// src/pins.rs
+//! 置顶 / 降级列表：用户在 picker 里手动调整的排序偏好。
+//!
+//! 文件（一行一个绝对路径，按加入顺序）：
+//!   DATA/pins      置顶：排在所有推荐结果之前
+//!   DATA/demoted   降级：排在所有推荐结果之后
+//!
+//! 同一路径最多出现在其中一个列表里；写入复用历史锁，“临时文件 + rename”原子替换。
+
+use crate::history::{with_history_lock, write_lines_atomic};
+use crate::AppContext;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// 置顶列表路径：DATA/pins
+pub fn pins_path(ctx: &AppContext) -> PathBuf {
+    ctx.paths.data_dir.join("pins")
+}
+
+/// 降级列表路径：DATA/demoted
+pub fn demoted_path(ctx: &AppContext) -> PathBuf {
+    ctx.paths.data_dir.join("demoted")
+}
+
+/// 读取置顶列表（不存在视为空）
+pub fn load_pins(ctx: &AppContext) -> Vec<String> {
+    read_list(&pins_path(ctx))
+}
+
+/// 读取降级列表（不存在视为空）
+pub fn load_demoted(ctx: &AppContext) -> Vec<String> {
+    read_list(&demoted_path(ctx))
+}
+
+/// 置顶（同时取消降级）
+pub fn pin(ctx: &AppContext, dir: &str) -> io::Result<()> {
+    update(ctx, |pins, demoted| {
+        demoted.retain(|p| p != dir);
+        if !pins.iter().any(|p| p == dir) {
+            pins.push(dir.to_string());
+        }
+    })
+}
+
+/// 取消置顶
+pub fn unpin(ctx: &AppContext, dir: &str) -> io::Result<()> {
+    update(ctx, |pins, _| pins.retain(|p| p != dir))
+}
+
+/// 降级（同时取消置顶）
+pub fn demote(ctx: &AppContext, dir: &str) -> io::Result<()> {
+    update(ctx, |pins, demoted| {
+        pins.retain(|p| p != dir);
+        if !demoted.iter().any(|p| p == dir) {
+            demoted.push(dir.to_string());
+        }
+    })
+}
+
+/// 取消降级
+pub fn undemote(ctx: &AppContext, dir: &str) -> io::Result<()> {
+    update(ctx, |_, demoted| demoted.retain(|p| p != dir))
+}
+
+/// 从两个列表里都移除（路径被删出历史时调用）
+pub fn forget(ctx: &AppContext, dir: &str) -> io::Result<()> {
+    update(ctx, |pins, demoted| {
+        pins.retain(|p| p != dir);
+        demoted.retain(|p| p != dir);
+    })
+}
+
+/// 按置顶 / 降级列表调整推荐顺序：
+/// - 结果里的置顶路径按置顶顺序移到最前；
+/// - 结果里的降级路径保持相对顺序移到最后；
+/// - 不在结果里的置顶/降级路径不会被补进来。
+///
+/// 条目可以是路径本身，也可以是带元数据的条目（按 `AsRef<str>` 取路径）。
+pub fn apply<T: AsRef<str>>(items: Vec<T>, pins: &[String], demoted: &[String]) -> Vec<T> {
+    let pinned: HashSet<&str> = pins.iter().map(String::as_str).collect();
+    let sunk: HashSet<&str> = demoted.iter().map(String::as_str).collect();
+
+    let mut head: HashMap<String, T> = HashMap::new();
+    let mut out = Vec::with_capacity(items.len());
+    let mut tail = Vec::new();
+    for it in items {
+        let p = it.as_ref();
+        if pinned.contains(p) {
+            head.insert(p.to_string(), it);
+        } else if sunk.contains(p) {
+            tail.push(it);
+        } else {
+            out.push(it);
+        }
+    }
+    let mut res: Vec<T> = pins.iter().filter_map(|p| head.remove(p)).collect();
+    res.extend(out);
+    res.extend(tail);
+    res
+}
+
+fn read_list(path: &std::path::Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+/// 在历史锁内读出两个列表、修改、写回
+fn update(ctx: &AppContext, f: impl FnOnce(&mut Vec<String>, &mut Vec<String>)) -> io::Result<()> {
+    with_history_lock(ctx, || {
+        let mut pins = load_pins(ctx);
+        let mut demoted = load_demoted(ctx);
+        f(&mut pins, &mut demoted);
+        write_lines_atomic(&pins_path(ctx), &pins)?;
+        write_lines_atomic(&demoted_path(ctx), &demoted)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pin_and_demote_are_mutually_exclusive() {
+        let (root, ctx) = AppContext::for_test("exclusive");
+        pin(&ctx, "/a").unwrap();
+        pin(&ctx, "/b").unwrap();
+        demote(&ctx, "/a").unwrap();
+        assert_eq!(load_pins(&ctx), vec!["/b"]);
+        assert_eq!(load_demoted(&ctx), vec!["/a"]);
+
+        forget(&ctx, "/a").unwrap();
+        unpin(&ctx, "/b").unwrap();
+        assert!(load_pins(&ctx).is_empty());
+        assert!(load_demoted(&ctx).is_empty());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn apply_moves_pins_first_and_demoted_last() {
+        let paths = ["/a", "/b", "/c", "/d"].map(String::from).to_vec();
+        let pins = ["/c", "/zz"].map(String::from).to_vec();
+        let demoted = ["/a"].map(String::from).to_vec();
+        assert_eq!(apply(paths, &pins, &demoted), vec!["/c", "/b", "/d", "/a"]);
+    }
+}
@@ -0,0 +1,155 @@
+// src/proximity.rs
+//! 位置信号：候选目录离当前工作目录越“近”，分越高（0~1）。
+//!
+//! 规则（取其中最高的一项）：
+//!   - 当前目录的子目录：直接子目录 1.0，更深的后代 0.8；
+//!   - 同一父目录下的兄弟目录：0.8；
+//!   - 与当前目录处在同一个 git 仓库（向上找到的第一个含 `.git` 的目录）里：0.6；
+//!   - 其余按公共前缀的段数占比给分，最多 0.5（只共享 `/home/<user>` 这类短前缀时很低）。
+//!
+//! 当前目录本身记 0：跳回原地没有意义。
+//!
+//! 历史里的路径都经过 canonicalize（解析软链接），cwd 也要按同样的规则规范化再比较 ——
+//! shell 的 `$PWD` 保留软链接，不处理的话在软链接目录下永远匹配不上。
+//!
+//! 由 `recommend` 按 `w_proximity` 融合进最终分 —— 在 monorepo 里跳转时，附近的目录会排在全局常用目录前面。
+
+use crate::history::normalize_history_path;
+use crate::scorer::{Normalize, ScoreContext, Scorer};
+use std::path::{Path, PathBuf};
+
+/// 以某个当前目录为参照的位置打分器
+#[derive(Debug, Clone)]
+pub struct Proximity {
+    cwd: PathBuf,
+    /// cwd 所在 git 仓库的根目录（不在仓库里为 None）
+    repo_root: Option<PathBuf>,
+}
+
+impl Proximity {
+    /// 以 cwd 为参照（按写历史时的规则规范化）；会向上查找 git 仓库根目录
+    pub fn new(cwd: &str) -> Self {
+        let cwd = PathBuf::from(normalize_history_path(cwd).unwrap_or_else(|_| cwd.to_string()));
+        let repo_root = find_repo_root(&cwd);
+        Self { cwd, repo_root }
+    }
+
+    /// 候选路径的位置分（0~1）
+    pub fn closeness(&self, path: &str) -> f64 {
+        let p = Path::new(path);
+        if p == self.cwd {
+            return 0.0;
+        }
+        if let Ok(rest) = p.strip_prefix(&self.cwd) {
+            return if rest.components().count() == 1 {
+                1.0
+            } else {
+                0.8
+            };
+        }
+        if p.parent().is_some() && p.parent() == self.cwd.parent() {
+            return 0.8;
+        }
+        if self.repo_root.as_ref().is_some_and(|r| p.starts_with(r)) {
+            return 0.6;
+        }
+        let common = p
+            .components()
+            .zip(self.cwd.components())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let longest = p.components().count().max(self.cwd.components().count());
+        // 根目录 `/` 也算一段：只有它相同时不给分
+        if common <= 1 || longest == 0 {
+            return 0.0;
+        }
+        0.5 * (common - 1) as f64 / (longest - 1) as f64
+    }
+}
+
+impl Scorer for Proximity {
+    fn name(&self) -> &str {
+        "proximity"
+    }
+
+    fn score(&self, path: &str, _ctx: &ScoreContext) -> Option<f64> {
+        Some(self.closeness(path))
+    }
+
+    fn normalize(&self) -> Normalize {
+        Normalize::None
+    }
+}
+
+/// 从 dir 向上找第一个含 `.git`（目录或文件，后者为 worktree / submodule）的目录
+fn find_repo_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|d| d.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn at(cwd: &str) -> Proximity {
+        Proximity {
+            cwd: PathBuf::from(cwd),
+            repo_root: None,
+        }
+    }
+
+    #[test]
+    fn children_and_siblings_rank_highest() {
+        let p = at("/w/mono/services/api");
+        assert_eq!(p.closeness("/w/mono/services/api"), 0.0);
+        assert_eq!(p.closeness("/w/mono/services/api/src"), 1.0);
+        assert_eq!(p.closeness("/w/mono/services/api/src/db"), 0.8);
+        assert_eq!(p.closeness("/w/mono/services/web"), 0.8);
+        // 公共前缀 /w/mono：3 段里的 2 段（不算根）
+        assert!((p.closeness("/w/mono/docs") - 0.5 * 2.0 / 4.0).abs() < 1e-9);
+        assert_eq!(p.closeness("/etc"), 0.0);
+        assert!(p.closeness("/w/other") < p.closeness("/w/mono/docs"));
+    }
+
+    #[test]
+    fn same_repository_counts_as_near() {
+        let root = std::env::temp_dir().join(format!("cdh_proximity_{}", std::process::id()));
+        let deep = root.join("repo/pkg/a/b");
+        fs::create_dir_all(&deep).unwrap();
+        fs::create_dir_all(root.join("repo/.git")).unwrap();
+        // 临时目录本身可能在软链接下（macOS 的 /var）
+        let root = fs::canonicalize(&root).unwrap();
+        let deep = root.join("repo/pkg/a/b");
+
+        let p = Proximity::new(&deep.to_string_lossy());
+        assert_eq!(p.repo_root.as_deref(), Some(root.join("repo").as_path()));
+        let other = root.join("repo/tools/x");
+        assert_eq!(p.closeness(&other.to_string_lossy()), 0.6);
+        let outside = root.join("elsewhere");
+        assert!(p.closeness(&outside.to_string_lossy()) < 0.5);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_cwd_matches_canonical_history_paths() {
+        let root = std::env::temp_dir().join(format!("cdh_proximity_link_{}", std::process::id()));
+        let real = root.join("real/mono");
+        fs::create_dir_all(real.join("api")).unwrap();
+        std::os::unix::fs::symlink(root.join("real"), root.join("link")).unwrap();
+        let real = fs::canonicalize(&real).unwrap();
+
+        // $PWD 是软链接路径，历史里记的是解析后的真实路径
+        let p = Proximity::new(&root.join("link/mono").to_string_lossy());
+        assert_eq!(p.cwd, real);
+        assert_eq!(p.closeness(&real.to_string_lossy()), 0.0);
+        assert_eq!(p.closeness(&real.join("api").to_string_lossy()), 1.0);
+
+        // 不存在的目录退化为词法规范化
+        let p = Proximity::new("/px/mono/../mono/./api");
+        assert_eq!(p.cwd, PathBuf::from("/px/mono/api"));
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,147 @@
+// src/queries.rs
+//! 搜索历史：picker 搜索模式下选中条目时用的查询。
+//!
+//! 文件：STATE/queries，一行一条 `<ts_secs>\t<查询>\t<选中的路径>`（多选时每个路径一行），
+//! 只保留最近 `MAX_LINES` 行。写入复用历史锁，“临时文件 + rename”原子替换。
+//!
+//! 读出后交给 picker：
+//! - 最近的查询（去重）用于输入行为空时 ↑/↓ / Ctrl-R 召回；
+//! - “查询 → 路径”的次数用于输入时优先高亮以前选过的路径。
+
+use crate::history::{now_secs, with_history_lock, write_lines_atomic};
+use crate::picker::{QueryHistory, QueryPick};
+use crate::AppContext;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// 文件最多保留的行数
+const MAX_LINES: usize = 500;
+/// 召回时最多提供的查询条数
+const MAX_RECENT: usize = 50;
+
+/// 搜索历史路径：STATE/queries
+pub fn queries_path(ctx: &AppContext) -> PathBuf {
+    ctx.paths.state_dir.join("queries")
+}
+
+/// 读取搜索历史（不存在视为空；格式不对的行跳过）
+pub fn load(ctx: &AppContext) -> QueryHistory {
+    let text = fs::read_to_string(queries_path(ctx)).unwrap_or_default();
+    // (查询, 路径)，文件里旧的在前
+    let records: Vec<(&str, &str)> = text
+        .lines()
+        .filter_map(|l| {
+            let mut it = l.splitn(3, '\t');
+            let _ts = it.next()?.parse::<i64>().ok()?;
+            let query = it.next().filter(|q| !q.is_empty())?;
+            let path = it.next().filter(|p| !p.is_empty())?;
+            Some((query, path))
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    let recent = records
+        .iter()
+        .rev()
+        .filter(|(q, _)| seen.insert(*q))
+        .take(MAX_RECENT)
+        .map(|(q, _)| q.to_string())
+        .collect();
+
+    // (次数, 最近出现的行号)
+    let mut counts: HashMap<(&str, &str), (u32, usize)> = HashMap::new();
+    for (i, rec) in records.iter().enumerate() {
+        let e = counts.entry(*rec).or_insert((0, i));
+        e.0 += 1;
+        e.1 = i;
+    }
+    let mut picks: Vec<_> = counts.into_iter().collect();
+    picks.sort_by_key(|&(_, stat)| Reverse(stat));
+    let picks = picks
+        .into_iter()
+        .map(|((query, path), (count, _))| QueryPick {
+            query: query.to_string(),
+            path: path.to_string(),
+            count,
+        })
+        .collect();
+
+    QueryHistory { recent, picks }
+}
+
+/// 记录一次“用 query 选中了 selected”（当前时间）
+pub fn record(ctx: &AppContext, query: &str, selected: &[String]) -> io::Result<()> {
+    record_at(ctx, query, selected, now_secs())
+}
+
+/// 同 `record`，时间戳由调用方给出
+pub fn record_at(ctx: &AppContext, query: &str, selected: &[String], ts: i64) -> io::Result<()> {
+    let query = query.trim();
+    if query.is_empty() || query.contains(['\t', '\n']) {
+        return Ok(());
+    }
+    let path = queries_path(ctx);
+    with_history_lock(ctx, || {
+        let mut lines: Vec<String> = fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        lines.extend(
+            selected
+                .iter()
+                .filter(|p| !p.contains(['\t', '\n']))
+                .map(|p| format!("{ts}\t{query}\t{p}")),
+        );
+        let excess = lines.len().saturating_sub(MAX_LINES);
+        lines.drain(..excess);
+        write_lines_atomic(&path, &lines)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent_queries_and_pick_counts() {
+        let (root, ctx) = AppContext::for_test("load");
+        let one = |p: &str| vec![p.to_string()];
+        record_at(&ctx, "proj", &one("/w/proj"), 1).unwrap();
+        record_at(&ctx, " cfg ", &one("/etc"), 2).unwrap();
+        record_at(&ctx, "proj", &one("/w/proj"), 3).unwrap();
+        record_at(&ctx, "proj", &one("/w/proj2"), 4).unwrap();
+        record_at(&ctx, "", &one("/ignored"), 5).unwrap();
+
+        let h = load(&ctx);
+        assert_eq!(h.recent, vec!["proj", "cfg"]);
+        let picks: Vec<(&str, &str, u32)> = h
+            .picks
+            .iter()
+            .map(|p| (p.query.as_str(), p.path.as_str(), p.count))
+            .collect();
+        assert_eq!(
+            picks,
+            vec![
+                ("proj", "/w/proj", 2),
+                ("proj", "/w/proj2", 1),
+                ("cfg", "/etc", 1)
+            ]
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn file_is_capped() {
+        let (root, ctx) = AppContext::for_test("cap");
+        let many: Vec<String> = (0..MAX_LINES + 10).map(|i| format!("/d{i}")).collect();
+        record_at(&ctx, "d", &many, 1).unwrap();
+        let text = fs::read_to_string(queries_path(&ctx)).unwrap();
+        assert_eq!(text.lines().count(), MAX_LINES);
+        assert!(text.starts_with("1\td\t/d10\n"));
+        let _ = fs::remove_dir_all(root);
+    }
+}
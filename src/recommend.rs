@@ -3,18 +3,31 @@
 //!
 //! 设计要点：
 //! - 以 raw 的 Frecency 分数为主、uniq 的“最近唯一”几何衰减分为辅，线性融合（可调权重）。
+//! - 选择反馈（见 src/feedback.rs）作为第三个信号：常被从靠后位置选中的路径额外加分。
+//! - 位置信号（见 src/proximity.rs）：给定当前目录时，子目录 / 兄弟目录 / 同一 git 仓库里的路径额外加分。
+//! - 时段信号（见 src/temporal.rs，可选）：常在“现在这个星期几 / 几点”访问的路径额外加分。
 //! - 流式读取 raw，低内存；一次性 lower tokens；可选校验目录存在性（WSL/网络盘可关）。
-//! - 归一化到 [0,1] 再融合；支持阈值、关键词/正则过滤；对连续相同 (ts,path) 去重防抖。
+//! - 每个信号都是一个 `Scorer`（见 src/scorer.rs），各自归一化到 [0,1] 再加权融合；支持阈值、关键词/正则过滤；对连续相同 (ts,path) 去重防抖。
+//! - 置顶路径（`pins`）排在所有打分条目之前，不受阈值影响，但仍要通过过滤。
 //!
 //! 对外接口：
 //! - `RecommendOpt`：融合推荐所有配置
-//! - `Recommendation{ path, score }`：推荐结果
+//! - `Recommendation{ path, score, visits, last_visit }`：推荐结果（后两项供 picker 显示）
 //! - `recommend(&RecommendOpt) -> Vec<Recommendation>`：路径+融合分
 //! - `recommend_paths(&RecommendOpt) -> Vec<String>`：仅路径
 //! - `recommend_with_now(&RecommendOpt, now_secs)`：可注入“当前时间”的变体（便于测试）
+//! - `recommend_with_scorers(&RecommendOpt, extra, now)`：在内置信号之外再融合自定义 `Scorer`
+//! - `recommend_from_index(&RecommendOpt, &FrecencyIndex, uniq, now)`：内存索引变体（供 daemon 使用）
 //!
 //! 依赖：本 crate 需已提供 `Frecency` / `FrecencyIndex`（见 src/frecency.rs）。
-use crate::frecency::{Frecency, FrecencyIndex};
+use crate::feedback::FeedbackScorer;
+use crate::frecency::{
+    Frecency, FrecencyIndex, FrecencyKind, DEFAULT_LONG_HALF_LIFE, DEFAULT_WINDOW,
+};
+use crate::history::{parse_raw_line, RawLine};
+use crate::proximity::Proximity;
+use crate::scorer::{FrecencyScorer, Normalize, ScoreContext, Scorer, UniqScorer};
+use crate::temporal::TemporalScorer;
 use regex::Regex;
 use std::{
     collections::{HashMap, HashSet},
@@ -36,7 +49,11 @@ fn now_secs() -> i64 {
 #[derive(Debug, Clone)]
 pub struct Recommendation {
     pub path: String,
-    pub score: f64, // 融合后的最终分（0~1）
+    pub score: f64, // 融合后的最终分：各信号 0~1 按权重相加，打开额外信号后可能超过 1
+    /// raw 日志中的访问次数（只出现在 uniq 里时为 0）
+    pub visits: u64,
+    /// 最近一次访问的时间戳（秒）；raw 里没有记录时为 None
+    pub last_visit: Option<i64>,
 }
 
 /// 融合推荐的配置
@@ -46,10 +63,18 @@ pub struct RecommendOpt {
     pub raw: String,
     /// 最近唯一列表：一行一个 path（由 controller 注入 XDG history_uniq 路径）
     pub uniq: String,
+    /// picker 选择记录（由 controller 注入 DATA/selections；为空则不启用反馈信号）
+    pub feedback: String,
     /// 返回最大条数（默认 20）
     pub limit: usize,
     /// Frecency 半衰期（秒），默认 7 天
     pub half_life: f64,
+    /// Frecency 模型（默认 decay，见 src/frecency.rs）
+    pub frecency_model: FrecencyKind,
+    /// dual 模型的长期半衰期（秒），默认 90 天
+    pub long_half_life: f64,
+    /// window 模型的窗口长度（秒），默认 30 天
+    pub frecency_window: f64,
     /// 最终融合分阈值（< threshold 的条目会被丢弃；0 表示不启用）
     pub threshold: f64,
     /// 忽略路径的正则（默认读取 `CDH_IGNORE_RE`）
@@ -63,6 +88,20 @@ pub struct RecommendOpt {
     /// 融合权重：frecency 与 uniq（建议和为 1.0；默认 0.7 / 0.3）
     pub w_frecency: f64,
     pub w_uniq: f64,
+    /// frecency 与 uniq 的归一化方式（默认 min-max；有个别访问量极大的目录时可用 rank / log）
+    pub normalize: Normalize,
+    /// 选择反馈的权重（在上面两项之外额外加分，默认 0 不启用）
+    pub w_feedback: f64,
+    /// 置顶路径（由 controller 注入 DATA/pins，按置顶顺序）：排在所有打分条目之前
+    pub pins: Vec<String>,
+    /// 当前工作目录（由 controller 注入 `$PWD`；None 则不启用位置信号）
+    pub cwd: Option<String>,
+    /// 位置信号的权重（同样是额外加分，默认 0 不启用）
+    pub w_proximity: f64,
+    /// 时段信号的权重（额外加分，默认 0 不启用）
+    pub w_temporal: f64,
+    /// 本地时区相对 UTC 的秒数（时段信号用它换算星期 / 小时；由 controller 注入）
+    pub utc_offset: i64,
 }
 
 impl Default for RecommendOpt {
@@ -72,19 +111,40 @@ impl Default for RecommendOpt {
         Self {
             raw: String::new(),             // 稍后由 controller 用 ctx.paths 覆盖
             uniq: String::new(),            // 同上
+            feedback: String::new(),        // 同上
             limit: 20,                      // 默认 20；可被 config/CLI 覆盖
             half_life: 7.0 * 24.0 * 3600.0, // 默认 7 天；可被 config/CLI 覆盖
-            threshold: 0.0,                 // 默认不开启阈值
-            ignore_re: None,                // 默认不忽略任何路径；可由 config/CLI 覆盖
+            frecency_model: FrecencyKind::Decay,
+            long_half_life: DEFAULT_LONG_HALF_LIFE,
+            frecency_window: DEFAULT_WINDOW,
+            threshold: 0.0,  // 默认不开启阈值
+            ignore_re: None, // 默认不忽略任何路径；可由 config/CLI 覆盖
             tokens: Vec::new(),
             check_dir: true,  // 默认检查目录存在性；可被 config 覆盖
             uniq_decay: 0.85, // 默认几何衰减
             w_frecency: 0.7,  // 默认权重
             w_uniq: 0.3,
+            normalize: Normalize::MinMax,
+            w_feedback: 0.0,
+            pins: Vec::new(),
+            cwd: None,
+            w_proximity: 0.0,
+            w_temporal: 0.0,
+            utc_offset: 0,
         }
     }
 }
 
+impl RecommendOpt {
+    /// 按配置构造 Frecency 模型（半衰期 / 长期半衰期 / 窗口须 > 0）
+    pub fn frecency(&self) -> Frecency {
+        Frecency::new(self.half_life)
+            .with_kind(self.frecency_model)
+            .with_long_half_life(self.long_half_life)
+            .with_window(self.frecency_window)
+    }
+}
+
 /// 外部主接口：融合 RAW+UNIQ，返回路径+分数（按最终分降序）
 pub fn recommend(opt: &RecommendOpt) -> Vec<Recommendation> {
     recommend_with_now(opt, now_secs())
@@ -92,8 +152,17 @@ pub fn recommend(opt: &RecommendOpt) -> Vec<Recommendation> {
 
 /// 变体：可注入“当前时间”，便于测试
 pub fn recommend_with_now(opt: &RecommendOpt, now: i64) -> Vec<Recommendation> {
+    recommend_with_scorers(opt, &[], now)
+}
+
+/// 变体：在内置信号之外，再按给定权重融合自定义信号（归一化方式由各 `Scorer` 决定）
+pub fn recommend_with_scorers(
+    opt: &RecommendOpt,
+    extra: &[(f64, &dyn Scorer)],
+    now: i64,
+) -> Vec<Recommendation> {
     // 预处理 tokens（一次性 lower）
-    let tokens_lc: Vec<String> = opt.tokens.iter().map(|t| t.to_lowercase()).collect();
+    let tokens_lc = lower_tokens(&opt.tokens);
 
     // 1) uniq -> 生成 “最近唯一”几何衰减分
     let uniq_scores = load_uniq_scores(
@@ -110,36 +179,141 @@ pub fn recommend_with_now(opt: &RecommendOpt, now: i64) -> Vec<Recommendation> {
         &opt.ignore_re,
         &tokens_lc,
         opt.check_dir,
-        opt.half_life,
+        opt.frecency(),
+        opt.w_temporal > 0.0,
     );
 
-    // 3) 候选集 = raw ∪ uniq
-    let mut candidates: HashSet<String> = seen_raw;
-    candidates.extend(uniq_scores.keys().cloned());
+    fuse(opt, &idx, seen_raw, uniq_scores, extra, now)
+}
+
+/// 变体：直接使用内存中的 Frecency 索引 + uniq 列表（旧->新），不读文件。
+///
+/// 供常驻进程（`cdh daemon`）复用：`idx` 应按 `opt.frecency()` 构建且未经过滤，
+/// ignore_re / tokens / check_dir 过滤在这里按 `opt` 完成。
+pub fn recommend_from_index(
+    opt: &RecommendOpt,
+    idx: &FrecencyIndex,
+    uniq: &[String],
+    now: i64,
+) -> Vec<Recommendation> {
+    let tokens_lc = lower_tokens(&opt.tokens);
+
+    let uniq_scores = uniq_scores_from_lines(
+        uniq,
+        &opt.ignore_re,
+        &tokens_lc,
+        opt.check_dir,
+        opt.uniq_decay,
+    );
+
+    let seen_raw: HashSet<String> = idx
+        .paths()
+        .filter(|p| keep_path(p, &opt.ignore_re, &tokens_lc, opt.check_dir))
+        .map(str::to_string)
+        .collect();
 
-    // 4) 计算 frecency 分（未归一）
-    let mut fre_scores: HashMap<String, f64> = HashMap::with_capacity(candidates.len());
-    for dir in &candidates {
-        let s = idx.score_at(dir, now);
-        if s > 0.0 {
-            fre_scores.insert(dir.clone(), s);
+    fuse(opt, idx, seen_raw, uniq_scores, &[], now)
+}
+
+/// 仅返回路径（同排序/同截断）
+pub fn recommend_paths(opt: &RecommendOpt) -> Vec<String> {
+    recommend(opt).into_iter().map(|r| r.path).collect()
+}
+
+/* ----------------------------- 内部实现细节 ----------------------------- */
+
+fn lower_tokens(tokens: &[String]) -> Vec<String> {
+    tokens.iter().map(|t| t.to_lowercase()).collect()
+}
+
+/// 候选路径是否通过 ignore_re / tokens / check_dir 过滤
+fn keep_path(p: &str, ignore_re: &Option<Regex>, tokens_lc: &[String], check_dir: bool) -> bool {
+    if let Some(rx) = ignore_re {
+        if rx.is_match(p) {
+            return false;
         }
     }
+    if !tokens_lc.is_empty() {
+        let lp = p.to_lowercase();
+        if !tokens_lc.iter().any(|tk| lp.contains(tk)) {
+            return false;
+        }
+    }
+    !check_dir || Path::new(p).is_dir()
+}
 
-    // 5) 归一化到 [0,1]
-    let fre_norm = normalize01(&fre_scores);
-    let uniq_norm = normalize01(&uniq_scores);
+/// 内置信号：frecency / uniq 总是参与；反馈 / 位置 / 时段只在权重 > 0（且有所需数据）时参与
+fn builtin_scorers(opt: &RecommendOpt, uniq: UniqScorer, now: i64) -> Vec<(f64, Box<dyn Scorer>)> {
+    let mut v: Vec<(f64, Box<dyn Scorer>)> = vec![
+        (
+            opt.w_frecency,
+            Box::new(FrecencyScorer {
+                normalize: opt.normalize,
+            }),
+        ),
+        (opt.w_uniq, Box::new(uniq)),
+    ];
+    if opt.w_feedback > 0.0 && !opt.feedback.is_empty() {
+        let fb = FeedbackScorer::load(&opt.feedback, opt.half_life, now);
+        v.push((opt.w_feedback, Box::new(fb)));
+    }
+    if let Some(cwd) = opt.cwd.as_deref().filter(|_| opt.w_proximity > 0.0) {
+        v.push((opt.w_proximity, Box::new(Proximity::new(cwd))));
+    }
+    if opt.w_temporal > 0.0 {
+        v.push((opt.w_temporal, Box::new(TemporalScorer)));
+    }
+    v
+}
+
+/// 候选集 = raw ∪ uniq；内置信号 + extra 逐个归一化后加权融合，阈值过滤并排序截断
+fn fuse(
+    opt: &RecommendOpt,
+    idx: &FrecencyIndex,
+    seen_raw: HashSet<String>,
+    uniq_scores: HashMap<String, f64>,
+    extra: &[(f64, &dyn Scorer)],
+    now: i64,
+) -> Vec<Recommendation> {
+    // 3) 候选集 = raw ∪ uniq（信号只给候选打分，不会把历史里没有的路径带进来）
+    let mut candidates: HashSet<String> = seen_raw;
+    candidates.extend(uniq_scores.keys().cloned());
+
+    let builtin = builtin_scorers(
+        opt,
+        UniqScorer::new(uniq_scores).with_normalize(opt.normalize),
+        now,
+    );
+    let scorers: Vec<(f64, &dyn Scorer)> = builtin
+        .iter()
+        .map(|(w, s)| (*w, s.as_ref()))
+        .chain(extra.iter().copied())
+        .collect();
+
+    // 4) 每个信号：原始分 -> 归一化到 [0,1]（没有数据的路径不参与归一化，按 0 计）
+    let ctx = ScoreContext {
+        now,
+        opt,
+        index: idx,
+    };
+    let normalized: Vec<(f64, HashMap<String, f64>)> = scorers
+        .iter()
+        .map(|(w, scorer)| {
+            let raw: HashMap<String, f64> = candidates
+                .iter()
+                .filter_map(|dir| scorer.score(dir, &ctx).map(|s| (dir.clone(), s)))
+                .collect();
+            (*w, scorer.normalize().apply(&raw))
+        })
+        .collect();
 
-    // 6) 融合 + 阈值过滤 + 排序
-    let mut items: Vec<(String, f64, f64, f64)> = Vec::with_capacity(candidates.len());
-    let (wf, wu) = (opt.w_frecency, opt.w_uniq);
+    // 5) 融合 + 排序；第一个信号（frecency）的分用于次排序
+    let mut items: Vec<(String, f64, f64)> = Vec::with_capacity(candidates.len());
     for dir in candidates {
-        let fz = *fre_norm.get(&dir).unwrap_or(&0.0);
-        let uz = *uniq_norm.get(&dir).unwrap_or(&0.0);
-        let final_score = wf * fz + wu * uz;
-        if opt.threshold <= 0.0 || final_score >= opt.threshold {
-            items.push((dir, final_score, fz, uz));
-        }
+        let z = |norm: &HashMap<String, f64>| *norm.get(&dir).unwrap_or(&0.0);
+        let final_score: f64 = normalized.iter().map(|(w, norm)| w * z(norm)).sum();
+        let primary = normalized.first().map_or(0.0, |(_, norm)| z(norm));
+        items.push((dir, final_score, primary));
     }
 
     // 主排序：final desc；次排序：frecency desc；再次：路径字典序
@@ -150,23 +324,58 @@ pub fn recommend_with_now(opt: &RecommendOpt, now: i64) -> Vec<Recommendation> {
             .then(a.0.cmp(&b.0))
     });
 
-    items
+    // 6) 置顶路径按置顶顺序排在最前：不受阈值限制，不在历史里也会补进来，
+    //    但同样要通过 ignore_re / tokens / check_dir 过滤；其余条目按分数补足 limit
+    let tokens_lc = lower_tokens(&opt.tokens);
+    let mut pinned: Vec<(String, f64)> = Vec::new();
+    for p in &opt.pins {
+        if pinned.iter().any(|(q, _)| q == p)
+            || !keep_path(p, &opt.ignore_re, &tokens_lc, opt.check_dir)
+        {
+            continue;
+        }
+        let score = items.iter().find(|it| &it.0 == p).map_or(0.0, |it| it.1);
+        pinned.push((p.clone(), score));
+    }
+    let pin_set: HashSet<&str> = opt.pins.iter().map(String::as_str).collect();
+    let rest = items
         .into_iter()
-        .take(opt.limit)
-        .map(|(path, score, ..)| Recommendation { path, score })
+        .filter(|(p, s, _)| {
+            !pin_set.contains(p.as_str()) && (opt.threshold <= 0.0 || *s >= opt.threshold)
+        })
+        .take(opt.limit.saturating_sub(pinned.len()))
+        .map(|(p, s, _)| (p, s));
+
+    pinned
+        .into_iter()
+        .chain(rest)
+        .map(|(path, score)| {
+            let st = idx.state(&path);
+            Recommendation {
+                visits: st.map(|s| s.visits).unwrap_or(0),
+                last_visit: st.map(|s| s.last_ts),
+                path,
+                score,
+            }
+        })
         .collect()
 }
 
-/// 仅返回路径（同排序/同截断）
-pub fn recommend_paths(opt: &RecommendOpt) -> Vec<String> {
-    recommend(opt).into_iter().map(|r| r.path).collect()
+/// 读取 uniq 文件（旧->新，一行一个路径；不存在视为空）
+pub(crate) fn load_uniq_lines(uniq_file: &str) -> Vec<String> {
+    let f = match File::open(uniq_file) {
+        Ok(f) => f,
+        Err(_) => return Vec::new(),
+    };
+    BufReader::new(f)
+        .lines()
+        .map_while(Result::ok)
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
 }
 
-/* ----------------------------- 内部实现细节 ----------------------------- */
-
-/// 从 uniq 生成几何衰减分：
-/// - 假设 uniq 文件通常“旧->新”，因此从尾到头赋分（最新=1.0，次新=decay，…）
-/// - 支持 ignore_re / tokens / check_dir 过滤
+/// 从 uniq 文件生成几何衰减分（见 `uniq_scores_from_lines`）
 fn load_uniq_scores(
     uniq_file: &str,
     ignore_re: &Option<Regex>,
@@ -174,42 +383,30 @@ fn load_uniq_scores(
     check_dir: bool,
     decay: f64,
 ) -> HashMap<String, f64> {
-    let f = match File::open(uniq_file) {
-        Ok(f) => f,
-        Err(_) => return HashMap::new(),
-    };
-    let mut lines: Vec<String> = BufReader::new(f)
-        .lines()
-        .flatten()
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .collect();
-
-    if lines.is_empty() {
-        return HashMap::new();
-    }
+    let lines = load_uniq_lines(uniq_file);
+    uniq_scores_from_lines(&lines, ignore_re, tokens_lc, check_dir, decay)
+}
 
+/// 从 uniq 列表生成几何衰减分：
+/// - 假设 uniq 列表通常“旧->新”，因此从尾到头赋分（最新=1.0，次新=decay，…）
+/// - 支持 ignore_re / tokens / check_dir 过滤
+fn uniq_scores_from_lines(
+    lines: &[String],
+    ignore_re: &Option<Regex>,
+    tokens_lc: &[String],
+    check_dir: bool,
+    decay: f64,
+) -> HashMap<String, f64> {
     let mut scores = HashMap::with_capacity(lines.len());
     let mut k: usize = 0;
-    for p in lines.drain(..).rev() {
-        if let Some(rx) = ignore_re {
-            if rx.is_match(&p) {
-                continue;
-            }
-        }
-        if !tokens_lc.is_empty() {
-            let lp = p.to_lowercase();
-            if !tokens_lc.iter().any(|tk| lp.contains(tk)) {
-                continue;
-            }
-        }
-        if check_dir && !Path::new(&p).is_dir() {
+    for p in lines.iter().rev() {
+        if !keep_path(p, ignore_re, tokens_lc, check_dir) {
             continue;
         }
         let s = decay.powi(k as i32);
         // 若重复，保留更“新”的那次（分更大）
         scores
-            .entry(p)
+            .entry(p.clone())
             .and_modify(|old| {
                 if s > *old {
                     *old = s
@@ -223,16 +420,21 @@ fn load_uniq_scores(
 
 /// 从 raw 流式构建 Frecency 索引，并记录出现过的路径
 /// - 连续重复 (ts,path) 去重（防抖）
+/// - 摘要行（保留策略折叠掉的旧访问）并入对应目录
 /// - 支持 ignore_re / tokens / check_dir 过滤
-fn build_frecency_from_raw(
+/// - patterns：同时记录访问时段分布（只有时段信号要用）
+pub(crate) fn build_frecency_from_raw(
     raw_file: &str,
     ignore_re: &Option<Regex>,
     tokens_lc: &[String],
     check_dir: bool,
-    half_life: f64,
+    model: Frecency,
+    patterns: bool,
 ) -> (FrecencyIndex, HashSet<String>) {
-    let model = Frecency::new(half_life);
     let mut idx = FrecencyIndex::new(model);
+    if patterns {
+        idx = idx.with_patterns();
+    }
     let mut seen: HashSet<String> = HashSet::new();
 
     let f = match File::open(raw_file) {
@@ -241,25 +443,16 @@ fn build_frecency_from_raw(
     };
 
     let mut last: Option<(i64, String)> = None;
-    for line in BufReader::new(f).lines().flatten() {
-        if let Some((ts, p)) = line.split_once('\t') {
-            if let Ok(t) = ts.parse::<i64>() {
-                let path = p.trim().to_string();
-
-                if let Some(rx) = ignore_re {
-                    if rx.is_match(&path) {
-                        continue;
-                    }
-                }
-                if !tokens_lc.is_empty() {
-                    let lp = path.to_lowercase();
-                    if !tokens_lc.iter().any(|tk| lp.contains(tk)) {
-                        continue;
-                    }
-                }
-                if check_dir && !Path::new(&path).is_dir() {
-                    continue;
-                }
+    for line in BufReader::new(f).lines().map_while(Result::ok) {
+        let Some(raw) = parse_raw_line(&line) else {
+            continue;
+        };
+        let path = raw.path().to_string();
+        if !keep_path(&path, ignore_re, tokens_lc, check_dir) {
+            continue;
+        }
+        match raw {
+            RawLine::Visit(t, _) => {
                 if let Some((lts, ref lp)) = last {
                     if lts == t && lp == &path {
                         continue;
@@ -267,37 +460,14 @@ fn build_frecency_from_raw(
                 }
                 last = Some((t, path.clone()));
                 idx.record_visit(path.clone(), t);
-                seen.insert(path);
             }
+            RawLine::Summary(s, _) => idx.record_summary(path.clone(), &s),
         }
+        seen.insert(path);
     }
     (idx, seen)
 }
 
-/// 把 map 的值线性归一化到 [0,1]
-fn normalize01(map: &HashMap<String, f64>) -> HashMap<String, f64> {
-    if map.is_empty() {
-        return HashMap::new();
-    }
-    let (mut vmin, mut vmax) = (f64::INFINITY, f64::NEG_INFINITY);
-    for &v in map.values() {
-        if v < vmin {
-            vmin = v
-        }
-        if v > vmax {
-            vmax = v
-        }
-    }
-    if !vmin.is_finite() || !vmax.is_finite() || (vmax - vmin).abs() < f64::EPSILON {
-        // 退化：全部给 1.0（单元素或全相等），避免除零
-        return map.iter().map(|(k, _)| (k.clone(), 1.0)).collect();
-    }
-    let span = vmax - vmin;
-    map.iter()
-        .map(|(k, &v)| (k.clone(), (v - vmin) / span))
-        .collect()
-}
-
 /* ---------------------------------- 测试 ---------------------------------- */
 #[cfg(test)]
 mod tests {
@@ -351,6 +521,7 @@ mod tests {
             uniq_decay: 0.85,
             w_frecency: 0.7,
             w_uniq: 0.3,
+            ..RecommendOpt::default()
         };
 
         let out = recommend_with_now(&opt, 3000);
@@ -358,6 +529,9 @@ mod tests {
         // b 更新更近，应优于 a
         assert_eq!(out[0].path, "/tmp/cdh_test_b");
         assert!(out[0].score >= out[1].score);
+        // 访问次数与最近访问时刻随结果一起返回（供 picker 显示）
+        assert_eq!(out[0].visits, 2);
+        assert_eq!(out[0].last_visit, Some(2000));
     }
 
     #[test]
@@ -387,8 +561,245 @@ mod tests {
             uniq_decay: 0.85,
             w_frecency: 0.7,
             w_uniq: 0.3,
+            ..RecommendOpt::default()
         };
         let paths = recommend_paths(&opt);
         assert_eq!(paths, vec!["/tmp/keep_alpha"]);
     }
+
+    #[test]
+    fn feedback_lifts_paths_picked_from_low_rows() {
+        // 两个路径访问记录完全相同：没有反馈时按路径字典序，a 在前
+        let raw = tmp_file("raw3.tsv");
+        let mut f = File::create(&raw).unwrap();
+        writeln!(f, "1000\t/fb/a").unwrap();
+        writeln!(f, "1000\t/fb/b").unwrap();
+        let mut opt = RecommendOpt {
+            raw,
+            uniq: tmp_file("uniq3_missing"),
+            check_dir: false,
+            w_feedback: 0.0,
+            ..RecommendOpt::default()
+        };
+        assert_eq!(recommend_with_now(&opt, 3000)[0].path, "/fb/a");
+
+        // b 曾被从第 2 行选中；没有历史的路径不会因为反馈被带进来
+        let fb = tmp_file("selections");
+        fs::write(&fb, "2000\t3\t\t/fb/gone\n2000\t1\t\t/fb/b\t/fb/a\n").unwrap();
+        opt.feedback = fb;
+        opt.w_feedback = 0.2;
+        let out = recommend_with_now(&opt, 3000);
+        assert_eq!(out[0].path, "/fb/b");
+        assert_eq!(out.len(), 2);
+    }
+
+    #[test]
+    fn proximity_prefers_directories_near_cwd() {
+        // 全局常用的 /global/fav 访问更多；站在 /px/mono 下时，子目录 /px/mono/web 应排到它前面
+        let raw = tmp_file("raw4.tsv");
+        let mut f = File::create(&raw).unwrap();
+        writeln!(f, "-864000\t/px/cold").unwrap();
+        for t in [1000, 1100] {
+            writeln!(f, "{t}\t/global/fav").unwrap();
+        }
+        writeln!(f, "1000\t/px/mono/web").unwrap();
+
+        let mut opt = RecommendOpt {
+            raw,
+            uniq: tmp_file("uniq4_missing"),
+            check_dir: false,
+            ..RecommendOpt::default()
+        };
+        assert_eq!(recommend_with_now(&opt, 2000)[0].path, "/global/fav");
+
+        opt.cwd = Some("/px/mono".into());
+        opt.w_proximity = 0.5;
+        let out = recommend_with_now(&opt, 2000);
+        assert_eq!(out[0].path, "/px/mono/web");
+        // 兄弟目录 /px/cold 也被抬高了，但它很久没去过，仍排在全局常用目录之后
+        assert_eq!(out[1].path, "/global/fav");
+    }
+
+    #[test]
+    fn temporal_pattern_follows_injected_now() {
+        // 2024-01-01 是周一：/tp/oncall 总在工作日早上 9 点（UTC+8）去，/tp/side 总在周末下午
+        const MONDAY: i64 = 1_704_067_200;
+        const DAY: i64 = 86_400;
+        const HOUR: i64 = 3_600;
+        let offset = 8 * HOUR;
+        let raw = tmp_file("raw5.tsv");
+        let mut f = File::create(&raw).unwrap();
+        for week in 0..4 {
+            let w = MONDAY + week * 7 * DAY - offset;
+            for day in 0..2 {
+                writeln!(f, "{}\t/tp/oncall", w + day * DAY + 9 * HOUR).unwrap();
+            }
+            writeln!(f, "{}\t/tp/side", w + 5 * DAY + 15 * HOUR).unwrap();
+            writeln!(f, "{}\t/tp/side", w + 6 * DAY + 15 * HOUR).unwrap();
+        }
+
+        let opt = RecommendOpt {
+            raw,
+            uniq: tmp_file("uniq5_missing"),
+            check_dir: false,
+            w_temporal: 1.0,
+            utc_offset: offset,
+            half_life: 60.0 * DAY as f64,
+            ..RecommendOpt::default()
+        };
+        let week5 = MONDAY + 4 * 7 * DAY - offset;
+        let monday_morning = week5 + 9 * HOUR + 600;
+        let saturday_afternoon = week5 + 5 * DAY + 15 * HOUR + 600;
+        assert_eq!(
+            recommend_with_now(&opt, monday_morning)[0].path,
+            "/tp/oncall"
+        );
+        assert_eq!(
+            recommend_with_now(&opt, saturday_afternoon)[0].path,
+            "/tp/side"
+        );
+
+        // 不启用时两次排序一样
+        let off = RecommendOpt {
+            w_temporal: 0.0,
+            ..opt
+        };
+        assert_eq!(
+            recommend_with_now(&off, monday_morning)[0].path,
+            recommend_with_now(&off, saturday_afternoon)[0].path
+        );
+    }
+
+    #[test]
+    fn rank_and_log_keep_order_stable_with_an_outlier() {
+        // work 比 misc 多去了 10 倍，但 misc 在 uniq 里更新；加上一个访问了 2000 次的 home
+        let write = |name: &str, with_outlier: bool| -> RecommendOpt {
+            let raw = tmp_file(&format!("raw_outlier_{name}.tsv"));
+            let uniq = tmp_file(&format!("uniq_outlier_{name}.txt"));
+            let mut f = File::create(&raw).unwrap();
+            let mut u = File::create(&uniq).unwrap();
+            let mut dirs = vec![("/n/work", 20), ("/n/misc", 2)];
+            if with_outlier {
+                dirs.insert(0, ("/n/home", 2000));
+            }
+            let mut ts = 1000;
+            for (dir, visits) in dirs {
+                for _ in 0..visits {
+                    ts += 1;
+                    writeln!(f, "{ts}\t{dir}").unwrap();
+                }
+                writeln!(u, "{dir}").unwrap();
+            }
+            RecommendOpt {
+                raw,
+                uniq,
+                check_dir: false,
+                ..RecommendOpt::default()
+            }
+        };
+        let order = |opt: &RecommendOpt, normalize: Normalize| -> Vec<String> {
+            let opt = RecommendOpt {
+                normalize,
+                ..opt.clone()
+            };
+            recommend_with_now(&opt, 5000)
+                .into_iter()
+                .map(|r| r.path)
+                .filter(|p| p != "/n/home")
+                .collect()
+        };
+
+        let plain = write("plain", false);
+        let outlier = write("outlier", true);
+        for n in [Normalize::MinMax, Normalize::Rank, Normalize::Log] {
+            assert_eq!(order(&plain, n), ["/n/work", "/n/misc"], "{n:?}");
+        }
+        // min-max：home 把其余的 frecency 都压到 0 附近，uniq 说了算
+        assert_eq!(order(&outlier, Normalize::MinMax), ["/n/misc", "/n/work"]);
+        for n in [Normalize::Rank, Normalize::Log] {
+            assert_eq!(order(&outlier, n), ["/n/work", "/n/misc"], "{n:?}");
+        }
+    }
+
+    #[test]
+    fn pins_come_first_but_still_filtered() {
+        let raw = tmp_file("raw_pins.tsv");
+        let mut f = File::create(&raw).unwrap();
+        for (t, p) in [
+            (1000, "/pn/a"),
+            (1001, "/pn/a"),
+            (1002, "/pn/b"),
+            (1003, "/pn/c"),
+        ] {
+            writeln!(f, "{t}\t{p}").unwrap();
+        }
+        let opt = RecommendOpt {
+            raw,
+            uniq: tmp_file("uniq_pins_missing"),
+            check_dir: false,
+            limit: 3,
+            // /pn/never 不在历史里也会被补进来；重复的置顶只出现一次
+            pins: ["/pn/c", "/pn/never", "/pn/c", "/pn/skip.cache"]
+                .map(String::from)
+                .to_vec(),
+            ignore_re: Some(Regex::new(r"\.cache").unwrap()),
+            ..RecommendOpt::default()
+        };
+        let paths = |opt: &RecommendOpt| -> Vec<String> {
+            recommend_with_now(opt, 2000)
+                .into_iter()
+                .map(|r| r.path)
+                .collect()
+        };
+        // 置顶的排在前面并占用 limit 名额
+        assert_eq!(paths(&opt), ["/pn/c", "/pn/never", "/pn/a"]);
+
+        // 阈值只作用于打分条目
+        let high = RecommendOpt {
+            threshold: 10.0,
+            ..opt.clone()
+        };
+        assert_eq!(paths(&high), ["/pn/c", "/pn/never"]);
+
+        // 关键词同样过滤置顶
+        let tokens = RecommendOpt {
+            tokens: vec!["NEV".into()],
+            ..opt
+        };
+        assert_eq!(paths(&tokens), ["/pn/never"]);
+    }
+
+    #[test]
+    fn custom_scorer_is_fused_with_builtins() {
+        struct Prefer(&'static str);
+        impl Scorer for Prefer {
+            fn name(&self) -> &str {
+                "prefer"
+            }
+            fn score(&self, path: &str, ctx: &ScoreContext) -> Option<f64> {
+                assert_eq!(ctx.now, 3000);
+                (path == self.0).then_some(1.0)
+            }
+        }
+
+        let raw = tmp_file("raw6.tsv");
+        let mut f = File::create(&raw).unwrap();
+        for t in [1000, 1500, 2000] {
+            writeln!(f, "{t}\t/sc/busy").unwrap();
+        }
+        writeln!(f, "1000\t/sc/quiet").unwrap();
+        let opt = RecommendOpt {
+            raw,
+            uniq: tmp_file("uniq6_missing"),
+            check_dir: false,
+            ..RecommendOpt::default()
+        };
+        assert_eq!(recommend_with_now(&opt, 3000)[0].path, "/sc/busy");
+
+        let prefer = Prefer("/sc/quiet");
+        let out = recommend_with_scorers(&opt, &[(1.0, &prefer)], 3000);
+        assert_eq!(out[0].path, "/sc/quiet");
+        // 只有一个路径有数据：min-max 退化为 1.0，加上权重 1.0
+        assert!((out[0].score - 1.0).abs() < 1e-9);
+    }
 }
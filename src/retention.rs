@@ -0,0 +1,471 @@
+// src/retention.rs
+//! 历史保留策略：history_raw 每次 cd 追加一行、只增不减，这里按最长保留时间 / 最多行数 / 最大字节数修剪。
+//!
+//! - 修剪掉的旧访问按目录折叠成摘要行（见 `frecency::FrecencySummary`），写在文件开头：
+//!   decay / dual / zoxide 模型下的分数、访问次数、最近访问时刻和访问时段分布都保持不变；
+//!   window 模型只数访问行，所以配置的是 window 时，还在窗口内的访问一律保留，不折叠
+//!   （窗口内的访问本身超过行数 / 字节上限时，修剪后仍可能超限）；
+//! - 超过行数 / 字节上限时修剪到上限的 90%，避免之后每次 cd 都重写一遍文件；
+//! - 触发：每次记录访问后例行检查（`cdh log` 自己，或者 daemon 在跑时由 daemon 在回复之后做）——
+//!   文件超过 max_size 时立即修剪，其余条件最多一天检查一次（上次检查的时刻记在 STATE/retention）；
+//!   平时只多一次 stat 和一次小文件读取，不拖慢 prompt；也可以手动 `cdh compact`。
+//!
+//! 默认只限制大小（`DEFAULT_MAX_SIZE`），时间和行数不限制。
+
+use crate::frecency::{Frecency, FrecencyKind, FrecencySummary};
+use crate::history::{
+    format_summary_line, now_secs, parse_raw_line, with_history_lock, write_lines_atomic, RawLine,
+    SUMMARY_TAG,
+};
+use crate::AppContext;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// 两次例行检查之间至少间隔的秒数
+const CHECK_INTERVAL: i64 = 24 * 3600;
+
+/// history_raw 默认的最大字节数（8 MiB，约二十万次访问）
+pub const DEFAULT_MAX_SIZE: u64 = 8 << 20;
+
+/// 保留策略（各项为 0 表示不限制；`Default` 全部不限制，配置默认只限制大小）
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Retention {
+    /// 访问最多保留多久（秒）
+    pub max_age: f64,
+    /// 最多保留多少行访问（摘要行不计）
+    pub max_lines: usize,
+    /// history_raw 最大字节数
+    pub max_size: u64,
+}
+
+impl Retention {
+    /// 三项都不限制
+    pub fn is_unlimited(&self) -> bool {
+        self.max_age <= 0.0 && self.max_lines == 0 && self.max_size == 0
+    }
+}
+
+/// 一次修剪的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactStats {
+    /// 保留下来的访问行数
+    pub kept: usize,
+    /// 折叠进摘要的访问行数
+    pub dropped: usize,
+    /// 修剪后的摘要行数（每个目录最多一行）
+    pub summaries: usize,
+}
+
+/// 解析字节数：纯数字，或带 K / M / G 后缀（1024 进制，可再跟 `B` / `iB`）
+pub fn parse_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let upper = s.to_ascii_uppercase();
+    let body = upper
+        .strip_suffix("IB")
+        .or_else(|| upper.strip_suffix('B'))
+        .unwrap_or(&upper);
+    let (num, unit) = match body.chars().last()? {
+        'K' => (&body[..body.len() - 1], 1u64 << 10),
+        'M' => (&body[..body.len() - 1], 1 << 20),
+        'G' => (&body[..body.len() - 1], 1 << 30),
+        _ => (body, 1),
+    };
+    let n = num.trim().replace('_', "").parse::<f64>().ok()?;
+    (n.is_finite() && n >= 0.0).then_some((n * unit as f64) as u64)
+}
+
+/// 上次例行检查时刻的记录文件：STATE/retention
+fn stamp_path(ctx: &AppContext) -> PathBuf {
+    ctx.paths.state_dir.join("retention")
+}
+
+/// 配置的 Frecency 模型（半衰期不合法时为 None，不做修剪）
+fn model(ctx: &AppContext) -> Option<Frecency> {
+    let cfg = &ctx.config;
+    let ok = |v: f64| v.is_finite() && v > 0.0;
+    (ok(cfg.half_life) && ok(cfg.long_half_life) && ok(cfg.frecency_window)).then(|| cfg.frecency())
+}
+
+/// 手动修剪（`cdh compact`）：按 policy 修剪，摘要按配置的 Frecency 模型计算
+pub fn compact(ctx: &AppContext, policy: &Retention) -> io::Result<CompactStats> {
+    let model = model(ctx).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "half_life 必须是大于 0 的有限数字",
+        )
+    })?;
+    with_history_lock(ctx, || {
+        let now = now_secs();
+        let stats = compact_locked(ctx, policy, &model, now)?;
+        let _ = fs::write(stamp_path(ctx), format!("{now}\n"));
+        Ok(stats)
+    })
+}
+
+/// 例行检查（每次记录访问后调用）：到期或超过大小上限时按配置的策略修剪，否则什么都不做
+pub fn maybe_compact(ctx: &AppContext, now: i64) -> io::Result<()> {
+    let policy = ctx.config.retention();
+    let Some(model) = model(ctx).filter(|_| !policy.is_unlimited()) else {
+        return Ok(());
+    };
+    with_history_lock(ctx, || maybe_compact_locked(ctx, &policy, &model, now))
+}
+
+fn maybe_compact_locked(
+    ctx: &AppContext,
+    policy: &Retention,
+    model: &Frecency,
+    now: i64,
+) -> io::Result<()> {
+    let size = match fs::metadata(&ctx.paths.history_raw) {
+        Ok(m) => m.len(),
+        Err(_) => return Ok(()),
+    };
+    let over_size = policy.max_size > 0 && size > policy.max_size;
+    // 只限制大小时，没超限就不必读整个文件
+    if !over_size && policy.max_age <= 0.0 && policy.max_lines == 0 {
+        return Ok(());
+    }
+    let last_check = fs::read_to_string(stamp_path(ctx))
+        .ok()
+        .and_then(|s| s.trim().parse::<i64>().ok());
+    let due = last_check.map_or(true, |t| now - t >= CHECK_INTERVAL || t > now);
+    if !over_size && !due {
+        return Ok(());
+    }
+    compact_locked(ctx, policy, model, now)?;
+    fs::write(stamp_path(ctx), format!("{now}\n"))
+}
+
+/// 修剪 history_raw（调用方持有历史锁）：没有需要丢弃的行时不改动文件
+pub(crate) fn compact_locked(
+    ctx: &AppContext,
+    policy: &Retention,
+    model: &Frecency,
+    now: i64,
+) -> io::Result<CompactStats> {
+    let raw_path = &ctx.paths.history_raw;
+    let text = match fs::read_to_string(raw_path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(CompactStats::default()),
+        Err(e) => return Err(e),
+    };
+
+    let mut summaries: BTreeMap<String, Vec<FrecencySummary>> = BTreeMap::new();
+    let mut visits: Vec<(i64, &str)> = Vec::new();
+    for line in text.lines() {
+        match parse_raw_line(line) {
+            // 连续重复的 (ts, path) 建索引时本来就只算一次
+            Some(RawLine::Visit(ts, p)) if !p.is_empty() && visits.last() != Some(&(ts, p)) => {
+                visits.push((ts, p))
+            }
+            Some(RawLine::Summary(s, p)) => summaries.entry(p.to_string()).or_default().push(*s),
+            _ => {}
+        }
+    }
+
+    // 1) 按行数 / 字节数：从最早的访问开始丢，直到不超过上限的 90%
+    let mut cut = 0;
+    if policy.max_lines > 0 && visits.len() > policy.max_lines {
+        cut = visits.len() - policy.max_lines * 9 / 10;
+    }
+    if policy.max_size > 0 && text.len() as u64 > policy.max_size {
+        let target = policy.max_size * 9 / 10;
+        // 摘要行合并新访问后会变长：每个要写摘要的目录都按它全部访问折叠后最长的一行估算
+        let mut per_path: HashMap<&str, u64> = HashMap::new();
+        for (p, ss) in &summaries {
+            *per_path.entry(p).or_default() += ss.iter().map(|s| s.visits).sum::<u64>();
+        }
+        for (_, p) in &visits {
+            *per_path.entry(p).or_default() += 1;
+        }
+        let max_len = |p: &str| summary_line_max_len(p, per_path[p]);
+        let mut total = summaries.keys().map(|p| max_len(p)).sum::<u64>()
+            + visits
+                .iter()
+                .map(|(ts, p)| visit_line(*ts, p).len() as u64 + 1)
+                .sum::<u64>();
+        let mut summarized: HashSet<&str> = summaries.keys().map(String::as_str).collect();
+        let mut i = 0;
+        while i < visits.len() && total > target {
+            let (ts, p) = visits[i];
+            total -= visit_line(ts, p).len() as u64 + 1;
+            if summarized.insert(p) {
+                total += max_len(p);
+            }
+            i += 1;
+        }
+        cut = cut.max(i);
+    }
+    // 2) 按时间：早于 now - max_age 的访问全部丢掉
+    let cutoff = (policy.max_age > 0.0).then(|| now - policy.max_age as i64);
+    // window 模型只数访问行：还在窗口内的访问（之后的窗口只会更靠后）不管上面怎么算都留着
+    let window_start =
+        (model.kind() == FrecencyKind::Window).then(|| now - model.window_secs().ceil() as i64);
+
+    let mut dropped: BTreeMap<&str, Vec<i64>> = BTreeMap::new();
+    let mut kept: Vec<String> = Vec::new();
+    for (i, &(ts, p)) in visits.iter().enumerate() {
+        let expired = i < cut || cutoff.is_some_and(|c| ts < c);
+        if expired && window_start.map_or(true, |w| ts < w) {
+            dropped.entry(p).or_default().push(ts);
+        } else {
+            kept.push(visit_line(ts, p));
+        }
+    }
+    let dropped_count = visits.len() - kept.len();
+    if dropped_count == 0 {
+        return Ok(CompactStats {
+            kept: kept.len(),
+            dropped: 0,
+            summaries: summaries.len(),
+        });
+    }
+
+    // 3) 丢掉的访问连同已有摘要，按目录折叠成一条摘要
+    for (p, ts) in dropped {
+        if let Some(s) = model.summarize(&[], ts) {
+            summaries.entry(p.to_string()).or_default().push(s);
+        }
+    }
+    let mut lines: Vec<String> = summaries
+        .iter()
+        .filter_map(|(p, ss)| {
+            model
+                .summarize(ss, Vec::new())
+                .map(|s| format_summary_line(p, &s))
+        })
+        .collect();
+    let summary_count = lines.len();
+    let kept_count = kept.len();
+    lines.extend(kept);
+    write_lines_atomic(raw_path, &lines)?;
+
+    Ok(CompactStats {
+        kept: kept_count,
+        dropped: dropped_count,
+        summaries: summary_count,
+    })
+}
+
+/// 一个目录的摘要行（含换行）最长多少字节，visits 为它最多可能有的访问次数。
+///
+/// 按 `format_summary_line` 的各列取上界：
+/// - 访问次数 / 时间戳：i64 / u64 十进制最多 20 个字符；
+/// - 两个分数：聚合到 last_ts 的分数落在 [1, visits]，f64 的 `Display` 不用指数、
+///   最多 17 位有效数字，加上小数点不超过 18 个字符（visits 小于 10^17）；
+/// - 时段分布：每个非零小时一项 `小时:次数,`（小时最多 3 位），项数不超过 168 也不超过访问次数。
+fn summary_line_max_len(path: &str, visits: u64) -> u64 {
+    const INT_WIDTH: u64 = 20;
+    const SCORE_WIDTH: u64 = 18;
+    let count_width = visits.max(1).ilog10() as u64 + 1;
+    let hours = visits.clamp(1, 168) * (3 + 1 + count_width + 1);
+    let fields = SUMMARY_TAG.len() as u64 + 2 * INT_WIDTH + 2 * SCORE_WIDTH + hours;
+    // 6 个 TAB + 换行
+    fields + path.len() as u64 + 7
+}
+
+fn visit_line(ts: i64, path: &str) -> String {
+    format!("{ts}\t{path}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recommend::build_frecency_from_raw;
+    use crate::FrecencyIndex;
+
+    const DAY: i64 = 86_400;
+    const NOW: i64 = 1_700_000_000;
+
+    /// 两个月的历史：/old 只在前一个月去过，/daily 每天一次，/busy 每隔几天去一串
+    fn write_history(ctx: &AppContext) -> usize {
+        let mut lines = Vec::new();
+        for d in (0..60).rev() {
+            let day = NOW - d * DAY;
+            if d >= 30 {
+                lines.push(visit_line(day - 3600, "/old"));
+            }
+            lines.push(visit_line(day - 600, "/daily"));
+            if d % 3 == 0 {
+                for k in 0..4 {
+                    lines.push(visit_line(day - 300 + k, "/busy"));
+                }
+            }
+        }
+        write_lines_atomic(&ctx.paths.history_raw, &lines).unwrap();
+        lines.len()
+    }
+
+    fn index(ctx: &AppContext, model: Frecency) -> FrecencyIndex {
+        let raw = ctx.paths.history_raw.to_string_lossy();
+        build_frecency_from_raw(&raw, &None, &[], false, model, true).0
+    }
+
+    fn raw_lines(ctx: &AppContext) -> Vec<String> {
+        fs::read_to_string(&ctx.paths.history_raw)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn scores_survive_compaction() {
+        let (root, ctx) = AppContext::for_test("scores");
+        let total = write_history(&ctx);
+        let base = Frecency::new(7.0 * DAY as f64);
+        let models = [
+            base,
+            base.with_kind(FrecencyKind::Dual),
+            base.with_kind(FrecencyKind::Zoxide),
+        ];
+        let before: Vec<FrecencyIndex> = models.iter().map(|m| index(&ctx, *m)).collect();
+
+        // 先按行数修剪一次，再按时间修剪一次（第二次要和已有摘要合并）
+        let policy = Retention {
+            max_lines: 100,
+            ..Retention::default()
+        };
+        let st = compact_locked(&ctx, &policy, &base, NOW).unwrap();
+        assert_eq!(st.kept, 90);
+        assert_eq!(st.dropped, total - 90);
+        let policy = Retention {
+            max_age: 10.0 * DAY as f64,
+            max_lines: 0,
+            max_size: 0,
+        };
+        let st = compact_locked(&ctx, &policy, &base, NOW).unwrap();
+        assert_eq!(st.summaries, 3);
+        assert!(st.kept < 90);
+        assert_eq!(raw_lines(&ctx).len(), st.summaries + st.kept);
+
+        for (m, old) in models.iter().zip(&before) {
+            let new = index(&ctx, *m);
+            for p in ["/old", "/daily", "/busy"] {
+                let (a, b) = (old.score_at(p, NOW), new.score_at(p, NOW));
+                assert!((a - b).abs() < 1e-9, "{m:?} {p}: {a} vs {b}");
+                let (a, b) = (old.state(p).unwrap(), new.state(p).unwrap());
+                assert_eq!((a.visits, a.last_ts), (b.visits, b.last_ts), "{p}");
+                assert_eq!(old.pattern(p), new.pattern(p), "{p}");
+            }
+        }
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn window_ranking_survives_compaction() {
+        let (root, ctx) = AppContext::for_test("window");
+        write_history(&ctx);
+        let model = Frecency::new(7.0 * DAY as f64)
+            .with_kind(FrecencyKind::Window)
+            .with_window(14.0 * DAY as f64);
+        let before = index(&ctx, model);
+
+        // 行数上限远小于窗口内的访问数：只折叠窗口外的访问
+        let policy = Retention {
+            max_lines: 20,
+            ..Retention::default()
+        };
+        let st = compact_locked(&ctx, &policy, &model, NOW).unwrap();
+        assert!(st.dropped > 0);
+        assert!(st.kept > 20);
+        let after = index(&ctx, model);
+
+        let ranking = |idx: &FrecencyIndex, now: i64| -> Vec<(String, f64)> {
+            let mut v: Vec<(String, f64)> = ["/old", "/daily", "/busy"]
+                .iter()
+                .map(|p| (p.to_string(), idx.score_at(p, now)))
+                .collect();
+            v.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            v
+        };
+        for now in [NOW, NOW + 3 * DAY, NOW + 20 * DAY] {
+            assert_eq!(ranking(&before, now), ranking(&after, now), "{now}");
+        }
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn size_limit_and_no_op() {
+        let (root, ctx) = AppContext::for_test("size");
+        write_history(&ctx);
+        let model = Frecency::new(7.0 * DAY as f64);
+        let policy = Retention {
+            max_age: 0.0,
+            max_lines: 0,
+            max_size: 2048,
+        };
+        let st = compact_locked(&ctx, &policy, &model, NOW).unwrap();
+        assert!(st.dropped > 0);
+        let size = fs::metadata(&ctx.paths.history_raw).unwrap().len();
+        assert!(size <= 2048, "{size}");
+
+        // 已经在限制内：不改动文件
+        let before = raw_lines(&ctx);
+        let st = compact_locked(&ctx, &policy, &model, NOW).unwrap();
+        assert_eq!(st.dropped, 0);
+        assert_eq!(raw_lines(&ctx), before);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn summary_width_is_an_upper_bound() {
+        let model = Frecency::new(7.0 * DAY as f64);
+        for n in [1i64, 7, 500, 5000] {
+            // 每次访问间隔 1 小时 + 1 秒：时段分布尽量铺满，分数带长小数
+            let events: Vec<i64> = (0..n).map(|k| NOW - k * 3601).collect();
+            let s = model.summarize(&[], events).unwrap();
+            let line = format_summary_line("/p", &s);
+            assert!(
+                (line.len() as u64) < summary_line_max_len("/p", n as u64),
+                "{line}"
+            );
+        }
+    }
+
+    #[test]
+    fn maybe_compact_runs_at_most_daily() {
+        let (root, mut ctx) = AppContext::for_test("maybe");
+        ctx.config.history_max_lines = 50;
+        let dir = root.join("here");
+        fs::create_dir_all(&dir).unwrap();
+        let total = write_history(&ctx);
+
+        // log_visit 本身只追加，修剪交给调用方的例行检查
+        crate::history::log_visit(&ctx, dir.to_str().unwrap()).unwrap();
+        assert_eq!(raw_lines(&ctx).len(), total + 1);
+
+        maybe_compact(&ctx, NOW).unwrap();
+        let lines = raw_lines(&ctx);
+        assert!(lines.len() < total);
+        assert!(lines[0].starts_with("summary\t"));
+
+        // 刚检查过：行数再超限也要等到下一次例行检查
+        write_history(&ctx);
+        maybe_compact(&ctx, NOW + 3600).unwrap();
+        assert_eq!(raw_lines(&ctx).len(), total);
+        maybe_compact(&ctx, NOW + CHECK_INTERVAL).unwrap();
+        assert!(raw_lines(&ctx).len() < total);
+
+        // 只限制大小且没超限：不读文件，也不记检查时刻
+        ctx.config.history_max_lines = 0;
+        let _ = fs::remove_file(stamp_path(&ctx));
+        maybe_compact(&ctx, NOW + 2 * CHECK_INTERVAL).unwrap();
+        assert!(!stamp_path(&ctx).exists());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn sizes_with_units() {
+        assert_eq!(parse_size("4096"), Some(4096));
+        assert_eq!(parse_size("10K"), Some(10 * 1024));
+        assert_eq!(parse_size("1.5m"), Some(3 * 1024 * 1024 / 2));
+        assert_eq!(parse_size("2GiB"), Some(2 << 30));
+        assert_eq!(parse_size("512 KB"), Some(512 * 1024));
+        assert_eq!(parse_size("-1"), None);
+        assert_eq!(parse_size("lots"), None);
+    }
+}
@@ -0,0 +1,347 @@
+// src/scorer.rs
+//! 打分信号：推荐的最终分是若干个 `Scorer` 的加权和。
+//!
+//! 每个 `Scorer` 对一个候选路径给出原始分（没有数据时返回 None），
+//! 融合时先在全部候选上按它自己的 `Normalize` 方式归一化到 [0,1]，再乘以权重相加。
+//!
+//! 内置信号：
+//!   - `FrecencyScorer`：raw 日志的 Frecency（默认 min-max 归一化，可由配置 `normalize` 改成 rank / log / zscore）
+//!   - `UniqScorer`：uniq 列表的“最近唯一”几何衰减分（同上）
+//!   - `feedback::FeedbackScorer`：选择反馈（按最大值缩放）
+//!   - `proximity::Proximity`：离当前目录的远近（本身就在 [0,1]）
+//!   - `temporal::TemporalScorer`：访问时段与现在的吻合度（按最大值缩放）
+//!
+//! 库的使用者可以实现自己的 `Scorer`，通过 `recommend::recommend_with_scorers` 和内置信号一起融合：
+//!
+//! ```
+//! use cdh::scorer::{ScoreContext, Scorer};
+//!
+//! /// 工作目录下的路径加分
+//! struct UnderWork;
+//!
+//! impl Scorer for UnderWork {
+//!     fn name(&self) -> &str {
+//!         "work"
+//!     }
+//!     fn score(&self, path: &str, _ctx: &ScoreContext) -> Option<f64> {
+//!         Some(if path.starts_with("/work/") { 1.0 } else { 0.0 })
+//!     }
+//! }
+//! ```
+
+use crate::frecency::FrecencyIndex;
+use crate::recommend::RecommendOpt;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// 打分时可用的上下文
+pub struct ScoreContext<'a> {
+    /// 当前时间（秒）
+    pub now: i64,
+    /// 本次推荐的配置
+    pub opt: &'a RecommendOpt,
+    /// raw 日志建出的 Frecency 索引（含访问次数、最近访问、访问时段）
+    pub index: &'a FrecencyIndex,
+}
+
+/// 原始分如何归一化到 [0,1]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Normalize {
+    /// (v - min) / (max - min)；全部相等时都记 1
+    #[default]
+    MinMax,
+    /// v / max：最小值不会被压成 0
+    Max,
+    /// 原样使用（信号本身已经在 [0,1]）
+    None,
+    /// 名次百分位：(比它小的个数 + 并列个数 - 1) / (n - 1)，并列的取最高名次；
+    /// 只看先后不看差距，一个访问了几千次的目录不会把其余的都压到 0 附近
+    Rank,
+    /// ln(1 + v) 后再 min-max：压缩长尾，保留“差多少”的信息
+    Log,
+    /// z 分数截断到 [-2, 2] 再线性映射到 [0,1]；标准差为 0 时都记 1
+    ZScore,
+}
+
+impl Normalize {
+    /// 对一组原始分归一化
+    pub fn apply(self, raw: &HashMap<String, f64>) -> HashMap<String, f64> {
+        match self {
+            Normalize::MinMax => normalize01(raw),
+            Normalize::Max => scale_to_max(raw),
+            Normalize::None => raw.clone(),
+            Normalize::Rank => percentile_rank(raw),
+            Normalize::Log => {
+                let logged = raw
+                    .iter()
+                    .map(|(k, &v)| (k.clone(), v.max(0.0).ln_1p()))
+                    .collect();
+                normalize01(&logged)
+            }
+            Normalize::ZScore => zscore_clamped(raw),
+        }
+    }
+
+    /// 配置里的名字
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Normalize::MinMax => "minmax",
+            Normalize::Max => "max",
+            Normalize::None => "none",
+            Normalize::Rank => "rank",
+            Normalize::Log => "log",
+            Normalize::ZScore => "zscore",
+        }
+    }
+}
+
+impl FromStr for Normalize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s
+            .trim()
+            .to_ascii_lowercase()
+            .replace(['-', '_'], "")
+            .as_str()
+        {
+            "minmax" => Ok(Normalize::MinMax),
+            "max" => Ok(Normalize::Max),
+            "none" => Ok(Normalize::None),
+            "rank" | "percentile" => Ok(Normalize::Rank),
+            "log" => Ok(Normalize::Log),
+            "zscore" => Ok(Normalize::ZScore),
+            _ => Err(format!(
+                "未知的归一化方式: {}（minmax / rank / log / zscore）",
+                s.trim()
+            )),
+        }
+    }
+}
+
+/// 一个打分信号
+pub trait Scorer {
+    /// 信号名（调试 / 说明用）
+    fn name(&self) -> &str;
+
+    /// 候选路径的原始分（越大越靠前）；None 表示这个信号对该路径没有数据
+    /// （不参与归一化的 min / max，融合时按 0 计）
+    fn score(&self, path: &str, ctx: &ScoreContext) -> Option<f64>;
+
+    /// 归一化方式（默认 min-max）
+    fn normalize(&self) -> Normalize {
+        Normalize::MinMax
+    }
+}
+
+/// Frecency：频次 × 时效衰减（见 src/frecency.rs）
+#[derive(Default)]
+pub struct FrecencyScorer {
+    /// 归一化方式（默认 min-max）
+    pub normalize: Normalize,
+}
+
+impl Scorer for FrecencyScorer {
+    fn name(&self) -> &str {
+        "frecency"
+    }
+
+    fn score(&self, path: &str, ctx: &ScoreContext) -> Option<f64> {
+        let s = ctx.index.score_at(path, ctx.now);
+        (s > 0.0).then_some(s)
+    }
+
+    fn normalize(&self) -> Normalize {
+        self.normalize
+    }
+}
+
+/// uniq 列表的“最近唯一”分：最新 = 1.0，次新 = decay，…（由 recommend 预先算好）
+pub struct UniqScorer {
+    scores: HashMap<String, f64>,
+    normalize: Normalize,
+}
+
+impl UniqScorer {
+    pub fn new(scores: HashMap<String, f64>) -> Self {
+        Self {
+            scores,
+            normalize: Normalize::MinMax,
+        }
+    }
+
+    /// 换一种归一化方式
+    pub fn with_normalize(mut self, normalize: Normalize) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// uniq 里出现过的路径
+    pub fn paths(&self) -> impl Iterator<Item = &String> {
+        self.scores.keys()
+    }
+}
+
+impl Scorer for UniqScorer {
+    fn name(&self) -> &str {
+        "uniq"
+    }
+
+    fn score(&self, path: &str, _ctx: &ScoreContext) -> Option<f64> {
+        self.scores.get(path).copied()
+    }
+
+    fn normalize(&self) -> Normalize {
+        self.normalize
+    }
+}
+
+/// 把 map 的值线性归一化到 [0,1]
+fn normalize01(map: &HashMap<String, f64>) -> HashMap<String, f64> {
+    if map.is_empty() {
+        return HashMap::new();
+    }
+    let (mut vmin, mut vmax) = (f64::INFINITY, f64::NEG_INFINITY);
+    for &v in map.values() {
+        if v < vmin {
+            vmin = v
+        }
+        if v > vmax {
+            vmax = v
+        }
+    }
+    if !vmin.is_finite() || !vmax.is_finite() || (vmax - vmin).abs() < f64::EPSILON {
+        // 退化：全部给 1.0（单元素或全相等），避免除零
+        return map.keys().map(|k| (k.clone(), 1.0)).collect();
+    }
+    let span = vmax - vmin;
+    map.iter()
+        .map(|(k, &v)| (k.clone(), (v - vmin) / span))
+        .collect()
+}
+
+/// 把 map 的值按最大值缩放到 [0,1]（最小值不会被压成 0）
+fn scale_to_max(map: &HashMap<String, f64>) -> HashMap<String, f64> {
+    let vmax = map.values().copied().fold(0.0, f64::max);
+    if vmax <= 0.0 || !vmax.is_finite() {
+        return HashMap::new();
+    }
+    map.iter().map(|(k, &v)| (k.clone(), v / vmax)).collect()
+}
+
+/// 名次百分位：升序排好后，并列的一组都取组内最高名次，再除以 n - 1
+fn percentile_rank(map: &HashMap<String, f64>) -> HashMap<String, f64> {
+    let mut vals: Vec<f64> = map.values().copied().collect();
+    vals.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let n = vals.len();
+    if n <= 1 {
+        return map.keys().map(|k| (k.clone(), 1.0)).collect();
+    }
+    map.iter()
+        .map(|(k, &v)| {
+            // 不大于 v 的个数（即 v 这一组的最高名次 + 1）
+            let le = vals.partition_point(|&x| x <= v);
+            (k.clone(), (le - 1) as f64 / (n - 1) as f64)
+        })
+        .collect()
+}
+
+/// z 分数（总体标准差）截断到 [-2, 2]，再映射到 [0,1]
+fn zscore_clamped(map: &HashMap<String, f64>) -> HashMap<String, f64> {
+    const CLAMP: f64 = 2.0;
+    let n = map.len() as f64;
+    let mean = map.values().sum::<f64>() / n;
+    let var = map.values().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    let sd = var.sqrt();
+    if !sd.is_finite() || sd < f64::EPSILON {
+        return map.keys().map(|k| (k.clone(), 1.0)).collect();
+    }
+    map.iter()
+        .map(|(k, &v)| {
+            let z = ((v - mean) / sd).clamp(-CLAMP, CLAMP);
+            (k.clone(), (z + CLAMP) / (2.0 * CLAMP))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_modes() {
+        let raw: HashMap<String, f64> = [("a", 2.0), ("b", 4.0)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        let mm = Normalize::MinMax.apply(&raw);
+        assert_eq!((mm["a"], mm["b"]), (0.0, 1.0));
+        let mx = Normalize::Max.apply(&raw);
+        assert_eq!((mx["a"], mx["b"]), (0.5, 1.0));
+        assert_eq!(Normalize::None.apply(&raw), raw);
+
+        let single: HashMap<String, f64> = [("a".to_string(), 3.0)].into_iter().collect();
+        assert_eq!(Normalize::MinMax.apply(&single)["a"], 1.0);
+        assert!(Normalize::Max.apply(&HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn outlier_does_not_squash_the_rest() {
+        let raw: HashMap<String, f64> = [
+            ("home", 5000.0),
+            ("work", 40.0),
+            ("misc", 4.0),
+            ("tmp", 4.0),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+
+        // min-max：除了 home 之外都挤在 0 附近
+        let mm = Normalize::MinMax.apply(&raw);
+        assert!(mm["work"] < 0.01);
+
+        // rank：只看先后；并列的取最高名次
+        let rk = Normalize::Rank.apply(&raw);
+        assert_eq!(
+            (rk["home"], rk["work"], rk["misc"], rk["tmp"]),
+            (1.0, 2.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0)
+        );
+
+        // log：work 和 misc 的差距被保留下来
+        let lg = Normalize::Log.apply(&raw);
+        assert_eq!(lg["home"], 1.0);
+        assert!(lg["work"] - lg["misc"] > 0.25, "{lg:?}");
+
+        // zscore：截断后都在 [0,1]，顺序不变
+        let zs = Normalize::ZScore.apply(&raw);
+        assert!(zs.values().all(|v| (0.0..=1.0).contains(v)));
+        assert!(zs["home"] > zs["work"] && zs["work"] > zs["misc"]);
+        assert_eq!(zs["misc"], zs["tmp"]);
+
+        let flat: HashMap<String, f64> = [("a".to_string(), 3.0), ("b".to_string(), 3.0)]
+            .into_iter()
+            .collect();
+        for n in [Normalize::Rank, Normalize::Log, Normalize::ZScore] {
+            assert!(n.apply(&flat).values().all(|&v| v == 1.0), "{n:?}");
+        }
+    }
+
+    #[test]
+    fn normalize_names_round_trip() {
+        for n in [
+            Normalize::MinMax,
+            Normalize::Max,
+            Normalize::None,
+            Normalize::Rank,
+            Normalize::Log,
+            Normalize::ZScore,
+        ] {
+            assert_eq!(n.as_str().parse::<Normalize>(), Ok(n));
+        }
+        assert_eq!("min-max".parse::<Normalize>(), Ok(Normalize::MinMax));
+        assert_eq!("z_score".parse::<Normalize>(), Ok(Normalize::ZScore));
+        assert!("sigmoid".parse::<Normalize>().is_err());
+    }
+}
@@ -0,0 +1,284 @@
+// src/temporal.rs
+//! 时段信号：从 `history_raw` 的时间戳学习每个目录常在“星期几的几点”被访问，
+//! 当前时刻与之吻合时加分（比如早上的值班仓库、周末的个人项目）。
+//!
+//! - 每个目录按 UTC 的“一周中的第几个小时”（0..168）计数，和本地时区无关；
+//!   查询时再按 `utc_offset` 换算成本地的星期 / 小时，所以 daemon 的内存索引不用关心时区。
+//! - 吻合度：同一小时记 1、相邻小时记 0.5；同一天记 1、同为工作日 / 同为周末记 0.5；
+//!   两者相乘后按访问次数加权平均，再乘以置信度 `n / (n + 5)`（只去过一两次的目录不会一下冲到最前）。
+//! - 默认不启用（`w_temporal = 0`，这时索引也不记时段分布，见 `FrecencyIndex::with_patterns`）；
+//!   本地时区取 `CDH_UTC_OFFSET`（如 `+08:00`），没有时问 `date +%z`（每个进程只问一次）。
+
+use crate::scorer::{Normalize, ScoreContext, Scorer};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// 一周的小时数
+const WEEK_HOURS: usize = 7 * 24;
+/// 置信度的平滑常数
+const CONFIDENCE_K: f64 = 5.0;
+
+/// 某个目录的访问时段分布（UTC 一周中的小时 → 次数）
+///
+/// 文本形式（历史摘要行里用）：非零的 `小时:次数` 以逗号分隔，没有访问时写 `-`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimePattern {
+    bins: [u32; WEEK_HOURS],
+    total: u32,
+}
+
+impl Default for TimePattern {
+    fn default() -> Self {
+        Self {
+            bins: [0; WEEK_HOURS],
+            total: 0,
+        }
+    }
+}
+
+impl TimePattern {
+    /// 记录一次访问
+    pub fn observe(&mut self, ts: i64) {
+        let b = week_hour(ts);
+        self.bins[b] = self.bins[b].saturating_add(1);
+        self.total = self.total.saturating_add(1);
+    }
+
+    /// 累计访问次数
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    /// 并入另一份分布（按小时相加）
+    pub fn merge(&mut self, other: &TimePattern) {
+        for (a, &b) in self.bins.iter_mut().zip(&other.bins) {
+            *a = a.saturating_add(b);
+        }
+        self.total = self.total.saturating_add(other.total);
+    }
+
+    /// now 时刻的吻合度（0~1，见模块文档）；utc_offset 为本地时区相对 UTC 的秒数
+    pub fn affinity(&self, now: i64, utc_offset: i64) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        let (day, hour) = split(week_hour(now + utc_offset));
+        // 时区按整小时换算（半小时时区的误差在“相邻小时记 0.5”里被吸收）
+        let shift = (utc_offset as f64 / 3600.0).round() as i64;
+        let mut matched = 0.0;
+        for (b, &c) in self.bins.iter().enumerate() {
+            if c == 0 {
+                continue;
+            }
+            let local = (b as i64 + shift).rem_euclid(WEEK_HOURS as i64) as usize;
+            let (d, h) = split(local);
+            matched += f64::from(c) * hour_weight(h, hour) * day_weight(d, day);
+        }
+        let n = f64::from(self.total);
+        matched / n * (n / (n + CONFIDENCE_K))
+    }
+}
+
+impl fmt::Display for TimePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.total == 0 {
+            return f.write_str("-");
+        }
+        let mut first = true;
+        for (b, &c) in self.bins.iter().enumerate().filter(|(_, &c)| c > 0) {
+            if !first {
+                f.write_str(",")?;
+            }
+            write!(f, "{b}:{c}")?;
+            first = false;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TimePattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut p = TimePattern::default();
+        let s = s.trim();
+        if s == "-" {
+            return Ok(p);
+        }
+        for item in s.split(',') {
+            let bad = || format!("invalid time pattern entry: {item}");
+            let (b, c) = item.split_once(':').ok_or_else(bad)?;
+            let b: usize = b.parse().map_err(|_| bad())?;
+            let c: u32 = c.parse().map_err(|_| bad())?;
+            if b >= WEEK_HOURS {
+                return Err(bad());
+            }
+            p.bins[b] = p.bins[b].saturating_add(c);
+            p.total = p.total.saturating_add(c);
+        }
+        Ok(p)
+    }
+}
+
+/// 时段信号：Frecency 索引里记下的访问时段与 `ctx.now` 的吻合度（按最大值缩放）
+pub struct TemporalScorer;
+
+impl Scorer for TemporalScorer {
+    fn name(&self) -> &str {
+        "temporal"
+    }
+
+    fn score(&self, path: &str, ctx: &ScoreContext) -> Option<f64> {
+        let p = ctx.index.pattern(path)?;
+        Some(p.affinity(ctx.now, ctx.opt.utc_offset))
+    }
+
+    fn normalize(&self) -> Normalize {
+        Normalize::Max
+    }
+}
+
+/// 时间戳 → 一周中的第几个小时（周一 00 点为 0）
+fn week_hour(ts: i64) -> usize {
+    // 1970-01-01 是周四：往前挪 3 天对齐到周一
+    let hours = ts.div_euclid(3600) + 3 * 24;
+    hours.rem_euclid(WEEK_HOURS as i64) as usize
+}
+
+/// 一周中的小时 → (星期，0 为周一；小时)
+fn split(week_hour: usize) -> (usize, usize) {
+    (week_hour / 24, week_hour % 24)
+}
+
+fn hour_weight(a: usize, b: usize) -> f64 {
+    let d = a.abs_diff(b);
+    match d.min(24 - d) {
+        0 => 1.0,
+        1 => 0.5,
+        _ => 0.0,
+    }
+}
+
+fn day_weight(a: usize, b: usize) -> f64 {
+    let weekend = |d: usize| d >= 5;
+    if a == b {
+        1.0
+    } else if weekend(a) == weekend(b) {
+        0.5
+    } else {
+        0.0
+    }
+}
+
+/// 解析 `+08:00` / `+0800` / `-5` / `0` 形式的时区偏移，返回秒
+pub fn parse_utc_offset(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let (sign, rest) = match s.as_bytes().first()? {
+        b'+' => (1, &s[1..]),
+        b'-' => (-1, &s[1..]),
+        _ => (1, s),
+    };
+    let digits: String = rest.chars().filter(|c| *c != ':').collect();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (h, m) = match digits.len() {
+        1 | 2 => (digits.parse::<i64>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i64>().ok()?,
+            digits[2..].parse::<i64>().ok()?,
+        ),
+        _ => return None,
+    };
+    if h > 14 || m >= 60 {
+        return None;
+    }
+    Some(sign * (h * 3600 + m * 60))
+}
+
+/// 本地时区偏移（秒）：`CDH_UTC_OFFSET`，否则 `date +%z`，都拿不到时按 UTC。
+/// 每个进程只算一次（`date` 是子进程，不在每次推荐时都起一个）
+pub fn local_utc_offset() -> i64 {
+    static OFFSET: OnceLock<i64> = OnceLock::new();
+    *OFFSET.get_or_init(|| {
+        if let Some(v) = std::env::var("CDH_UTC_OFFSET")
+            .ok()
+            .and_then(|s| parse_utc_offset(&s))
+        {
+            return v;
+        }
+        std::process::Command::new("date")
+            .arg("+%z")
+            .output()
+            .ok()
+            .and_then(|o| String::from_utf8(o.stdout).ok())
+            .and_then(|s| parse_utc_offset(&s))
+            .unwrap_or(0)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-01-01（周一）00:00 UTC
+    const MONDAY: i64 = 1_704_067_200;
+    const DAY: i64 = 86_400;
+    const HOUR: i64 = 3_600;
+
+    #[test]
+    fn week_hour_starts_on_monday() {
+        assert_eq!(week_hour(MONDAY), 0);
+        assert_eq!(week_hour(MONDAY + 5 * DAY + 15 * HOUR + 59), 5 * 24 + 15);
+        assert_eq!(week_hour(MONDAY - HOUR), WEEK_HOURS - 1);
+    }
+
+    #[test]
+    fn affinity_matches_hour_and_day() {
+        let mut p = TimePattern::default();
+        for week in 0..5 {
+            p.observe(MONDAY + week * 7 * DAY + 9 * HOUR);
+        }
+        let monday_9 = MONDAY + 9 * HOUR + 1800;
+        let tuesday_10 = MONDAY + DAY + 10 * HOUR;
+        let saturday_9 = MONDAY + 5 * DAY + 9 * HOUR;
+        assert!((p.affinity(monday_9, 0) - 0.5).abs() < 1e-9);
+        assert!((p.affinity(tuesday_10, 0) - 0.125).abs() < 1e-9);
+        assert_eq!(p.affinity(saturday_9, 0), 0.0);
+
+        // UTC 01:00 的访问在 +08:00 下是本地 09:00
+        let mut q = TimePattern::default();
+        for week in 0..5 {
+            q.observe(MONDAY + week * 7 * DAY + HOUR);
+        }
+        assert!((q.affinity(MONDAY + HOUR, 8 * HOUR) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn patterns_merge_and_round_trip_as_text() {
+        let mut p = TimePattern::default();
+        assert_eq!(p.to_string(), "-");
+        assert_eq!("-".parse::<TimePattern>(), Ok(p));
+        p.observe(MONDAY + 9 * HOUR);
+        p.observe(MONDAY + 9 * HOUR + 60);
+        let mut q = TimePattern::default();
+        q.observe(MONDAY + 6 * DAY + 23 * HOUR);
+        p.merge(&q);
+        assert_eq!(p.total(), 3);
+        assert_eq!(p.to_string(), "9:2,167:1");
+        assert_eq!("9:2,167:1".parse::<TimePattern>(), Ok(p));
+        assert!("168:1".parse::<TimePattern>().is_err());
+        assert!("9".parse::<TimePattern>().is_err());
+    }
+
+    #[test]
+    fn parse_offsets() {
+        assert_eq!(parse_utc_offset("+0800"), Some(8 * HOUR));
+        assert_eq!(parse_utc_offset("+05:30"), Some(5 * HOUR + 1800));
+        assert_eq!(parse_utc_offset("-5"), Some(-5 * HOUR));
+        assert_eq!(parse_utc_offset("0"), Some(0));
+        assert_eq!(parse_utc_offset("+99"), None);
+        assert_eq!(parse_utc_offset("CST"), None);
+    }
+}